@@ -181,7 +181,7 @@ export default function DashboardPage() {
       const paperId = await addPaper({
         folderId: selectedFolderId || 'default',
         title: searchResult.title,
-        author: searchResult.authors.map(a => a.name).join(', '),
+        author: searchResult.authors.map(a => a.name).join('; '),
         year: searchResult.year || undefined,
       });
 
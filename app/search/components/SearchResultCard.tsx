@@ -32,7 +32,7 @@ interface SearchResultCardProps {
 function formatAuthors(authors: SearchResult['authors']) {
   if (!authors || authors.length === 0) return 'Unknown authors';
   if (authors.length <= 2) {
-    return authors.map((a) => a.name).join(', ');
+    return authors.map((a) => a.name).join('; ');
   }
   return `${authors[0].name} et al.`;
 }
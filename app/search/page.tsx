@@ -2,8 +2,9 @@
 
 import { useState, useCallback, useMemo } from 'react';
 import { TooltipProvider } from '@/components/ui/tooltip';
-import { searchPapers, isTauri, createPaper } from '@/lib/tauri/commands';
+import { searchPapers, isTauri, createPaper, updatePaper } from '@/lib/tauri/commands';
 import type { SearchResult, SearchQuery, SearchSource } from '@/lib/tauri/types';
+import { isAppError } from '@/lib/tauri/types';
 import {
   type SortField,
   type SortDirection,
@@ -140,7 +141,7 @@ export default function SearchPage() {
         setTotalBySource(newTotalBySource);
       } catch (error) {
         console.error('Search failed:', error);
-        setSearchError(error instanceof Error ? error.message : 'Search failed');
+        setSearchError(isAppError(error) ? error.message : String(error));
         setResultsBySource({});
         setTotalBySource({});
       } finally {
@@ -156,13 +157,22 @@ export default function SearchPage() {
     setAddingPapers((prev) => new Set(prev).add(paper.paperId));
 
     try {
-      await createPaper({
+      const created = await createPaper({
         folderId: '',
         title: paper.title,
-        author: paper.authors?.map((a) => a.name).join(', ') || undefined,
+        author: paper.authors?.map((a) => a.name).join('; ') || undefined,
         year: paper.year || undefined,
       });
 
+      const url = paper.openAccessPdf?.url || paper.url;
+      const doi = paper.externalIds?.doi;
+      if (url || doi) {
+        await updatePaper(created.id, {
+          url: url || undefined,
+          doi: doi || undefined,
+        });
+      }
+
       setAddedPapers((prev) => new Set(prev).add(paper.paperId));
     } catch (error) {
       console.error('Failed to add paper:', error);
@@ -2,7 +2,7 @@
 
 import { useState, useEffect } from 'react';
 import { FileText, Loader2 } from 'lucide-react';
-import { isTauri } from '@/lib/tauri/commands';
+import { isTauri, resolvePdfFile } from '@/lib/tauri/commands';
 
 interface PDFViewerProps {
   pdfUrl?: string;
@@ -27,7 +27,7 @@ export function PDFViewer({ pdfUrl }: PDFViewerProps) {
         if (isTauri()) {
           // Convert local file path to asset URL for Tauri
           const { convertFileSrc } = await import('@tauri-apps/api/core');
-          const assetUrl = convertFileSrc(pdfUrl);
+          const assetUrl = convertFileSrc(await resolvePdfFile(pdfUrl));
           setDisplayUrl(assetUrl);
         } else {
           // For web, use the URL directly (if it's a remote URL)
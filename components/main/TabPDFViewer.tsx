@@ -15,7 +15,7 @@ import {
   Square,
 } from 'lucide-react';
 import { Document, Page, pdfjs } from 'react-pdf';
-import { isTauri, summarizeText, translateText } from '@/lib/tauri/commands';
+import { isTauri, resolvePdfFile, summarizeText, translateText } from '@/lib/tauri/commands';
 import { cn } from '@/lib/utils';
 import { useHighlights } from '@/hooks/useHighlights';
 import { useContainerWidth } from '@/hooks/useContainerWidth';
@@ -24,6 +24,7 @@ import { HighlightPopup } from '@/components/pdf/HighlightPopup';
 import { PDFContextMenu } from '@/components/pdf/PDFContextMenu';
 import { AIResultPopup } from '@/components/pdf/AIResultPopup';
 import type { Highlight, HighlightRect, CreateHighlightInput } from '@/lib/tauri/types';
+import { isAppError } from '@/lib/tauri/types';
 
 import 'react-pdf/dist/Page/AnnotationLayer.css';
 import 'react-pdf/dist/Page/TextLayer.css';
@@ -143,7 +144,7 @@ export function TabPDFViewer({ selectedPaper }: TabPDFViewerProps) {
 
     try {
       const { convertFileSrc } = await import('@tauri-apps/api/core');
-      const assetUrl = convertFileSrc(pdfPath);
+      const assetUrl = convertFileSrc(await resolvePdfFile(pdfPath));
       setDisplayUrls(prev => ({ ...prev, [tabId]: assetUrl }));
       // Initialize page and scale
       setCurrentPage(prev => ({ ...prev, [tabId]: 1 }));
@@ -457,7 +458,7 @@ export function TabPDFViewer({ selectedPaper }: TabPDFViewerProps) {
       setAiPopup(prev => prev ? {
         ...prev,
         isLoading: false,
-        error: err instanceof Error ? err.message : 'AI 요약 중 오류가 발생했습니다.',
+        error: isAppError(err) ? err.message : String(err),
       } : null);
     }
   }, [contextMenu]);
@@ -484,7 +485,7 @@ export function TabPDFViewer({ selectedPaper }: TabPDFViewerProps) {
       setAiPopup(prev => prev ? {
         ...prev,
         isLoading: false,
-        error: err instanceof Error ? err.message : 'AI 번역 중 오류가 발생했습니다.',
+        error: isAppError(err) ? err.message : String(err),
       } : null);
     }
   }, [contextMenu]);
@@ -502,7 +503,7 @@ export function TabPDFViewer({ selectedPaper }: TabPDFViewerProps) {
       setAiPopup(prev => prev ? {
         ...prev,
         isLoading: false,
-        error: err instanceof Error ? err.message : 'AI 번역 중 오류가 발생했습니다.',
+        error: isAppError(err) ? err.message : String(err),
       } : null);
     }
   }, [aiPopup]);
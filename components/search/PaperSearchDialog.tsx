@@ -131,7 +131,7 @@ export function PaperSearchDialog({
   const formatAuthors = (authors: SearchResult['authors']) => {
     if (!authors || authors.length === 0) return 'Unknown authors';
     if (authors.length <= 3) {
-      return authors.map((a) => a.name).join(', ');
+      return authors.map((a) => a.name).join('; ');
     }
     return `${authors[0].name} et al.`;
   };
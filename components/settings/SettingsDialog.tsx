@@ -20,6 +20,7 @@ import { Separator } from '@/components/ui/separator';
 import { Eye, EyeOff, FolderOpen, Check, Loader2, ExternalLink, CloudUpload, CloudDownload, RefreshCw } from 'lucide-react';
 import {
   getSettings,
+  getSetting,
   setSetting,
   isTauri,
   openDirectoryDialog,
@@ -90,6 +91,24 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
     defaultFontSize: null,
     storagePath: null,
     googleAccountEmail: null,
+    httpTimeoutSecs: null,
+    httpProxy: null,
+    contactEmail: null,
+    autoBackupIntervalHours: null,
+    autoAnalyzeDelaySecs: null,
+    serpapiKey: null,
+    renamePattern: null,
+    renameMaxTitleLength: null,
+    renameSpaceReplacement: null,
+    renameLowercase: null,
+    autoRenameAfterAnalysis: null,
+    ftsTokenizer: null,
+    indexingConcurrency: null,
+    bibtexCustomFields: null,
+    analysisTemperature: null,
+    textTemperature: null,
+    analysisMaxOutputTokens: null,
+    defaultCitationStyle: null,
   });
   const [loading, setLoading] = useState(false);
   const [showGeminiKey, setShowGeminiKey] = useState(false);
@@ -166,7 +185,8 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
       // Convert camelCase key to snake_case for database
       const dbKey = toSnakeCase(key);
       await setSetting(dbKey, value);
-      setSettings(prev => ({ ...prev, [key]: value || null }));
+      const parsed = key === 'defaultFontSize' ? Number(value) : value || null;
+      setSettings(prev => ({ ...prev, [key]: parsed }));
 
       // Apply font settings immediately
       if (key === 'defaultFontFamily') {
@@ -201,6 +221,24 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
     }
   };
 
+  // Settings listings only carry masked secrets, so fetch the full value before revealing it
+  const toggleRevealKey = async (
+    key: 'gemini_api_key' | 'openai_api_key',
+    field: 'geminiApiKey' | 'openaiApiKey',
+    shown: boolean,
+    setShown: (shown: boolean) => void,
+  ) => {
+    if (!shown && isTauri()) {
+      try {
+        const value = await getSetting(key);
+        setSettings(prev => ({ ...prev, [field]: value }));
+      } catch (error) {
+        console.error('Failed to load setting:', error);
+      }
+    }
+    setShown(!shown);
+  };
+
   const maskApiKey = (key: string | null): string => {
     if (!key) return '';
     if (key.length <= 8) return '*'.repeat(key.length);
@@ -308,7 +346,7 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
                 <div className="space-y-2">
                   <label className="text-sm text-stone-600">Font Size</label>
                   <Select
-                    value={settings.defaultFontSize || '14'}
+                    value={String(settings.defaultFontSize ?? 14)}
                     onValueChange={(value) => handleSaveSetting('defaultFontSize', value)}
                   >
                     <SelectTrigger className="w-full">
@@ -355,7 +393,7 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
                         type="button"
                         variant="ghost"
                         size="icon-sm"
-                        onClick={() => setShowGeminiKey(!showGeminiKey)}
+                        onClick={() => toggleRevealKey('gemini_api_key', 'geminiApiKey', showGeminiKey, setShowGeminiKey)}
                       >
                         {showGeminiKey ? (
                           <EyeOff className="w-4 h-4 text-stone-400" />
@@ -390,7 +428,7 @@ export function SettingsDialog({ open, onOpenChange }: SettingsDialogProps) {
                         type="button"
                         variant="ghost"
                         size="icon-sm"
-                        onClick={() => setShowOpenaiKey(!showOpenaiKey)}
+                        onClick={() => toggleRevealKey('openai_api_key', 'openaiApiKey', showOpenaiKey, setShowOpenaiKey)}
                       >
                         {showOpenaiKey ? (
                           <EyeOff className="w-4 h-4 text-stone-400" />
@@ -2,6 +2,7 @@
 
 import { useState, useEffect } from 'react';
 import type { Paper } from '@/lib/tauri/types';
+import { isAppError } from '@/lib/tauri/types';
 import { FileText, Sparkles, StickyNote, Tag, Star } from 'lucide-react';
 import { Button } from '@/components/ui/button';
 import { Input } from '@/components/ui/input';
@@ -96,7 +97,7 @@ export function MetadataPanel({ paper, aiEnabled, allTags = [], onUpdate }: Meta
       alert('AI 분석이 완료되었습니다.');
     } catch (error: unknown) {
       console.error('Analysis error:', error);
-      alert(`분석 실패: ${error instanceof Error ? error.message : String(error)}`);
+      alert(`분석 실패: ${error instanceof Error || isAppError(error) ? error.message : String(error)}`);
     } finally {
       setIsAnalyzing(false);
     }
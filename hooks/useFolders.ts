@@ -113,6 +113,8 @@ export function useFolders(options: UseFoldersOptions = {}): UseFoldersReturn {
       const newFolder: Folder = {
         id: `folder-${Date.now()}`,
         ...input,
+        defaultImportance: input.defaultImportance ?? null,
+        defaultTags: input.defaultTags ?? [],
         sortOrder: folders.filter((f) => f.topicId === input.topicId).length,
         createdAt: new Date().toISOString(),
         updatedAt: new Date().toISOString(),
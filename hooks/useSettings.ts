@@ -8,9 +8,27 @@ const defaultSettings: AppSettings = {
   geminiApiKey: null,
   openaiApiKey: null,
   defaultFontFamily: 'system',
-  defaultFontSize: '14',
+  defaultFontSize: 14,
   storagePath: null,
   googleAccountEmail: null,
+  httpTimeoutSecs: null,
+  httpProxy: null,
+  contactEmail: null,
+  autoBackupIntervalHours: null,
+  autoAnalyzeDelaySecs: null,
+  serpapiKey: null,
+  renamePattern: null,
+  renameMaxTitleLength: null,
+  renameSpaceReplacement: null,
+  renameLowercase: null,
+  autoRenameAfterAnalysis: null,
+  ftsTokenizer: null,
+  indexingConcurrency: null,
+  bibtexCustomFields: null,
+  analysisTemperature: null,
+  textTemperature: null,
+  analysisMaxOutputTokens: null,
+  defaultCitationStyle: null,
 };
 
 // Font family CSS mapping
@@ -43,7 +61,7 @@ export function useSettings() {
   }, []);
 
   // Apply font settings to document
-  const applyFontSettings = useCallback((fontFamily: string | null, fontSize: string | null) => {
+  const applyFontSettings = useCallback((fontFamily: string | null, fontSize: number | null) => {
     const root = document.documentElement;
 
     // Apply font family
@@ -51,7 +69,7 @@ export function useSettings() {
     root.style.setProperty('--app-font-family', fontValue);
 
     // Apply font size
-    const sizeValue = fontSize || '14';
+    const sizeValue = fontSize ?? 14;
     root.style.setProperty('--app-font-size', `${sizeValue}px`);
   }, []);
 
@@ -8,6 +8,7 @@ import type {
   UpdateWritingDocumentInput,
   MoveWritingDocumentInput,
 } from '@/lib/tauri/types';
+import { isAppError } from '@/lib/tauri/types';
 import {
   isTauri,
   getWritingDocuments,
@@ -39,7 +40,7 @@ export function useWritingDocuments(projectId: string | null) {
       setError(null);
     } catch (err) {
       console.error('Failed to fetch writing documents:', err);
-      setError(err instanceof Error ? err : new Error(String(err)));
+      setError(new Error(isAppError(err) ? err.message : String(err)));
     } finally {
       setLoading(false);
     }
@@ -3,6 +3,7 @@
 import { useState, useEffect, useCallback } from 'react';
 import { listen } from '@tauri-apps/api/event';
 import type { WritingProject, CreateWritingProjectInput, UpdateWritingProjectInput } from '@/lib/tauri/types';
+import { isAppError } from '@/lib/tauri/types';
 import {
   isTauri,
   getWritingProjects,
@@ -29,7 +30,7 @@ export function useWritingProjects() {
       setError(null);
     } catch (err) {
       console.error('Failed to fetch writing projects:', err);
-      setError(err instanceof Error ? err : new Error(String(err)));
+      setError(new Error(isAppError(err) ? err.message : String(err)));
     } finally {
       setLoading(false);
     }
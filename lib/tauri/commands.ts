@@ -10,29 +10,44 @@ import type {
   UpdateFolderInput,
   CreatePaperInput,
   UpdatePaperInput,
+  PdfInfo,
   AppSettings,
+  Diagnostics,
+  PaperLinkReport,
   GoogleTokens,
   SearchQuery,
   SearchResponse,
+  SearchSourceInfo,
+  SearchHistoryEntry,
+  SearchSource,
+  MultiSearchResponse,
   SearchResult,
   DriveFile,
+  DriveBackupInfo,
   SyncStatus,
   Highlight,
   CreateHighlightInput,
   UpdateHighlightInput,
   FullTextSearchQuery,
   FullTextSearchResponse,
+  FtsRebuildReport,
+  IndexingProgress,
   IndexingStatus,
   CitationStyle,
   CitationExport,
+  BibtexEntryType,
   BatchCitationExport,
   SmartGroup,
   SmartGroupCriteria,
+  CriteriaGroup,
   CreateSmartGroupInput,
   WatchFolder,
   CreateWatchFolderInput,
+  WatchFolderImport,
+  WatchImportResult,
   RenameConfig,
   RenameResult,
+  UndoResult,
   WritingProject,
   WritingDocument,
   CreateWritingProjectInput,
@@ -97,8 +112,19 @@ export const createPaper = (input: CreatePaperInput): Promise<Paper> =>
 export const updatePaper = (paperId: string, input: UpdatePaperInput): Promise<Paper> =>
   invoke('update_paper', { paperId, input });
 
-export const deletePaper = (paperId: string): Promise<void> =>
-  invoke('delete_paper', { paperId });
+// Moves the paper to the trash unless `permanent` is set
+export const deletePaper = (paperId: string, permanent?: boolean): Promise<void> =>
+  invoke('delete_paper', { paperId, permanent });
+
+export const restorePaper = (paperId: string): Promise<Paper> =>
+  invoke('restore_paper', { paperId });
+
+export const getTrashedPapers = (): Promise<Paper[]> =>
+  invoke('get_trashed_papers');
+
+// Returns the number of papers permanently deleted
+export const emptyTrash = (): Promise<number> =>
+  invoke('empty_trash');
 
 export const checkDuplicate = (title: string): Promise<boolean> =>
   invoke('check_duplicate', { title });
@@ -112,18 +138,81 @@ export const batchUpdatePapers = (
 export const batchDeletePapers = (paperIds: string[]): Promise<void> =>
   invoke('batch_delete_papers', { paperIds });
 
+export const batchSetRead = (paperIds: string[], isRead: boolean): Promise<number> =>
+  invoke('batch_set_read', { paperIds, isRead });
+
+// importance: 0 (unrated) to 5
+export const batchSetImportance = (paperIds: string[], importance: number): Promise<number> =>
+  invoke('batch_set_importance', { paperIds, importance });
+
+// Library papers by an author, matched across "Last, First" and "First Last" formats
+export const getPapersByAuthor = (name: string): Promise<Paper[]> =>
+  invoke('get_papers_by_author', { name });
+
+// Tags for a paper, best first, spelled as they already appear in the library
+export const suggestTags = (paperId: string, limit?: number): Promise<string[]> =>
+  invoke('suggest_tags', { paperId, limit });
+
+// Reverses the latest batch rename or batch delete; null when there is nothing to undo
+export const undoLastOperation = (): Promise<UndoResult | null> =>
+  invoke('undo_last_operation');
+
 // PDF
 export const importPdf = (sourcePath: string, paperId: string): Promise<string> =>
   invoke('import_pdf', { sourcePath, paperId });
 
+// Progress arrives as 'import-directory-progress' events and the counts as 'import-directory-done'
+export const importDirectory = (
+  dir: string,
+  folderId: string,
+  recursive: boolean,
+  index?: boolean,
+  operationId?: string
+): Promise<Paper[]> =>
+  invoke('import_directory', { dir, folderId, recursive, index, operationId });
+
 export const getPdfAsBase64 = (pdfPath: string): Promise<string> =>
   invoke('get_pdf_as_base64', { pdfPath });
 
+// PDFs above this size should be streamed with getPdfByteRange (matches the backend cutoff)
+export const PDF_STREAMING_THRESHOLD_BYTES = 10 * 1024 * 1024;
+
+export const getPdfSize = (pdfPath: string): Promise<number> =>
+  invoke('get_pdf_size', { pdfPath });
+
+// hasTextLayer is false for scanned PDFs that need OCR before they are searchable
+export const getPdfInfo = (paperId: string): Promise<PdfInfo> =>
+  invoke('get_pdf_info', { paperId });
+
+export const getPdfByteRange = (pdfPath: string, start: number, end: number): Promise<ArrayBuffer> =>
+  invoke('get_pdf_byte_range', { pdfPath, start, end });
+
+// Stored PDF paths may be relative to the app data dir; resolve before using the asset protocol
+export const resolvePdfFile = (pdfPath: string): Promise<string> =>
+  invoke('resolve_pdf_file', { pdfPath });
+
 export const deletePdf = (pdfPath: string): Promise<void> =>
   invoke('delete_pdf', { pdfPath });
 
 export const getPdfStoragePath = (): Promise<string> => invoke('get_pdf_storage_path');
 
+// Concatenates the papers' PDFs in order into a new paper; the originals are kept
+export const mergePdfs = (
+  paperIds: string[],
+  outputTitle: string,
+  folderId: string
+): Promise<Paper> => invoke('merge_pdfs', { paperIds, outputTitle, folderId });
+
+// Copies pages startPage..endPage (1-based, inclusive) into a new, indexed paper
+export const extractPdfPages = (
+  paperId: string,
+  startPage: number,
+  endPage: number,
+  newTitle: string,
+  folderId: string
+): Promise<Paper> =>
+  invoke('extract_pdf_pages', { paperId, startPage, endPage, newTitle, folderId });
+
 // Settings
 export const getSettings = (): Promise<AppSettings> => invoke('get_settings');
 
@@ -139,6 +228,18 @@ export const updateSettings = (settings: Record<string, string>): Promise<void>
 export const deleteSetting = (key: string): Promise<void> =>
   invoke('delete_setting', { key });
 
+export const getDiagnostics = (): Promise<Diagnostics> => invoke('get_diagnostics');
+
+// Links (with resolve, dead links are replaced by where the DOI now leads)
+export const validatePaperLinks = (paperId: string, resolve?: boolean): Promise<PaperLinkReport> =>
+  invoke('validate_paper_links', { paperId, resolve });
+
+export const validateAllLinks = (
+  resolve?: boolean,
+  operationId?: string
+): Promise<PaperLinkReport[]> =>
+  invoke('validate_all_links', { resolve, operationId });
+
 // File Dialog
 export const openPdfDialog = async (): Promise<string[] | null> => {
   const result = await open({
@@ -187,19 +288,38 @@ export const refreshGoogleToken = (): Promise<GoogleTokens> =>
 export const revokeGoogleTokens = (): Promise<void> =>
   invoke('revoke_google_tokens');
 
-export const startOAuthServer = (): Promise<void> =>
+export const startOAuthServer = (): Promise<number> =>
   invoke('start_oauth_server');
 
 // Paper Search (Semantic Scholar)
 export const searchPapers = (query: SearchQuery): Promise<SearchResponse> =>
   invoke('search_papers', { query });
 
+export const searchMultipleSources = (
+  query: SearchQuery,
+  sources?: SearchSource[]
+): Promise<MultiSearchResponse> =>
+  invoke('search_multiple_sources', { query, sources });
+
+export const getSearchSources = (): Promise<SearchSourceInfo[]> =>
+  invoke('get_search_sources');
+
+// Recent searchPapers calls, newest first (20 by default, at most 200)
+export const getSearchHistory = (limit?: number): Promise<SearchHistoryEntry[]> =>
+  invoke('get_search_history', { limit });
+
+export const clearSearchHistory = (): Promise<void> =>
+  invoke('clear_search_history');
+
 export const getPaperDetails = (paperId: string): Promise<SearchResult> =>
   invoke('get_paper_details', { paperId });
 
 export const searchByDoi = (doi: string): Promise<SearchResult> =>
   invoke('search_by_doi', { doi });
 
+export const enrichPaperFromDoi = (paperId: string, doi: string): Promise<Paper> =>
+  invoke('enrich_paper_from_doi', { paperId, doi });
+
 export const searchByArxiv = (arxivId: string): Promise<SearchResult> =>
   invoke('search_by_arxiv', { arxivId });
 
@@ -209,10 +329,26 @@ export const getPaperRecommendations = (
 ): Promise<SearchResult[]> =>
   invoke('get_paper_recommendations', { paperId, limit });
 
+// Snowballing: papers this one cites, and papers citing it (Semantic Scholar IDs or DOI:...)
+export const getPaperReferences = (
+  paperId: string,
+  limit?: number,
+  offset?: number
+): Promise<SearchResult[]> =>
+  invoke('get_paper_references', { paperId, limit, offset });
+
+export const getPaperCitations = (
+  paperId: string,
+  limit?: number,
+  offset?: number
+): Promise<SearchResult[]> =>
+  invoke('get_paper_citations', { paperId, limit, offset });
+
 // Google Drive
 export const backupToDrive = (): Promise<string> =>
   invoke('backup_to_drive');
 
+// Emits 'database-restored' once the restored database is open
 export const restoreFromDrive = (): Promise<void> =>
   invoke('restore_from_drive');
 
@@ -222,6 +358,9 @@ export const getSyncStatus = (): Promise<SyncStatus> =>
 export const listDriveFiles = (): Promise<DriveFile[]> =>
   invoke('list_drive_files');
 
+export const describeDriveBackup = (fileId: string): Promise<DriveBackupInfo> =>
+  invoke('describe_drive_backup', { fileId });
+
 // AI Analysis
 export interface AnalysisResult {
   keywords?: string;
@@ -246,15 +385,63 @@ export interface AnalysisResult {
   futurePlans?: string[];
 }
 
-export const analyzePaper = (paperId: string): Promise<AnalysisResult> =>
-  invoke('analyze_paper', { paperId });
+export interface PaperAnswer {
+  answer: string;
+  pages: number[];
+}
+
+export interface PaperComparison {
+  paperIdA: string;
+  paperIdB: string;
+  summary: string;
+  sharedMethods: string[];
+  differingMethods: string[];
+  differingVariables: string[];
+  sharedFindings: string[];
+  contrastingResults: string[];
+}
+
+// Pass the password after a 'pdf_encrypted' error; it is not stored
+export const analyzePaper = (paperId: string, password?: string): Promise<AnalysisResult> =>
+  invoke('analyze_paper', { paperId, password });
 
 export const summarizeText = (text: string): Promise<string> =>
   invoke('summarize_text', { text });
 
+export const summarizePaper = (paperId: string): Promise<string> =>
+  invoke('summarize_paper', { paperId });
+
+export const askPaper = (paperId: string, question: string): Promise<PaperAnswer> =>
+  invoke('ask_paper', { paperId, question });
+
+export const comparePapers = (paperIdA: string, paperIdB: string): Promise<PaperComparison> =>
+  invoke('compare_papers', { paperIdA, paperIdB });
+
 export const translateText = (text: string, targetLang: string): Promise<string> =>
   invoke('translate_text', { text, targetLang });
 
+// Key phrases from the indexed full text, without AI; `save` replaces the paper's keywords
+export const extractKeywords = (
+  paperId: string,
+  max?: number,
+  save?: boolean
+): Promise<string[]> =>
+  invoke('extract_keywords', { paperId, max, save });
+
+export interface AbstractTranslation {
+  // ISO 639-3 code, e.g. 'kor'
+  detectedLanguage: string | null;
+  // False when the abstract was already in the target language
+  translated: boolean;
+  paper: Paper;
+}
+
+export const detectAndTranslateAbstract = (
+  paperId: string,
+  targetLang: string
+): Promise<AbstractTranslation> =>
+  invoke('detect_and_translate_abstract', { paperId, targetLang });
+
 // Highlights
 export const getHighlights = (
   paperId: string,
@@ -262,6 +449,10 @@ export const getHighlights = (
 ): Promise<Highlight[]> =>
   invoke('get_highlights', { paperId, pageNumber });
 
+// Highlight count per paper id, papers without highlights included as 0
+export const getHighlightCounts = (paperIds: string[]): Promise<Record<string, number>> =>
+  invoke('get_highlight_counts', { paperIds });
+
 export const getHighlight = (highlightId: string): Promise<Highlight> =>
   invoke('get_highlight', { highlightId });
 
@@ -281,21 +472,39 @@ export const deleteHighlight = (highlightId: string): Promise<void> =>
 export const searchFullText = (query: FullTextSearchQuery): Promise<FullTextSearchResponse> =>
   invoke('search_full_text', { query });
 
-export const indexPaper = (paperId: string): Promise<IndexingStatus> =>
-  invoke('index_paper', { paperId });
+export const indexPaper = (paperId: string, password?: string): Promise<IndexingStatus> =>
+  invoke('index_paper', { paperId, password });
 
 export const indexAllPapers = (): Promise<IndexingStatus[]> =>
   invoke('index_all_papers');
 
+// Recreates the index with the ftsTokenizer setting; returns the tokenizer in use
+export const rebuildSearchIndex = (): Promise<'unicode61' | 'trigram'> =>
+  invoke('rebuild_search_index');
+
+// Repairs the index in place after a restore or manual DB edit
+export const rebuildFtsIndex = (): Promise<FtsRebuildReport> => invoke('rebuild_fts_index');
+
+export const getIndexingProgress = (): Promise<IndexingProgress> =>
+  invoke('get_indexing_progress');
+
 export const getPaperIndexStatus = (paperId: string): Promise<boolean> =>
   invoke('get_paper_index_status', { paperId });
 
 // Citations
-export const exportBibtex = (paperId: string): Promise<CitationExport> =>
-  invoke('export_bibtex', { paperId });
+// entryType defaults to 'article'; a paper's own bibtexType takes precedence
+export const exportBibtex = (paperId: string, entryType?: BibtexEntryType): Promise<CitationExport> =>
+  invoke('export_bibtex', { paperId, entryType });
+
+export const exportBibtexBatch = (
+  paperIds: string[],
+  entryType?: BibtexEntryType
+): Promise<BatchCitationExport> =>
+  invoke('export_bibtex_batch', { paperIds, entryType });
 
-export const exportBibtexBatch = (paperIds: string[]): Promise<BatchCitationExport> =>
-  invoke('export_bibtex_batch', { paperIds });
+// Replaces the stored key with one generated from the current author and year
+export const regenerateCitationKey = (paperId: string): Promise<Paper> =>
+  invoke('regenerate_citation_key', { paperId });
 
 export const exportRis = (paperId: string): Promise<CitationExport> =>
   invoke('export_ris', { paperId });
@@ -303,21 +512,55 @@ export const exportRis = (paperId: string): Promise<CitationExport> =>
 export const exportRisBatch = (paperIds: string[]): Promise<BatchCitationExport> =>
   invoke('export_ris_batch', { paperIds });
 
+export const exportPapersCsv = (folderId?: string): Promise<string> =>
+  invoke('export_papers_csv', { folderId });
+
+export const importBibtex = (content: string, folderId: string): Promise<Paper[]> =>
+  invoke('import_bibtex', { content, folderId });
+
+export const importRis = (content: string, folderId: string): Promise<Paper[]> =>
+  invoke('import_ris', { content, folderId });
+
+// Without a style, the default citation style is used
 export const generateCitation = (
   paperId: string,
-  style: CitationStyle
+  style?: CitationStyle
 ): Promise<CitationExport> =>
   invoke('generate_citation', { paperId, style });
 
 export const generateCitationBatch = (
   paperIds: string[],
-  style: CitationStyle
+  style?: CitationStyle
 ): Promise<BatchCitationExport> =>
   invoke('generate_citation_batch', { paperIds, style });
 
+// Markdown: each citation followed by the paper's notes and highlights
+export const exportAnnotatedBibliography = (
+  paperIds: string[],
+  style: CitationStyle
+): Promise<string> =>
+  invoke('export_annotated_bibliography', { paperIds, style });
+
 export const getCitationStyles = (): Promise<string[]> =>
   invoke('get_citation_styles');
 
+export const getDefaultCitationStyle = (): Promise<CitationStyle> =>
+  invoke('get_default_citation_style');
+
+export const setDefaultCitationStyle = (style: CitationStyle): Promise<void> =>
+  invoke('set_default_citation_style', { style });
+
+// Markdown export
+export const exportPaperMarkdown = (paperId: string): Promise<string> =>
+  invoke('export_paper_markdown', { paperId });
+
+/**
+ * Write one Markdown note per paper into a folder (e.g. an Obsidian vault),
+ * wiki-linking papers that cite each other. Returns the number of notes written.
+ */
+export const exportLibraryObsidian = (dir: string): Promise<number> =>
+  invoke('export_library_obsidian', { dir });
+
 // ============================================================================
 // Automation - Smart Groups
 // ============================================================================
@@ -326,12 +569,22 @@ export const getCitationStyles = (): Promise<string[]> =>
  * Get papers matching smart group criteria.
  * @param criteria - Array of criteria to match papers against
  * @param matchMode - How to combine criteria: "and" (all must match) or "or" (any must match)
+ * @param subgroups - Nested criteria groups, combined with the top-level criteria using matchMode
+ * @param scopeFolderIds - Only consider papers in these folders (default: the whole library)
  */
 export const getSmartGroupPapers = (
   criteria: SmartGroupCriteria[],
-  matchMode?: string
+  matchMode?: string,
+  subgroups?: CriteriaGroup[],
+  scopeFolderIds?: string[]
 ): Promise<Paper[]> =>
-  invoke('get_smart_group_papers', { criteria, matchMode });
+  invoke('get_smart_group_papers', { criteria, matchMode, subgroups, scopeFolderIds });
+
+/**
+ * Count papers in a saved or predefined smart group without loading them.
+ */
+export const getSmartGroupCount = (groupId: string): Promise<number> =>
+  invoke('get_smart_group_count', { groupId });
 
 /**
  * Get predefined smart groups (built-in groups like "Unread", "Favorites", etc.).
@@ -388,6 +641,16 @@ export const toggleWatchFolder = (
 ): Promise<WatchFolder> =>
   invoke('toggle_watch_folder', { watchFolderId, isActive });
 
+/**
+ * Set what happens to a watch folder's files after import.
+ * @param postImportAction - "leave", "delete", or "move_to:<dir>" (relative to the watch folder or absolute)
+ */
+export const setWatchFolderPostImportAction = (
+  watchFolderId: string,
+  postImportAction: string
+): Promise<WatchFolder> =>
+  invoke('set_watch_folder_post_import_action', { watchFolderId, postImportAction });
+
 /**
  * Start watching a folder for new PDFs.
  * Emits 'watch-folder-event' when new PDFs are detected.
@@ -402,22 +665,37 @@ export const stopWatching = (watchFolderId: string): Promise<void> =>
   invoke('stop_watching', { watchFolderId });
 
 /**
- * Scan a watch folder for existing PDFs.
+ * Scan a watch folder for PDFs that haven't been imported from it yet.
  * Returns array of PDF file paths.
+ * @param includeImported - Also return files the folder already imported
  */
-export const scanWatchFolder = (watchFolderId: string): Promise<string[]> =>
-  invoke('scan_watch_folder', { watchFolderId });
+export const scanWatchFolder = (
+  watchFolderId: string,
+  includeImported?: boolean
+): Promise<string[]> =>
+  invoke('scan_watch_folder', { watchFolderId, includeImported });
 
 /**
  * Import a PDF from a watch folder.
  * Creates a paper entry and copies the PDF to storage.
+ * A file already in the library (same file, content or title) is skipped with
+ * status 'already_imported' and the existing paper when known.
  */
 export const importFromWatchFolder = (
   watchFolderId: string,
   filePath: string
-): Promise<Paper> =>
+): Promise<WatchImportResult> =>
   invoke('import_from_watch_folder', { watchFolderId, filePath });
 
+/**
+ * Get a watch folder's most recent imports, newest first.
+ */
+export const getWatchFolderHistory = (
+  watchFolderId: string,
+  limit?: number
+): Promise<WatchFolderImport[]> =>
+  invoke('get_watch_folder_history', { watchFolderId, limit });
+
 // ============================================================================
 // Automation - PDF Auto-Rename
 // ============================================================================
@@ -471,6 +749,17 @@ export const previewRename = (
 ): Promise<RenameResult> =>
   invoke('preview_rename', { paperId, config });
 
+/**
+ * Copy papers' PDFs into a directory, optionally named with the saved rename pattern.
+ * Papers without a PDF come back with success: false.
+ */
+export const exportPdfs = (
+  paperIds: string[],
+  destinationDir: string,
+  useRenamePattern: boolean
+): Promise<RenameResult[]> =>
+  invoke('export_pdfs', { paperIds, destinationDir, useRenamePattern });
+
 // ============================================================================
 // Writing - Projects
 // ============================================================================
@@ -564,12 +853,26 @@ export const moveWritingDocument = (
 ): Promise<WritingDocument> =>
   invoke('move_writing_document', { documentId, input });
 
+// Appends the highlight as a blockquote and records the link
+export const insertHighlightIntoDocument = (
+  documentId: string,
+  highlightId: string
+): Promise<WritingDocument> =>
+  invoke('insert_highlight_into_document', { documentId, highlightId });
+
+export const getHighlightDocuments = (highlightId: string): Promise<WritingDocument[]> =>
+  invoke('get_highlight_documents', { highlightId });
+
 // ============================================================================
 // Writing - Export
 // ============================================================================
 
 /**
- * Export a writing project as Markdown.
+ * Export a writing project as Markdown. `[@citekey]` citations are expanded in
+ * the given style (APA by default) and a References section is appended.
  */
-export const exportProjectMarkdown = (projectId: string): Promise<string> =>
-  invoke('export_project_markdown', { projectId });
+export const exportProjectMarkdown = (
+  projectId: string,
+  citationStyle?: CitationStyle
+): Promise<string> =>
+  invoke('export_project_markdown', { projectId, citationStyle });
@@ -31,6 +31,8 @@ export interface Folder {
   topicId: string;
   name: string;
   sortOrder: number;
+  defaultImportance: number | null;
+  defaultTags: string[];
   createdAt: string;
   updatedAt: string;
 }
@@ -38,14 +40,34 @@ export interface Folder {
 export interface CreateFolderInput {
   topicId: string;
   name: string;
+  defaultImportance?: number;
+  defaultTags?: string[];
 }
 
 export interface UpdateFolderInput {
   name?: string;
   sortOrder?: number;
+  // 0 clears the default
+  defaultImportance?: number;
+  defaultTags?: string[];
 }
 
 // Paper Types
+export type BibtexEntryType =
+  | 'article'
+  | 'book'
+  | 'booklet'
+  | 'inbook'
+  | 'incollection'
+  | 'inproceedings'
+  | 'manual'
+  | 'mastersthesis'
+  | 'misc'
+  | 'phdthesis'
+  | 'proceedings'
+  | 'techreport'
+  | 'unpublished';
+
 export interface Paper {
   id: string;
   folderId: string;
@@ -58,6 +80,15 @@ export interface Paper {
   title: string;
   publisher: string;
   subject: string;
+  // Translation of the abstract (or subject) from detectAndTranslateAbstract
+  subjectTranslated: string;
+  doi: string;
+  abstract: string;
+  url: string;
+  // Empty uses the export default
+  bibtexType: BibtexEntryType | '';
+  // Kept once assigned; empty until the paper has an author/year or is first exported
+  citationKey: string;
 
   // Research design
   purposes: string[];
@@ -90,11 +121,15 @@ export interface Paper {
   tags: string[];
   isRead: boolean;
   importance: number;
+  // Counted when the paper is loaded
+  highlightCount: number;
 
   // Timestamps
   createdAt: string;
   updatedAt: string;
   lastAnalyzedAt: string | null;
+  lastOpenedAt: string | null;
+  deletedAt: string | null;
 }
 
 export interface CreatePaperInput {
@@ -114,6 +149,10 @@ export interface UpdatePaperInput {
   title?: string;
   publisher?: string;
   subject?: string;
+  doi?: string;
+  abstract?: string;
+  url?: string;
+  bibtexType?: BibtexEntryType | '';
   purposes?: string[];
   isQualitative?: boolean;
   isQuantitative?: boolean;
@@ -142,9 +181,57 @@ export interface AppSettings {
   geminiApiKey: string | null;
   openaiApiKey: string | null;
   defaultFontFamily: string | null;
-  defaultFontSize: string | null;
+  defaultFontSize: number | null;
   storagePath: string | null;
   googleAccountEmail: string | null;
+  httpTimeoutSecs: number | null;
+  httpProxy: string | null;
+  contactEmail: string | null;
+  autoBackupIntervalHours: number | null;
+  autoAnalyzeDelaySecs: number | null;
+  serpapiKey: string | null;
+  renamePattern: string | null;
+  renameMaxTitleLength: number | null;
+  renameSpaceReplacement: string | null;
+  renameLowercase: boolean | null;
+  autoRenameAfterAnalysis: boolean | null;
+  ftsTokenizer: 'unicode61' | 'trigram' | null;
+  indexingConcurrency: number | null;
+  // One `name = value` line per extra BibTeX field, e.g. `url = {url}`
+  bibtexCustomFields: string | null;
+  // Gemini sampling temperature, 0-2
+  analysisTemperature: number | null;
+  textTemperature: number | null;
+  analysisMaxOutputTokens: number | null;
+  defaultCitationStyle: CitationStyle | null;
+}
+
+export interface LinkCheck {
+  url: string;
+  kind: 'url' | 'doi';
+  status: number | null;
+  alive: boolean;
+  error: string | null;
+}
+
+export interface PaperLinkReport {
+  paperId: string;
+  title: string;
+  links: LinkCheck[];
+  resolvedUrl: string | null;
+}
+
+export interface Diagnostics {
+  dbPath: string;
+  dbSizeBytes: number;
+  schemaVersion: number;
+  paperCount: number;
+  highlightCount: number;
+  indexedPageCount: number;
+  googleConnected: boolean;
+  geminiKeySet: boolean;
+  pdfDir: string;
+  pdfDirSizeBytes: number;
 }
 
 // Google OAuth Types
@@ -184,6 +271,7 @@ export interface SearchResult {
   url: string | null;
   openAccessPdf: OpenAccessPdf | null;
   externalIds: ExternalIds | null;
+  tldr?: string | null;
 }
 
 export type SearchSource =
@@ -201,6 +289,12 @@ export interface SearchQuery {
   offset?: number;
   year?: string;
   fieldsOfStudy?: string[];
+  // Field-specific queries, used by Crossref only; other sources ignore them
+  author?: string;
+  titleQuery?: string;
+  bibliographic?: string;
+  // Page through Semantic Scholar, Crossref or PubMed results (up to 1000)
+  fetchAll?: boolean;
 }
 
 export interface SearchResponse {
@@ -208,6 +302,40 @@ export interface SearchResponse {
   results: SearchResult[];
 }
 
+export interface SourceStat {
+  source: SearchSource;
+  // Results returned, before duplicates were merged
+  count: number;
+  elapsedMs: number;
+  error: string | null;
+}
+
+export interface MultiSearchResponse {
+  total: number;
+  results: SearchResult[];
+  sources: SourceStat[];
+}
+
+export interface SearchSourceInfo {
+  source: SearchSource;
+  displayName: string;
+  supportsYearFilter: boolean;
+  supportsFieldsOfStudy: boolean;
+  supportsServerSort: boolean;
+  requiresApiKey: boolean;
+  apiKeyConfigured: boolean;
+  // Setting key to prompt for, when the key is set from Settings
+  apiKeySetting: string | null;
+}
+
+export interface SearchHistoryEntry {
+  id: number;
+  query: string;
+  source: SearchSource;
+  resultCount: number;
+  searchedAt: string;
+}
+
 // Google Drive Types
 export interface DriveFile {
   id: string;
@@ -217,6 +345,14 @@ export interface DriveFile {
   size: string | null;
 }
 
+export interface DriveBackupInfo {
+  fileId: string;
+  name: string;
+  paperCount: number;
+  backedUpAt: string | null;
+  schemaVersion: number;
+}
+
 export interface SyncStatus {
   lastSync: string | null;
   dbSynced: boolean;
@@ -266,6 +402,8 @@ export interface FullTextSearchResult {
   pageNumber: number;
   snippet: string;
   rank: number;
+  // UTF-16 ranges of each match in the page text (usable with slice), with includeOffsets
+  offsets?: [number, number][];
 }
 
 export interface FullTextSearchQuery {
@@ -273,6 +411,13 @@ export interface FullTextSearchQuery {
   limit?: number;
   offset?: number;
   folderId?: string;
+  paperId?: string;
+  includeOffsets?: boolean;
+  // Snippet length in tokens (default 32, at most 64)
+  snippetTokens?: number;
+  // Around each match in the snippet, '<mark>'/'</mark>' by default; '' for plain text
+  markStart?: string;
+  markEnd?: string;
 }
 
 export interface FullTextSearchResponse {
@@ -280,12 +425,45 @@ export interface FullTextSearchResponse {
   results: FullTextSearchResult[];
 }
 
+export interface ImportDirectoryProgress {
+  current: number;
+  total: number;
+  filePath: string;
+  status: 'imported' | 'skipped' | 'failed';
+  error?: string;
+}
+
+export interface ImportDirectorySummary {
+  imported: number;
+  skipped: number;
+  failed: number;
+}
+
+export interface FtsRebuildReport {
+  pageCount: number;
+  indexedCount: number;
+  inSync: boolean;
+}
+
+export interface PdfInfo {
+  pageCount: number;
+  isEncrypted: boolean;
+  hasTextLayer: boolean;
+  fileSize: number;
+}
+
+export interface IndexingProgress {
+  indexed: number;
+  total: number;
+}
+
 export interface IndexingStatus {
   paperId: string;
   totalPages: number;
   indexedPages: number;
   isComplete: boolean;
   error?: string;
+  failedPages: number[];
 }
 
 // Citation Types
@@ -327,7 +505,20 @@ export type SmartGroupCriteria =
   | { type: 'noPdf' }
   | { type: 'hasPdf' }
   | { type: 'unread' }
-  | { type: 'favorites' };
+  | { type: 'favorites' }
+  | { type: 'missingAuthor' }
+  | { type: 'missingYear' }
+  | { type: 'missingPublisher' }
+  | { type: 'notAnalyzed' };
+
+/**
+ * A nested group of criteria, combined with its own "and"/"or" mode.
+ */
+export interface CriteriaGroup {
+  mode: string;
+  criteria: SmartGroupCriteria[];
+  subgroups: CriteriaGroup[];
+}
 
 /**
  * A smart group definition that auto-groups papers by criteria.
@@ -336,8 +527,11 @@ export interface SmartGroup {
   id: string;
   name: string;
   criteria: SmartGroupCriteria[];
-  /** How to combine criteria: "and" or "or" */
+  /** How to combine criteria and subgroups: "and" or "or" */
   matchMode: string;
+  subgroups: CriteriaGroup[];
+  /** Only papers in these folders are considered; null means the whole library */
+  scopeFolderIds: string[] | null;
   icon: string | null;
   color: string | null;
   createdAt: string;
@@ -359,6 +553,8 @@ export interface CreateSmartGroupInput {
   name: string;
   criteria: SmartGroupCriteria[];
   matchMode?: string;
+  subgroups?: CriteriaGroup[];
+  scopeFolderIds?: string[];
   icon?: string;
   color?: string;
 }
@@ -377,6 +573,8 @@ export interface WatchFolder {
   autoAnalyze: boolean;
   autoRename: boolean;
   isActive: boolean;
+  /** After import: "leave", "delete", or "move_to:<dir>" */
+  postImportAction: string;
   createdAt: string;
 }
 
@@ -388,6 +586,7 @@ export interface CreateWatchFolderInput {
   targetFolderId: string;
   autoAnalyze?: boolean;
   autoRename?: boolean;
+  postImportAction?: string;
 }
 
 /**
@@ -400,6 +599,25 @@ export interface WatchFolderEvent {
   eventType: string;
 }
 
+/**
+ * A file imported (or attempted) from a watch folder.
+ */
+export interface WatchFolderImport {
+  id: string;
+  watchFolderId: string;
+  fileName: string;
+  sourcePath: string;
+  paperId: string | null;
+  status: 'imported' | 'already_imported' | 'failed';
+  error: string | null;
+  importedAt: string;
+}
+
+export interface WatchImportResult {
+  status: 'imported' | 'already_imported';
+  paper: Paper | null;
+}
+
 // ============================================================================
 // Automation Types - PDF Auto-Rename
 // ============================================================================
@@ -408,7 +626,7 @@ export interface WatchFolderEvent {
  * Configuration for PDF auto-rename.
  */
 export interface RenameConfig {
-  /** Pattern for renaming: {author}, {year}, {title}, {keywords}, {publisher} */
+  /** Pattern for renaming: {author}, {year}, {title}, {keywords}, {publisher}, {folder}, {topic} */
   pattern: string;
   /** Maximum length for title in filename */
   maxTitleLength: number;
@@ -431,6 +649,15 @@ export interface RenameResult {
   error: string | null;
 }
 
+/**
+ * Result of undoing the latest batch operation.
+ */
+export interface UndoResult {
+  kind: 'batch_rename' | 'batch_delete';
+  reverted: number;
+  errors: string[];
+}
+
 // ============================================================================
 // Writing Types - Scrivener-like Writing Workspace
 // ============================================================================
@@ -583,3 +810,27 @@ export interface DocxExportOptions {
   templatePath?: string;
   includeTableOfContents?: boolean;
 }
+
+// Error Types (rejected value of every failing command)
+export type AppErrorCode =
+  | 'database'
+  | 'not_found'
+  | 'io'
+  | 'validation'
+  | 'auth'
+  | 'network'
+  | 'parse'
+  | 'analysis'
+  | 'rate_limited'
+  | 'pdf_encrypted';
+
+export interface AppError {
+  code: AppErrorCode;
+  message: string;
+}
+
+export const isAppError = (error: unknown): error is AppError =>
+  typeof error === 'object' &&
+  error !== null &&
+  'code' in error &&
+  'message' in error;
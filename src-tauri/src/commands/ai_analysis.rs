@@ -1,9 +1,12 @@
+use crate::commands::cancellation::CancellationState;
 use crate::db::DbConnection;
 use crate::error::AppError;
+use crate::models::{FullTextSearchQuery, PdfPage};
 use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use tauri::State;
+use tauri::{AppHandle, Emitter, Manager, State};
 
 const GEMINI_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.0-flash:generateContent";
 
@@ -64,12 +67,57 @@ pub struct AnalysisResult {
     pub future_plans: Option<Vec<String>>,
 }
 
+const ANALYSIS_TEMPERATURE_SETTING: &str = "analysis_temperature";
+const DEFAULT_ANALYSIS_TEMPERATURE: f32 = 0.1;
+const TEXT_TEMPERATURE_SETTING: &str = "text_temperature";
+/// Slightly higher than analysis for more natural text
+const DEFAULT_TEXT_TEMPERATURE: f32 = 0.3;
+
+const ANALYSIS_MAX_OUTPUT_TOKENS_SETTING: &str = "analysis_max_output_tokens";
+/// The model's own output limit, used by default and for the retry after an
+/// analysis truncated by a lower configured limit
+const GEMINI_MAX_OUTPUT_TOKENS: u32 = 8192;
+const DEFAULT_ANALYSIS_MAX_OUTPUT_TOKENS: u32 = GEMINI_MAX_OUTPUT_TOKENS;
+
+/// Rename the PDF from its new metadata once an analysis is saved
+const AUTO_RENAME_SETTING: &str = "auto_rename_after_analysis";
+
+/// Harm categories relaxed for every request. Papers on violence, abuse or
+/// medicine are otherwise blocked wholesale at the default thresholds.
+const SAFETY_CATEGORIES: [&str; 4] = [
+    "HARM_CATEGORY_HARASSMENT",
+    "HARM_CATEGORY_HATE_SPEECH",
+    "HARM_CATEGORY_SEXUALLY_EXPLICIT",
+    "HARM_CATEGORY_DANGEROUS_CONTENT",
+];
+
+/// Finish reasons meaning Gemini withheld the answer rather than failed
+const BLOCKED_FINISH_REASONS: [&str; 4] = ["SAFETY", "BLOCKLIST", "PROHIBITED_CONTENT", "SPII"];
+
 // Gemini API request/response types
 #[derive(Serialize)]
 struct GeminiRequest {
     contents: Vec<GeminiContent>,
     #[serde(rename = "generationConfig")]
     generation_config: GeminiGenerationConfig,
+    #[serde(rename = "safetySettings")]
+    safety_settings: Vec<GeminiSafetySetting>,
+}
+
+#[derive(Serialize)]
+struct GeminiSafetySetting {
+    category: &'static str,
+    threshold: &'static str,
+}
+
+fn safety_settings() -> Vec<GeminiSafetySetting> {
+    SAFETY_CATEGORIES
+        .iter()
+        .map(|category| GeminiSafetySetting {
+            category,
+            threshold: "BLOCK_ONLY_HIGH",
+        })
+        .collect()
 }
 
 #[derive(Serialize)]
@@ -95,21 +143,35 @@ struct GeminiGenerationConfig {
     temperature: f32,
     #[serde(rename = "responseMimeType")]
     response_mime_type: String,
+    #[serde(rename = "maxOutputTokens")]
+    max_output_tokens: u32,
 }
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct GeminiResponse {
     candidates: Option<Vec<GeminiCandidate>>,
     error: Option<GeminiError>,
+    prompt_feedback: Option<GeminiPromptFeedback>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiPromptFeedback {
+    block_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct GeminiCandidate {
-    content: GeminiResponseContent,
+    // Missing when the candidate was blocked
+    content: Option<GeminiResponseContent>,
+    finish_reason: Option<String>,
 }
 
 #[derive(Deserialize)]
 struct GeminiResponseContent {
+    #[serde(default)]
     parts: Vec<GeminiResponsePart>,
 }
 
@@ -123,20 +185,84 @@ struct GeminiError {
     message: String,
 }
 
-/// Analyze a paper's PDF using Gemini AI
+impl GeminiResponse {
+    /// Whether the answer was cut off at `maxOutputTokens`
+    fn truncated(&self) -> bool {
+        self.candidates
+            .as_ref()
+            .and_then(|c| c.first())
+            .and_then(|c| c.finish_reason.as_deref())
+            == Some("MAX_TOKENS")
+    }
+}
+
+/// Pull the generated text out of a Gemini response, telling a safety block
+/// apart from an empty answer
+fn response_text(response: GeminiResponse) -> Result<String, AppError> {
+    if let Some(error) = response.error {
+        return Err(AppError::Analysis(format!("Gemini API 오류: {}", error.message)));
+    }
+
+    let blocked = || AppError::Analysis("content blocked by safety filter".to_string());
+    if response.prompt_feedback.and_then(|f| f.block_reason).is_some() {
+        return Err(blocked());
+    }
+
+    let candidate = response
+        .candidates
+        .and_then(|c| c.into_iter().next())
+        .ok_or_else(|| AppError::Analysis("Gemini 응답이 비어있습니다.".to_string()))?;
+    if candidate
+        .finish_reason
+        .as_deref()
+        .is_some_and(|reason| BLOCKED_FINISH_REASONS.contains(&reason))
+    {
+        return Err(blocked());
+    }
+
+    candidate
+        .content
+        .and_then(|c| c.parts.into_iter().next())
+        .map(|p| p.text)
+        .ok_or_else(|| AppError::Analysis("Gemini 응답이 비어있습니다.".to_string()))
+}
+
+/// Gemini can't read an encrypted PDF, so send it a decrypted copy. Files
+/// lopdf can't parse are sent as they are.
+fn readable_pdf_bytes(pdf_bytes: Vec<u8>, password: Option<&str>) -> Result<Vec<u8>, AppError> {
+    let Ok(mut doc) = lopdf::Document::load_mem(&pdf_bytes) else {
+        return Ok(pdf_bytes);
+    };
+    if !doc.is_encrypted() {
+        return Ok(pdf_bytes);
+    }
+
+    crate::commands::pdf_indexing::decrypt_pdf(&mut doc, password)?;
+    let mut decrypted = Vec::new();
+    doc.save_to(&mut decrypted)?;
+    Ok(decrypted)
+}
+
+/// Analyze a paper's PDF using Gemini AI. `password` opens an encrypted PDF and
+/// is not stored; without it, an encrypted PDF fails with `pdf_encrypted`.
 #[tauri::command]
 pub async fn analyze_paper(
+    app: AppHandle,
     paper_id: String,
     db: State<'_, DbConnection>,
+    password: Option<String>,
 ) -> Result<AnalysisResult, AppError> {
-    // 1. Get Gemini API key from settings
-    let api_key = {
+    // 1. Get Gemini API key and generation settings
+    let (api_key, temperature, max_output_tokens) = {
         let conn = db.get()?;
-        let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = 'gemini_api_key'")?;
-        let key: Option<String> = stmt
-            .query_row([], |row| row.get(0))
-            .ok();
-        key
+        (
+            crate::db::settings::get_setting(&conn, "gemini_api_key")?,
+            crate::db::settings::get_setting_f32(&conn, ANALYSIS_TEMPERATURE_SETTING)?
+                .unwrap_or(DEFAULT_ANALYSIS_TEMPERATURE),
+            crate::db::settings::get_setting_i32(&conn, ANALYSIS_MAX_OUTPUT_TOKENS_SETTING)?
+                .map(|tokens| tokens as u32)
+                .unwrap_or(DEFAULT_ANALYSIS_MAX_OUTPUT_TOKENS),
+        )
     };
 
     let api_key = api_key.ok_or_else(|| {
@@ -148,29 +274,31 @@ pub async fn analyze_paper(
     }
 
     // 2. Get paper info and PDF path
-    let (pdf_path, current_title): (Option<String>, String) = {
+    let (pdf_path, current_title, folder_id): (Option<String>, String, String) = {
         let conn = db.get()?;
-        let mut stmt = conn.prepare("SELECT pdf_path, title FROM papers WHERE id = ?")?;
+        let mut stmt = conn.prepare("SELECT pdf_path, title, folder_id FROM papers WHERE id = ?")?;
         stmt.query_row([&paper_id], |row| {
-            Ok((row.get(0)?, row.get(1)?))
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
         })?
     };
 
-    let pdf_path = pdf_path.ok_or_else(|| {
+    let stored_pdf_path = pdf_path.ok_or_else(|| {
         AppError::Analysis("이 논문에는 PDF 파일이 없습니다.".to_string())
     })?;
 
     // 3. Read PDF file and encode to base64
+    let pdf_path = crate::commands::pdf::resolve_pdf_path(&app, &stored_pdf_path)?;
     let pdf_bytes = fs::read(&pdf_path).map_err(|e| {
         AppError::Analysis(format!("PDF 파일을 읽을 수 없습니다: {}", e))
     })?;
+    let pdf_bytes = readable_pdf_bytes(pdf_bytes, password.as_deref())?;
 
     let base64_pdf = STANDARD.encode(&pdf_bytes);
 
     // 4. Call Gemini API
-    let client = reqwest::Client::new();
+    let client = crate::commands::http::client()?;
 
-    let request_body = GeminiRequest {
+    let mut request_body = GeminiRequest {
         contents: vec![GeminiContent {
             parts: vec![
                 GeminiPart::Text {
@@ -185,38 +313,48 @@ pub async fn analyze_paper(
             ],
         }],
         generation_config: GeminiGenerationConfig {
-            temperature: 0.1,
+            temperature,
             response_mime_type: "application/json".to_string(),
+            max_output_tokens,
         },
+        safety_settings: safety_settings(),
     };
 
     let url = format!("{}?key={}", GEMINI_API_URL, api_key);
 
-    let response = client
-        .post(&url)
-        .json(&request_body)
-        .send()
-        .await
-        .map_err(|e| AppError::Analysis(format!("Gemini API 호출 실패: {}", e)))?;
+    // Truncated JSON can't be parsed, so retry once with the model's full output limit
+    let text = loop {
+        let response = client
+            .post(&url)
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|e| AppError::Analysis(format!("Gemini API 호출 실패: {}", e)))?;
 
-    let gemini_response: GeminiResponse = response
-        .json()
-        .await
-        .map_err(|e| AppError::Analysis(format!("Gemini 응답 파싱 실패: {}", e)))?;
+        crate::commands::http::check_rate_limit(&response, "Gemini")?;
 
-    // Check for API error
-    if let Some(error) = gemini_response.error {
-        return Err(AppError::Analysis(format!("Gemini API 오류: {}", error.message)));
-    }
+        let gemini_response: GeminiResponse = response
+            .json()
+            .await
+            .map_err(|e| AppError::Analysis(format!("Gemini 응답 파싱 실패: {}", e)))?;
 
-    // Extract text from response
-    let text = gemini_response
-        .candidates
-        .and_then(|c| c.into_iter().next())
-        .map(|c| c.content.parts.into_iter().next())
-        .flatten()
-        .map(|p| p.text)
-        .ok_or_else(|| AppError::Analysis("Gemini 응답이 비어있습니다.".to_string()))?;
+        if !gemini_response.truncated() {
+            break response_text(gemini_response)?;
+        }
+        let config = &mut request_body.generation_config;
+        if config.max_output_tokens >= GEMINI_MAX_OUTPUT_TOKENS {
+            return Err(AppError::Analysis(
+                "논문이 너무 커서 분석 결과가 잘렸습니다. 더 작은 PDF로 다시 시도해주세요.".to_string(),
+            ));
+        }
+        log::info!(
+            "Gemini analysis of {} hit {} output tokens; retrying with {}",
+            paper_id,
+            config.max_output_tokens,
+            GEMINI_MAX_OUTPUT_TOKENS
+        );
+        config.max_output_tokens = GEMINI_MAX_OUTPUT_TOKENS;
+    };
 
     // 5. Parse JSON response (handle both array and single object)
     let result: AnalysisResult = {
@@ -314,9 +452,147 @@ pub async fn analyze_paper(
         )?;
     }
 
+    // 7. Rename the PDF from the new metadata, if enabled
+    let auto_rename = {
+        let conn = db.get()?;
+        crate::db::settings::get_setting_bool(&conn, AUTO_RENAME_SETTING)?.unwrap_or(false)
+    };
+    if auto_rename && !stored_pdf_path.is_empty() {
+        let config = {
+            let conn = db.get()?;
+            crate::commands::automation::load_rename_config(&conn)?
+        };
+        // The analysis is already saved, so a failed rename only gets logged
+        let renamed = crate::commands::automation::rename_paper_pdf(
+            app.clone(),
+            db.clone(),
+            paper_id.clone(),
+            Some(config),
+        );
+        if let Err(e) = renamed {
+            log::warn!("Auto-rename of {} after analysis failed: {}", paper_id, e);
+        }
+    }
+
+    let _ = app.emit("papers-changed", &folder_id);
+
     Ok(result)
 }
 
+/// Outcome of analyzing one paper within a batch
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchAnalysisResult {
+    pub paper_id: String,
+    pub success: bool,
+    pub result: Option<AnalysisResult>,
+    pub error: Option<String>,
+}
+
+/// Analyze multiple papers one after another.
+/// Pass an `operation_id` to allow stopping the batch with `cancel_operation`.
+#[tauri::command]
+pub async fn analyze_papers_batch(
+    app: AppHandle,
+    paper_ids: Vec<String>,
+    operation_id: Option<String>,
+    db: State<'_, DbConnection>,
+    cancellation: State<'_, CancellationState>,
+) -> Result<Vec<BatchAnalysisResult>, AppError> {
+    let token = cancellation.register(operation_id);
+    let mut results = Vec::new();
+
+    for paper_id in paper_ids {
+        // Each analysis is a paid API call, so check before starting the next one
+        if token.is_cancelled() {
+            break;
+        }
+
+        match analyze_paper(app.clone(), paper_id.clone(), db.clone(), None).await {
+            Ok(result) => results.push(BatchAnalysisResult {
+                paper_id,
+                success: true,
+                result: Some(result),
+                error: None,
+            }),
+            Err(e) => results.push(BatchAnalysisResult {
+                paper_id,
+                success: false,
+                result: None,
+                error: Some(e.to_string()),
+            }),
+        }
+    }
+    cancellation.finish(&token);
+
+    Ok(results)
+}
+
+/// Seconds to wait between queued auto-analyses, to stay under Gemini's rate limit
+const AUTO_ANALYZE_DELAY_SETTING: &str = "auto_analyze_delay_secs";
+const DEFAULT_AUTO_ANALYZE_DELAY_SECS: u64 = 10;
+
+/// Extra wait before retrying a paper that Gemini rate-limited
+const RATE_LIMIT_BACKOFF: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Queue of papers waiting for automatic analysis (e.g. from watch folder imports).
+/// A single worker analyzes them one at a time, pausing between calls, and emits
+/// `auto-analyze-done` with the paper id or `auto-analyze-failed` with `{paperId, error}`.
+pub struct AnalysisQueue {
+    tx: tokio::sync::mpsc::UnboundedSender<String>,
+}
+
+impl AnalysisQueue {
+    /// Spawn the worker and return the handle to enqueue papers with
+    pub fn start(app: AppHandle) -> Self {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+
+        tauri::async_runtime::spawn(async move {
+            while let Some(paper_id) = rx.recv().await {
+                let mut result =
+                    analyze_paper(app.clone(), paper_id.clone(), app.state(), None).await;
+                if let Err(AppError::RateLimited(_)) = result {
+                    tokio::time::sleep(RATE_LIMIT_BACKOFF).await;
+                    result = analyze_paper(app.clone(), paper_id.clone(), app.state(), None).await;
+                }
+
+                match result {
+                    Ok(_) => {
+                        let _ = app.emit("auto-analyze-done", &paper_id);
+                    }
+                    Err(e) => {
+                        log::warn!("Auto-analysis of {} failed: {}", paper_id, e);
+                        let _ = app.emit(
+                            "auto-analyze-failed",
+                            serde_json::json!({ "paperId": paper_id, "error": e }),
+                        );
+                    }
+                }
+
+                tokio::time::sleep(auto_analyze_delay(&app.state())).await;
+            }
+        });
+
+        Self { tx }
+    }
+
+    pub fn enqueue(&self, paper_id: String) {
+        if self.tx.send(paper_id).is_err() {
+            log::warn!("Auto-analysis queue has stopped; paper not queued");
+        }
+    }
+}
+
+fn auto_analyze_delay(db: &DbConnection) -> std::time::Duration {
+    let secs = db
+        .get()
+        .ok()
+        .and_then(|conn| crate::db::settings::get_setting_i32(&conn, AUTO_ANALYZE_DELAY_SETTING).ok().flatten())
+        .map(|secs| secs as u64)
+        .unwrap_or(DEFAULT_AUTO_ANALYZE_DELAY_SECS);
+    std::time::Duration::from_secs(secs)
+}
+
 // ============================================================================
 // Text-only AI functions (for summarization and translation)
 // ============================================================================
@@ -324,15 +600,36 @@ pub async fn analyze_paper(
 /// Gemini API URL for text-only generation
 const GEMINI_TEXT_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.0-flash:generateContent";
 
+/// API key and sampling temperature for text-only requests
+struct TextModel {
+    api_key: String,
+    temperature: f32,
+}
+
 /// Helper function to call Gemini API with text-only input
-async fn call_gemini_text(api_key: &str, prompt: &str) -> Result<String, AppError> {
-    let client = reqwest::Client::new();
+async fn call_gemini_text(model: &TextModel, prompt: &str) -> Result<String, AppError> {
+    call_gemini_text_with_mime(model, prompt, None).await
+}
+
+/// Call Gemini with text-only input, asking for a JSON response
+async fn call_gemini_json(model: &TextModel, prompt: &str) -> Result<String, AppError> {
+    call_gemini_text_with_mime(model, prompt, Some("application/json")).await
+}
+
+async fn call_gemini_text_with_mime(
+    model: &TextModel,
+    prompt: &str,
+    response_mime_type: Option<&str>,
+) -> Result<String, AppError> {
+    let client = crate::commands::http::client()?;
 
     #[derive(Serialize)]
     struct TextRequest {
         contents: Vec<TextContent>,
         #[serde(rename = "generationConfig")]
         generation_config: TextGenerationConfig,
+        #[serde(rename = "safetySettings")]
+        safety_settings: Vec<GeminiSafetySetting>,
     }
 
     #[derive(Serialize)]
@@ -348,6 +645,8 @@ async fn call_gemini_text(api_key: &str, prompt: &str) -> Result<String, AppErro
     #[derive(Serialize)]
     struct TextGenerationConfig {
         temperature: f32,
+        #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
+        response_mime_type: Option<String>,
     }
 
     let request_body = TextRequest {
@@ -357,11 +656,13 @@ async fn call_gemini_text(api_key: &str, prompt: &str) -> Result<String, AppErro
             }],
         }],
         generation_config: TextGenerationConfig {
-            temperature: 0.3, // Slightly higher for more natural text
+            temperature: model.temperature,
+            response_mime_type: response_mime_type.map(str::to_string),
         },
+        safety_settings: safety_settings(),
     };
 
-    let url = format!("{}?key={}", GEMINI_TEXT_API_URL, api_key);
+    let url = format!("{}?key={}", GEMINI_TEXT_API_URL, model.api_key);
 
     let response = client
         .post(&url)
@@ -370,38 +671,26 @@ async fn call_gemini_text(api_key: &str, prompt: &str) -> Result<String, AppErro
         .await
         .map_err(|e| AppError::Analysis(format!("Gemini API 호출 실패: {}", e)))?;
 
+    crate::commands::http::check_rate_limit(&response, "Gemini")?;
+
     let gemini_response: GeminiResponse = response
         .json()
         .await
         .map_err(|e| AppError::Analysis(format!("Gemini 응답 파싱 실패: {}", e)))?;
 
-    // Check for API error
-    if let Some(error) = gemini_response.error {
-        return Err(AppError::Analysis(format!("Gemini API 오류: {}", error.message)));
-    }
-
-    // Extract text from response
-    let text = gemini_response
-        .candidates
-        .and_then(|c| c.into_iter().next())
-        .map(|c| c.content.parts.into_iter().next())
-        .flatten()
-        .map(|p| p.text)
-        .ok_or_else(|| AppError::Analysis("Gemini 응답이 비어있습니다.".to_string()))?;
-
-    Ok(text)
+    response_text(gemini_response)
 }
 
-/// Get Gemini API key from database
-fn get_gemini_key(db: &DbConnection) -> Result<String, AppError> {
+/// Get Gemini API key and text temperature from database
+fn get_text_model(db: &DbConnection) -> Result<TextModel, AppError> {
     let conn = db.get()?;
-    let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = 'gemini_api_key'")?;
-    let key: Option<String> = stmt
-        .query_row([], |row| row.get(0))
-        .ok();
+    let key = crate::db::settings::get_setting(&conn, "gemini_api_key")?;
 
-    key.filter(|k| !k.is_empty())
-        .ok_or_else(|| AppError::Analysis("Gemini API 키가 설정되지 않았습니다. Settings에서 API 키를 입력해주세요.".to_string()))
+    let api_key = key.filter(|k| !k.is_empty())
+        .ok_or_else(|| AppError::Analysis("Gemini API 키가 설정되지 않았습니다. Settings에서 API 키를 입력해주세요.".to_string()))?;
+    let temperature = crate::db::settings::get_setting_f32(&conn, TEXT_TEMPERATURE_SETTING)?
+        .unwrap_or(DEFAULT_TEXT_TEMPERATURE);
+    Ok(TextModel { api_key, temperature })
 }
 
 /// Summarize selected text using Gemini AI
@@ -410,7 +699,7 @@ pub async fn summarize_text(
     text: String,
     db: State<'_, DbConnection>,
 ) -> Result<String, AppError> {
-    let api_key = get_gemini_key(&db)?;
+    let model = get_text_model(&db)?;
 
     if text.trim().is_empty() {
         return Err(AppError::Analysis("요약할 텍스트가 없습니다.".to_string()));
@@ -424,7 +713,362 @@ pub async fn summarize_text(
         text
     );
 
-    call_gemini_text(&api_key, &prompt).await
+    call_gemini_text(&model, &prompt).await
+}
+
+/// Largest piece of paper text sent to Gemini in one summary request, in characters
+const SUMMARY_CHUNK_CHARS: usize = 60_000;
+
+const PAPER_SUMMARY_PROMPT: &str = "다음은 학술 논문의 본문입니다. 한국어로 구조화된 요약을 작성해주세요. \
+    다음 Markdown 섹션을 사용하세요: ## 연구 목적, ## 연구 방법, ## 주요 결과, ## 한계 및 시사점. \
+    각 섹션은 2-4개의 글머리표로 작성하고, 학술 용어는 그대로 유지해주세요.";
+
+/// Split text into chunks of at most `max_chars` characters, breaking at whitespace where possible
+fn chunk_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_chars = 0;
+
+    for word in text.split_inclusive(char::is_whitespace) {
+        let word_chars = word.chars().count();
+        if current_chars + word_chars > max_chars && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_chars = 0;
+        }
+        current.push_str(word);
+        current_chars += word_chars;
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Summarize a whole paper from its indexed text, extracting it from the PDF
+/// when the paper hasn't been indexed. Long papers are summarized chunk by
+/// chunk and the partial summaries combined.
+#[tauri::command]
+pub async fn summarize_paper(
+    app: AppHandle,
+    paper_id: String,
+    db: State<'_, DbConnection>,
+) -> Result<String, AppError> {
+    let model = get_text_model(&db)?;
+
+    let (paper, pages) = {
+        let conn = db.get()?;
+        let paper = crate::db::papers::get_paper(&conn, &paper_id)?;
+        let pages = crate::db::pdf_content::get_pdf_pages(&conn, &paper_id)?;
+        (paper, pages)
+    };
+
+    let text = if pages.is_empty() {
+        if paper.pdf_path.is_empty() {
+            return Err(AppError::Analysis("이 논문에는 PDF 파일이 없습니다.".to_string()));
+        }
+        let pdf_path = crate::commands::pdf::resolve_pdf_path(&app, &paper.pdf_path)?;
+        crate::commands::pdf_indexing::extract_pdf_text(&pdf_path.to_string_lossy(), None)?
+    } else {
+        pages
+            .iter()
+            .map(|page| page.text_content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    };
+
+    if text.trim().is_empty() {
+        return Err(AppError::Analysis("요약할 텍스트가 없습니다.".to_string()));
+    }
+
+    let chunks = chunk_text(&text, SUMMARY_CHUNK_CHARS);
+    if chunks.len() == 1 {
+        let prompt = format!("{}\n\n제목: {}\n\n---\n{}\n---", PAPER_SUMMARY_PROMPT, paper.title, chunks[0]);
+        return call_gemini_text(&model, &prompt).await;
+    }
+
+    let mut partials = Vec::with_capacity(chunks.len());
+    for (i, chunk) in chunks.iter().enumerate() {
+        let prompt = format!(
+            "다음은 학술 논문 \"{}\"의 일부({}/{})입니다. \
+            연구 목적, 방법, 결과, 한계와 관련된 핵심 내용을 한국어 글머리표로 정리해주세요.\n\n---\n{}\n---",
+            paper.title,
+            i + 1,
+            chunks.len(),
+            chunk
+        );
+        partials.push(call_gemini_text(&model, &prompt).await?);
+    }
+
+    let prompt = format!(
+        "{}\n\n아래는 논문 본문을 나누어 정리한 메모입니다. 이를 종합해 하나의 요약으로 작성해주세요.\n\n제목: {}\n\n---\n{}\n---",
+        PAPER_SUMMARY_PROMPT,
+        paper.title,
+        partials.join("\n\n")
+    );
+    call_gemini_text(&model, &prompt).await
+}
+
+/// Most pages passed to Gemini as context for a question
+const ASK_MAX_PAGES: usize = 5;
+
+/// Words too common to help find relevant pages
+const QUESTION_STOPWORDS: &[&str] = &[
+    "about", "does", "from", "have", "how", "into", "that", "the", "their", "there", "these",
+    "this", "what", "when", "where", "which", "while", "who", "why", "with", "would", "paper",
+    "study", "authors",
+];
+
+/// Answer to a question about a paper, with the pages it was based on
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaperAnswer {
+    pub answer: String,
+    pub pages: Vec<i32>,
+}
+
+/// Answer a question about a paper from its most relevant indexed pages
+#[tauri::command]
+pub async fn ask_paper(
+    paper_id: String,
+    question: String,
+    db: State<'_, DbConnection>,
+) -> Result<PaperAnswer, AppError> {
+    if question.trim().is_empty() {
+        return Err(AppError::Validation("Question is required".to_string()));
+    }
+    let model = get_text_model(&db)?;
+
+    let (paper, pages) = {
+        let conn = db.get()?;
+        let paper = crate::db::papers::get_paper(&conn, &paper_id)?;
+        let pages = crate::db::pdf_content::get_pdf_pages(&conn, &paper_id)?;
+        if pages.is_empty() {
+            return Err(AppError::Validation(
+                "이 논문은 아직 색인되지 않았습니다. 먼저 PDF를 색인해주세요.".to_string(),
+            ));
+        }
+
+        // Score pages per question term; words are searched separately since
+        // the FTS query requires every word to match
+        let mut scores: HashMap<i32, f64> = HashMap::new();
+        for term in question
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| w.chars().count() >= 3)
+            .filter(|w| !QUESTION_STOPWORDS.contains(&w.to_lowercase().as_str()))
+        {
+            let query = FullTextSearchQuery {
+                query: term.to_string(),
+                limit: Some(ASK_MAX_PAGES as i32),
+                offset: None,
+                folder_id: None,
+                paper_id: Some(paper_id.clone()),
+                include_offsets: None,
+                snippet_tokens: None,
+                mark_start: None,
+                mark_end: None,
+            };
+            for hit in crate::db::pdf_content::search_pdf_content(&conn, &query)?.results {
+                // bm25 ranks are negative, better matches lower
+                *scores.entry(hit.page_number).or_default() -= hit.rank;
+            }
+        }
+
+        let mut ranked: Vec<(i32, f64)> = scores.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        let mut selected: Vec<i32> = ranked.into_iter().take(ASK_MAX_PAGES).map(|(page, _)| page).collect();
+        if selected.is_empty() {
+            // Nothing matched; fall back to the opening pages
+            selected = pages.iter().take(ASK_MAX_PAGES).map(|p| p.page_number).collect();
+        }
+        selected.sort_unstable();
+
+        let pages: Vec<PdfPage> = pages
+            .into_iter()
+            .filter(|p| selected.contains(&p.page_number))
+            .collect();
+        (paper, pages)
+    };
+
+    // Keep the whole context within a single request
+    let per_page_chars = SUMMARY_CHUNK_CHARS / pages.len();
+    let context = pages
+        .iter()
+        .map(|page| {
+            let text: String = page.text_content.chars().take(per_page_chars).collect();
+            format!("[Page {}]\n{}", page.page_number, text)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let prompt = format!(
+        "다음은 학술 논문 \"{}\"에서 발췌한 페이지입니다. 발췌문에 근거해서만 질문에 답해주세요. \
+        답을 찾을 수 없으면 그렇다고 말해주세요. 질문과 같은 언어로 답하고, 근거가 된 페이지를 [Page N] 형식으로 표시해주세요.\n\n\
+        ---\n{}\n---\n\n질문: {}",
+        paper.title,
+        context,
+        question.trim()
+    );
+
+    let answer = call_gemini_text(&model, &prompt).await?;
+    Ok(PaperAnswer {
+        answer,
+        pages: pages.iter().map(|p| p.page_number).collect(),
+    })
+}
+
+/// Structured AI comparison of two papers
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaperComparison {
+    #[serde(default)]
+    pub paper_id_a: String,
+    #[serde(default)]
+    pub paper_id_b: String,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub shared_methods: Vec<String>,
+    #[serde(default)]
+    pub differing_methods: Vec<String>,
+    #[serde(default)]
+    pub differing_variables: Vec<String>,
+    #[serde(default)]
+    pub shared_findings: Vec<String>,
+    #[serde(default)]
+    pub contrasting_results: Vec<String>,
+}
+
+const COMPARE_PROMPT: &str = r#"당신은 학술 논문 비교 전문가입니다. 아래 두 논문(A, B)의 분석 내용을 비교하여 다음 JSON 형식으로 한국어로 응답하세요.
+{
+  "summary": "두 논문의 관계를 2-3문장으로 요약",
+  "sharedMethods": ["두 논문이 공통으로 사용한 연구 방법/분석 기법"],
+  "differingMethods": ["A와 B의 연구 방법 차이 (예: 'A: 설문조사, B: 실험')"],
+  "differingVariables": ["A와 B의 변인 차이"],
+  "sharedFindings": ["두 논문이 공통으로 보고한 결과"],
+  "contrastingResults": ["서로 다르거나 상반된 결과"]
+}
+해당 항목이 없으면 빈 배열을 사용하세요."#;
+
+/// Describe a paper's bibliographic info and stored analysis for a prompt
+fn describe_paper_analysis(paper: &crate::models::Paper) -> String {
+    let mut lines = vec![format!("제목: {}", paper.title)];
+    let mut push = |label: &str, value: String| {
+        if !value.trim().is_empty() {
+            lines.push(format!("{}: {}", label, value));
+        }
+    };
+
+    push("저자", paper.author.clone());
+    push("연도", if paper.year > 0 { paper.year.to_string() } else { String::new() });
+    push("키워드", paper.keywords.clone());
+    push("초록", paper.abstract_text.clone());
+    push("연구 목적", paper.purposes.join("; "));
+    push("질적 도구", paper.qual_tools.join("; "));
+    push("양적 기법", paper.quant_techniques.join("; "));
+    push("독립변인", paper.vars_independent.join("; "));
+    push("종속변인", paper.vars_dependent.join("; "));
+    push("조절변인", paper.vars_moderator.join("; "));
+    push("매개변인", paper.vars_mediator.join("; "));
+    push("기타 변인", paper.vars_others.join("; "));
+    push("연구 결과", paper.results.join("; "));
+    push("한계", paper.limitations.join("; "));
+    push("시사점", paper.implications.join("; "));
+
+    lines.join("\n")
+}
+
+/// Compare two papers' methods, variables and results using their stored analysis
+#[tauri::command]
+pub async fn compare_papers(
+    paper_id_a: String,
+    paper_id_b: String,
+    db: State<'_, DbConnection>,
+) -> Result<PaperComparison, AppError> {
+    if paper_id_a == paper_id_b {
+        return Err(AppError::Validation("Choose two different papers to compare".to_string()));
+    }
+    let model = get_text_model(&db)?;
+
+    let (paper_a, paper_b) = {
+        let conn = db.get()?;
+        (
+            crate::db::papers::get_paper(&conn, &paper_id_a)?,
+            crate::db::papers::get_paper(&conn, &paper_id_b)?,
+        )
+    };
+
+    for paper in [&paper_a, &paper_b] {
+        if paper.last_analyzed_at.is_none() && paper.abstract_text.trim().is_empty() {
+            return Err(AppError::Validation(format!(
+                "\"{}\" 논문은 아직 분석되지 않았습니다. 먼저 분석을 실행해주세요.",
+                paper.title
+            )));
+        }
+    }
+
+    let prompt = format!(
+        "{}\n\n[논문 A]\n{}\n\n[논문 B]\n{}",
+        COMPARE_PROMPT,
+        describe_paper_analysis(&paper_a),
+        describe_paper_analysis(&paper_b)
+    );
+
+    let text = call_gemini_json(&model, &prompt).await?;
+    let mut comparison: PaperComparison = serde_json::from_str(&text).map_err(|e| {
+        let preview: String = text.chars().take(200).collect();
+        AppError::Analysis(format!("비교 결과 파싱 실패: {}. 응답: {}", e, preview))
+    })?;
+    comparison.paper_id_a = paper_id_a;
+    comparison.paper_id_b = paper_id_b;
+    Ok(comparison)
+}
+
+/// Translation target languages by ISO 639-1 code (plus Chinese script variants)
+const TRANSLATION_LANGUAGES: &[(&str, &str)] = &[
+    ("ar", "Arabic"),
+    ("cs", "Czech"),
+    ("da", "Danish"),
+    ("de", "German"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("es", "Spanish"),
+    ("fa", "Persian"),
+    ("fi", "Finnish"),
+    ("fr", "French"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("hu", "Hungarian"),
+    ("id", "Indonesian"),
+    ("it", "Italian"),
+    ("ja", "Japanese"),
+    ("ko", "Korean"),
+    ("ms", "Malay"),
+    ("nl", "Dutch"),
+    ("no", "Norwegian"),
+    ("pl", "Polish"),
+    ("pt", "Portuguese"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("sv", "Swedish"),
+    ("th", "Thai"),
+    ("tr", "Turkish"),
+    ("uk", "Ukrainian"),
+    ("vi", "Vietnamese"),
+    ("zh", "Chinese"),
+    ("zh-cn", "Simplified Chinese"),
+    ("zh-tw", "Traditional Chinese"),
+];
+
+/// Look up a language name, trying the full tag (`zh-TW`) before its primary subtag (`pt` for `pt-BR`)
+fn translation_language(code: &str) -> Option<&'static str> {
+    let code = code.trim().to_lowercase().replace('_', "-");
+    let primary = code.split('-').next().unwrap_or_default();
+    [code.as_str(), primary].iter().find_map(|candidate| {
+        TRANSLATION_LANGUAGES
+            .iter()
+            .find(|(c, _)| c == candidate)
+            .map(|(_, name)| *name)
+    })
 }
 
 /// Translate selected text using Gemini AI
@@ -434,17 +1078,27 @@ pub async fn translate_text(
     target_lang: String,
     db: State<'_, DbConnection>,
 ) -> Result<String, AppError> {
-    let api_key = get_gemini_key(&db)?;
+    let language = translation_language(&target_lang).ok_or_else(|| {
+        AppError::Validation(format!("Unsupported target language: {}", target_lang))
+    })?;
+
+    let model = get_text_model(&db)?;
 
     if text.trim().is_empty() {
         return Err(AppError::Analysis("번역할 텍스트가 없습니다.".to_string()));
     }
 
-    // Only support Korean <-> English
-    let (_lang_name, instruction) = match target_lang.as_str() {
-        "en" => ("영어", "Translate the following academic text to English. Maintain academic terminology accurately."),
-        "ko" => ("한국어", "다음 학술 텍스트를 한국어로 번역해주세요. 학술 용어는 정확하게 유지해주세요."),
-        _ => ("영어", "Translate the following academic text to English. Maintain academic terminology accurately."),
+    translate(&model, &text, language).await
+}
+
+async fn translate(model: &TextModel, text: &str, language: &str) -> Result<String, AppError> {
+    let instruction = if language == "Korean" {
+        "다음 학술 텍스트를 한국어로 번역해주세요. 학술 용어는 정확하게 유지해주세요.".to_string()
+    } else {
+        format!(
+            "Translate the following academic text to {}. Maintain academic terminology accurately.",
+            language
+        )
     };
 
     let prompt = format!(
@@ -452,5 +1106,73 @@ pub async fn translate_text(
         instruction, text
     );
 
-    call_gemini_text(&api_key, &prompt).await
+    call_gemini_text(model, &prompt).await
+}
+
+/// Outcome of `detect_and_translate_abstract`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbstractTranslation {
+    /// ISO 639-3 code of the detected language, e.g. `kor`
+    pub detected_language: Option<String>,
+    /// False when the text was already in the target language
+    pub translated: bool,
+    pub paper: crate::models::Paper,
+}
+
+/// Whether whatlang's language is the named translation language
+/// (`Simplified Chinese` counts as Chinese)
+fn is_language(detected: whatlang::Lang, language: &str) -> bool {
+    let name = match detected {
+        whatlang::Lang::Cmn => "Chinese",
+        other => other.eng_name(),
+    };
+    language == name || language.ends_with(&format!(" {}", name))
+}
+
+/// Detect the language of a paper's abstract (or subject, when there is no
+/// abstract) and store a Gemini translation in `subjectTranslated` when it isn't
+/// already in `target_lang`
+#[tauri::command]
+pub async fn detect_and_translate_abstract(
+    paper_id: String,
+    target_lang: String,
+    db: State<'_, DbConnection>,
+) -> Result<AbstractTranslation, AppError> {
+    let language = translation_language(&target_lang).ok_or_else(|| {
+        AppError::Validation(format!("Unsupported target language: {}", target_lang))
+    })?;
+
+    let paper = crate::db::papers::get_paper(&*db.get()?, &paper_id)?;
+    let text = [&paper.abstract_text, &paper.subject]
+        .into_iter()
+        .map(|t| t.trim())
+        .find(|t| !t.is_empty())
+        .ok_or_else(|| AppError::Analysis("번역할 초록이 없습니다.".to_string()))?
+        .to_string();
+
+    // Unreliable guesses (very short or mixed text) are translated to be safe
+    let detected = whatlang::detect(&text);
+    let detected_language = detected.as_ref().map(|info| info.lang().code().to_string());
+    if detected
+        .as_ref()
+        .is_some_and(|info| info.is_reliable() && is_language(info.lang(), language))
+    {
+        return Ok(AbstractTranslation {
+            detected_language,
+            translated: false,
+            paper,
+        });
+    }
+
+    let model = get_text_model(&db)?;
+    let translation = translate(&model, &text, language).await?;
+
+    let conn = db.get()?;
+    crate::db::papers::set_subject_translated(&conn, &paper_id, translation.trim())?;
+    Ok(AbstractTranslation {
+        detected_language,
+        translated: true,
+        paper: crate::db::papers::get_paper(&conn, &paper_id)?,
+    })
 }
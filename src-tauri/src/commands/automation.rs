@@ -7,10 +7,14 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use tauri::{AppHandle, Emitter, Manager, State};
+use rusqlite::OptionalExtension;
+use tauri::{AppHandle, Emitter, State};
 
+use crate::commands::ai_analysis::AnalysisQueue;
+use crate::commands::cancellation::CancellationState;
+use crate::commands::undo::{record_operation, PdfRename, UndoableOperation};
 use crate::db::DbConnection;
 use crate::error::AppError;
 use crate::models::Paper;
@@ -55,6 +59,54 @@ pub enum SmartGroupCriteria {
     Unread,
     /// Favorite papers (importance >= 4)
     Favorites,
+    /// Papers without an author
+    MissingAuthor,
+    /// Papers without a publication year
+    MissingYear,
+    /// Papers without a publisher or journal
+    MissingPublisher,
+    /// Papers that have never been analyzed
+    NotAnalyzed,
+}
+
+/// A node in a tree of criteria, e.g. `(year >= 2020) AND (tag = ml OR tag = nlp)`.
+/// The node's own criteria and its subgroups are all combined with `mode`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CriteriaGroup {
+    /// How to combine criteria and subgroups: "and" or "or"
+    #[serde(default = "default_match_mode")]
+    pub mode: String,
+    #[serde(default)]
+    pub criteria: Vec<SmartGroupCriteria>,
+    #[serde(default)]
+    pub subgroups: Vec<CriteriaGroup>,
+}
+
+/// Criteria as stored in `smart_groups.criteria`: a criteria tree, or the
+/// flat list written before nested groups existed
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCriteria {
+    Flat(Vec<SmartGroupCriteria>),
+    Group(CriteriaGroup),
+}
+
+/// Read the criteria column, treating an old flat list as a single group
+fn parse_stored_criteria(json: &str, match_mode: &str) -> CriteriaGroup {
+    match serde_json::from_str(json) {
+        Ok(StoredCriteria::Group(group)) => group,
+        Ok(StoredCriteria::Flat(criteria)) => CriteriaGroup {
+            mode: match_mode.to_string(),
+            criteria,
+            subgroups: Vec::new(),
+        },
+        Err(_) => CriteriaGroup {
+            mode: match_mode.to_string(),
+            criteria: Vec::new(),
+            subgroups: Vec::new(),
+        },
+    }
 }
 
 /// A smart group definition
@@ -64,8 +116,14 @@ pub struct SmartGroup {
     pub id: String,
     pub name: String,
     pub criteria: Vec<SmartGroupCriteria>,
-    /// How to combine criteria: "and" or "or"
+    /// How to combine criteria and subgroups: "and" or "or"
     pub match_mode: String,
+    /// Nested groups combined with the top-level criteria
+    #[serde(default)]
+    pub subgroups: Vec<CriteriaGroup>,
+    /// Only papers in these folders are considered; `None` means the whole library
+    #[serde(default)]
+    pub scope_folder_ids: Option<Vec<String>>,
     pub icon: Option<String>,
     pub color: Option<String>,
     pub created_at: String,
@@ -89,6 +147,10 @@ pub struct CreateSmartGroupInput {
     pub criteria: Vec<SmartGroupCriteria>,
     #[serde(default = "default_match_mode")]
     pub match_mode: String,
+    #[serde(default)]
+    pub subgroups: Vec<CriteriaGroup>,
+    #[serde(default)]
+    pub scope_folder_ids: Option<Vec<String>>,
     pub icon: Option<String>,
     pub color: Option<String>,
 }
@@ -111,6 +173,9 @@ pub struct WatchFolder {
     pub auto_analyze: bool,
     pub auto_rename: bool,
     pub is_active: bool,
+    /// What to do with the source file after a successful import:
+    /// "leave", "delete", or "move_to:<dir>" (relative to the watch folder or absolute)
+    pub post_import_action: String,
     pub created_at: String,
 }
 
@@ -124,6 +189,51 @@ pub struct CreateWatchFolderInput {
     pub auto_analyze: bool,
     #[serde(default)]
     pub auto_rename: bool,
+    #[serde(default = "default_post_import_action")]
+    pub post_import_action: String,
+}
+
+fn default_post_import_action() -> String {
+    "leave".to_string()
+}
+
+/// Parsed form of `WatchFolder::post_import_action`
+enum PostImportAction {
+    Leave,
+    Delete,
+    MoveTo(PathBuf),
+}
+
+impl PostImportAction {
+    fn parse(action: &str) -> Result<Self, AppError> {
+        match action {
+            "leave" => Ok(Self::Leave),
+            "delete" => Ok(Self::Delete),
+            _ => match action.strip_prefix("move_to:").map(str::trim) {
+                Some(dir) if !dir.is_empty() => Ok(Self::MoveTo(PathBuf::from(dir))),
+                _ => Err(AppError::Validation(format!(
+                    "Invalid post-import action: {} (expected leave, delete or move_to:<dir>)",
+                    action
+                ))),
+            },
+        }
+    }
+}
+
+const WATCH_FOLDER_COLUMNS: &str =
+    "id, path, target_folder_id, auto_analyze, auto_rename, is_active, post_import_action, created_at";
+
+fn row_to_watch_folder(row: &rusqlite::Row) -> rusqlite::Result<WatchFolder> {
+    Ok(WatchFolder {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        target_folder_id: row.get(2)?,
+        auto_analyze: row.get::<_, i32>(3)? != 0,
+        auto_rename: row.get::<_, i32>(4)? != 0,
+        is_active: row.get::<_, i32>(5)? != 0,
+        post_import_action: row.get(6)?,
+        created_at: row.get(7)?,
+    })
 }
 
 /// Event emitted when a file is detected in a watch folder
@@ -136,6 +246,49 @@ pub struct WatchFolderEvent {
     pub event_type: String,
 }
 
+/// One file imported (or attempted) from a watch folder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchFolderImport {
+    pub id: String,
+    pub watch_folder_id: String,
+    pub file_name: String,
+    pub source_path: String,
+    /// The created paper; `None` if the import failed or the paper was deleted
+    pub paper_id: Option<String>,
+    /// "imported", "already_imported" or "failed"
+    pub status: String,
+    pub error: Option<String>,
+    pub imported_at: String,
+}
+
+/// Outcome of `import_from_watch_folder`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchImportResult {
+    /// "imported", or "already_imported" when the file, its content or its
+    /// title is already in the library
+    pub status: String,
+    /// The new paper, or the existing one when it is known
+    pub paper: Option<Paper>,
+}
+
+impl WatchImportResult {
+    fn imported(paper: Paper) -> Self {
+        Self {
+            status: "imported".to_string(),
+            paper: Some(paper),
+        }
+    }
+
+    fn already_imported(paper: Option<Paper>) -> Self {
+        Self {
+            status: "already_imported".to_string(),
+            paper,
+        }
+    }
+}
+
 /// State for managing watch folder watchers
 pub struct WatchFolderState {
     /// Map of watch folder ID to active watcher handle
@@ -164,7 +317,8 @@ impl Default for WatchFolderState {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RenameConfig {
-    /// Pattern for renaming: {author}, {year}, {title}
+    /// Pattern for renaming: {author}, {year}, {title}, {keywords}, {publisher},
+    /// {folder}, {topic}
     pub pattern: String,
     /// Maximum length for title in filename
     #[serde(default = "default_max_title_length")]
@@ -219,35 +373,143 @@ pub fn get_smart_group_papers(
     db: State<'_, DbConnection>,
     criteria: Vec<SmartGroupCriteria>,
     match_mode: Option<String>,
+    subgroups: Option<Vec<CriteriaGroup>>,
+    scope_folder_ids: Option<Vec<String>>,
 ) -> Result<Vec<Paper>, AppError> {
     let conn = db.get()?;
-    let mode = match_mode.unwrap_or_else(|| "and".to_string());
+    let group = CriteriaGroup {
+        mode: match_mode.unwrap_or_else(default_match_mode),
+        criteria,
+        subgroups: subgroups.unwrap_or_default(),
+    };
+    filter_smart_group_papers(&conn, &group, normalize_scope(scope_folder_ids).as_deref())
+}
 
-    // Get all papers first
-    let all_papers = crate::db::papers::get_papers(&conn, None, None)?;
+/// Count the papers matching a saved or predefined smart group, for sidebar badges.
+/// Saved groups cache the count until papers change or the day rolls over.
+#[tauri::command]
+pub fn get_smart_group_count(
+    db: State<'_, DbConnection>,
+    group_id: String,
+) -> Result<usize, AppError> {
+    let conn = db.get()?;
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+
+    let saved = conn
+        .query_row(
+            "SELECT criteria, match_mode, cached_count, count_cached_on, scope_folder_ids
+             FROM smart_groups WHERE id = ?",
+            [&group_id],
+            |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, Option<i64>>(2)?,
+                    row.get::<_, Option<String>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                ))
+            },
+        )
+        .optional()?;
+
+    let Some((criteria_json, mode, cached_count, cached_on, scope_json)) = saved else {
+        let group = get_predefined_smart_groups()
+            .into_iter()
+            .find(|g| g.id == group_id)
+            .ok_or_else(|| AppError::NotFound(format!("Smart group not found: {}", group_id)))?;
+        let root = CriteriaGroup {
+            mode: group.match_mode,
+            criteria: group.criteria,
+            subgroups: group.subgroups,
+        };
+        return Ok(filter_smart_group_papers(&conn, &root, None)?.len());
+    };
 
-    if criteria.is_empty() {
-        return Ok(all_papers);
+    // Relative-date criteria shift daily, so a cached count is only good for the day it was taken
+    if let (Some(count), Some(cached_on)) = (cached_count, cached_on) {
+        if cached_on == today {
+            return Ok(count as usize);
+        }
     }
 
-    // Filter papers based on criteria
-    let filtered: Vec<Paper> = all_papers
-        .into_iter()
-        .filter(|paper| {
-            let matches: Vec<bool> = criteria
-                .iter()
-                .map(|c| matches_criteria(paper, c))
-                .collect();
+    let root = parse_stored_criteria(&criteria_json, &mode);
+    let scope = parse_stored_scope(scope_json.as_deref());
+    let count = filter_smart_group_papers(&conn, &root, scope.as_deref())?.len();
 
-            if mode == "or" {
-                matches.iter().any(|&m| m)
-            } else {
-                matches.iter().all(|&m| m)
+    conn.execute(
+        "UPDATE smart_groups SET cached_count = ?, count_cached_on = ? WHERE id = ?",
+        rusqlite::params![count as i64, today, group_id],
+    )?;
+
+    Ok(count)
+}
+
+/// An empty scope would match nothing, so treat it as the whole library
+fn normalize_scope(scope: Option<Vec<String>>) -> Option<Vec<String>> {
+    scope.filter(|ids| !ids.is_empty())
+}
+
+fn parse_stored_scope(json: Option<&str>) -> Option<Vec<String>> {
+    normalize_scope(json.and_then(|j| serde_json::from_str(j).ok()))
+}
+
+/// Load the papers in scope (every paper when `scope` is `None`) and keep
+/// those matching a criteria tree
+fn filter_smart_group_papers(
+    conn: &rusqlite::Connection,
+    group: &CriteriaGroup,
+    scope: Option<&[String]>,
+) -> Result<Vec<Paper>, AppError> {
+    let candidates = match scope {
+        Some(folder_ids) => {
+            let mut papers = Vec::new();
+            for folder_id in folder_ids {
+                let filter = crate::models::PaperFilter {
+                    folder_id: Some(folder_id.clone()),
+                    ..Default::default()
+                };
+                papers.extend(crate::db::papers::get_papers(conn, &filter, None, None, None)?);
             }
-        })
-        .collect();
+            papers
+        }
+        None => crate::db::papers::get_papers(conn, &Default::default(), None, None, None)?,
+    };
 
-    Ok(filtered)
+    Ok(candidates
+        .into_iter()
+        .filter(|paper| matches_group(paper, group))
+        .collect())
+}
+
+/// Whether a group has no criteria anywhere in its tree
+fn is_empty_group(group: &CriteriaGroup) -> bool {
+    group.criteria.is_empty() && group.subgroups.iter().all(is_empty_group)
+}
+
+/// Recursively evaluate a criteria tree. Empty subgroups are left out, so they
+/// can't make an "or" group match everything; an empty tree matches every paper.
+fn matches_group(paper: &Paper, group: &CriteriaGroup) -> bool {
+    if is_empty_group(group) {
+        return true;
+    }
+
+    let mut matches = group
+        .criteria
+        .iter()
+        .map(|c| matches_criteria(paper, c))
+        .chain(
+            group
+                .subgroups
+                .iter()
+                .filter(|g| !is_empty_group(g))
+                .map(|g| matches_group(paper, g)),
+        );
+
+    if group.mode == "or" {
+        matches.any(|m| m)
+    } else {
+        matches.all(|m| m)
+    }
 }
 
 /// Check if a paper matches a single criterion
@@ -324,6 +586,14 @@ fn matches_criteria(paper: &Paper, criteria: &SmartGroupCriteria) -> bool {
         SmartGroupCriteria::Unread => !paper.is_read,
 
         SmartGroupCriteria::Favorites => paper.importance >= 4,
+
+        SmartGroupCriteria::MissingAuthor => paper.author.trim().is_empty(),
+
+        SmartGroupCriteria::MissingYear => paper.year <= 0,
+
+        SmartGroupCriteria::MissingPublisher => paper.publisher.trim().is_empty(),
+
+        SmartGroupCriteria::NotAnalyzed => paper.last_analyzed_at.is_none(),
     }
 }
 
@@ -339,6 +609,8 @@ pub fn get_predefined_smart_groups() -> Vec<SmartGroup> {
             name: "Unread Papers".to_string(),
             criteria: vec![SmartGroupCriteria::Unread],
             match_mode: "and".to_string(),
+            subgroups: Vec::new(),
+            scope_folder_ids: None,
             icon: Some("book-open".to_string()),
             color: Some("#3b82f6".to_string()),
             created_at: now.clone(),
@@ -348,6 +620,8 @@ pub fn get_predefined_smart_groups() -> Vec<SmartGroup> {
             name: "Favorites".to_string(),
             criteria: vec![SmartGroupCriteria::Favorites],
             match_mode: "and".to_string(),
+            subgroups: Vec::new(),
+            scope_folder_ids: None,
             icon: Some("star".to_string()),
             color: Some("#eab308".to_string()),
             created_at: now.clone(),
@@ -357,6 +631,8 @@ pub fn get_predefined_smart_groups() -> Vec<SmartGroup> {
             name: "Added This Week".to_string(),
             criteria: vec![SmartGroupCriteria::RecentlyAdded(7)],
             match_mode: "and".to_string(),
+            subgroups: Vec::new(),
+            scope_folder_ids: None,
             icon: Some("clock".to_string()),
             color: Some("#22c55e".to_string()),
             created_at: now.clone(),
@@ -366,6 +642,8 @@ pub fn get_predefined_smart_groups() -> Vec<SmartGroup> {
             name: "Added This Month".to_string(),
             criteria: vec![SmartGroupCriteria::RecentlyAdded(30)],
             match_mode: "and".to_string(),
+            subgroups: Vec::new(),
+            scope_folder_ids: None,
             icon: Some("calendar".to_string()),
             color: Some("#06b6d4".to_string()),
             created_at: now.clone(),
@@ -375,6 +653,8 @@ pub fn get_predefined_smart_groups() -> Vec<SmartGroup> {
             name: format!("Published in {}", current_year),
             criteria: vec![SmartGroupCriteria::ByYear(current_year)],
             match_mode: "and".to_string(),
+            subgroups: Vec::new(),
+            scope_folder_ids: None,
             icon: Some("calendar-days".to_string()),
             color: Some("#8b5cf6".to_string()),
             created_at: now.clone(),
@@ -384,6 +664,8 @@ pub fn get_predefined_smart_groups() -> Vec<SmartGroup> {
             name: "Missing PDFs".to_string(),
             criteria: vec![SmartGroupCriteria::NoPdf],
             match_mode: "and".to_string(),
+            subgroups: Vec::new(),
+            scope_folder_ids: None,
             icon: Some("file-x".to_string()),
             color: Some("#ef4444".to_string()),
             created_at: now.clone(),
@@ -393,6 +675,8 @@ pub fn get_predefined_smart_groups() -> Vec<SmartGroup> {
             name: "Qualitative Research".to_string(),
             criteria: vec![SmartGroupCriteria::ByResearchType { qualitative: true, quantitative: false }],
             match_mode: "and".to_string(),
+            subgroups: Vec::new(),
+            scope_folder_ids: None,
             icon: Some("message-square".to_string()),
             color: Some("#f97316".to_string()),
             created_at: now.clone(),
@@ -402,6 +686,8 @@ pub fn get_predefined_smart_groups() -> Vec<SmartGroup> {
             name: "Quantitative Research".to_string(),
             criteria: vec![SmartGroupCriteria::ByResearchType { qualitative: false, quantitative: true }],
             match_mode: "and".to_string(),
+            subgroups: Vec::new(),
+            scope_folder_ids: None,
             icon: Some("bar-chart".to_string()),
             color: Some("#14b8a6".to_string()),
             created_at: now.clone(),
@@ -411,8 +697,26 @@ pub fn get_predefined_smart_groups() -> Vec<SmartGroup> {
             name: "Mixed Methods".to_string(),
             criteria: vec![SmartGroupCriteria::ByResearchType { qualitative: true, quantitative: true }],
             match_mode: "and".to_string(),
+            subgroups: Vec::new(),
+            scope_folder_ids: None,
             icon: Some("git-merge".to_string()),
             color: Some("#ec4899".to_string()),
+            created_at: now.clone(),
+        },
+        SmartGroup {
+            id: "needs-attention".to_string(),
+            name: "Needs Attention".to_string(),
+            criteria: vec![
+                SmartGroupCriteria::MissingAuthor,
+                SmartGroupCriteria::MissingYear,
+                SmartGroupCriteria::MissingPublisher,
+                SmartGroupCriteria::NotAnalyzed,
+            ],
+            match_mode: "or".to_string(),
+            subgroups: Vec::new(),
+            scope_folder_ids: None,
+            icon: Some("alert-triangle".to_string()),
+            color: Some("#f59e0b".to_string()),
             created_at: now,
         },
     ]
@@ -428,12 +732,23 @@ pub fn create_smart_group(
     let id = uuid::Uuid::new_v4().to_string();
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
-    let criteria_json = serde_json::to_string(&input.criteria)
+    let root = CriteriaGroup {
+        mode: input.match_mode.clone(),
+        criteria: input.criteria.clone(),
+        subgroups: input.subgroups.clone(),
+    };
+    let criteria_json = serde_json::to_string(&root)
         .map_err(|e| AppError::Validation(e.to_string()))?;
+    let scope_folder_ids = normalize_scope(input.scope_folder_ids);
+    let scope_json = scope_folder_ids
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()?;
 
     conn.execute(
-        r#"INSERT INTO smart_groups (id, name, criteria, match_mode, icon, color, created_at)
-           VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+        r#"INSERT INTO smart_groups
+               (id, name, criteria, match_mode, icon, color, created_at, scope_folder_ids)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?)"#,
         rusqlite::params![
             id,
             input.name,
@@ -441,7 +756,8 @@ pub fn create_smart_group(
             input.match_mode,
             input.icon,
             input.color,
-            now
+            now,
+            scope_json
         ],
     )?;
 
@@ -450,6 +766,8 @@ pub fn create_smart_group(
         name: input.name,
         criteria: input.criteria,
         match_mode: input.match_mode,
+        subgroups: input.subgroups,
+        scope_folder_ids,
         icon: input.icon,
         color: input.color,
         created_at: now,
@@ -462,20 +780,23 @@ pub fn get_smart_groups(db: State<'_, DbConnection>) -> Result<Vec<SmartGroup>,
     let conn = db.get()?;
 
     let mut stmt = conn.prepare(
-        "SELECT id, name, criteria, match_mode, icon, color, created_at FROM smart_groups ORDER BY name",
+        "SELECT id, name, criteria, match_mode, icon, color, created_at, scope_folder_ids
+         FROM smart_groups ORDER BY name",
     )?;
 
     let groups = stmt
         .query_map([], |row| {
             let criteria_json: String = row.get(2)?;
-            let criteria: Vec<SmartGroupCriteria> = serde_json::from_str(&criteria_json)
-                .unwrap_or_default();
+            let match_mode: String = row.get(3)?;
+            let root = parse_stored_criteria(&criteria_json, &match_mode);
 
             Ok(SmartGroup {
                 id: row.get(0)?,
                 name: row.get(1)?,
-                criteria,
-                match_mode: row.get(3)?,
+                criteria: root.criteria,
+                match_mode: root.mode,
+                subgroups: root.subgroups,
+                scope_folder_ids: parse_stored_scope(row.get::<_, Option<String>>(7)?.as_deref()),
                 icon: row.get(4)?,
                 color: row.get(5)?,
                 created_at: row.get(6)?,
@@ -505,6 +826,7 @@ pub fn create_watch_folder(
     input: CreateWatchFolderInput,
 ) -> Result<WatchFolder, AppError> {
     let conn = db.get()?;
+    PostImportAction::parse(&input.post_import_action)?;
 
     // Validate path exists
     let path = PathBuf::from(&input.path);
@@ -533,8 +855,8 @@ pub fn create_watch_folder(
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
     conn.execute(
-        r#"INSERT INTO watch_folders (id, path, target_folder_id, auto_analyze, auto_rename, is_active, created_at)
-           VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+        r#"INSERT INTO watch_folders (id, path, target_folder_id, auto_analyze, auto_rename, is_active, post_import_action, created_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?)"#,
         rusqlite::params![
             id,
             input.path,
@@ -542,6 +864,7 @@ pub fn create_watch_folder(
             input.auto_analyze as i32,
             input.auto_rename as i32,
             1, // is_active = true by default
+            input.post_import_action,
             now
         ],
     )?;
@@ -553,6 +876,7 @@ pub fn create_watch_folder(
         auto_analyze: input.auto_analyze,
         auto_rename: input.auto_rename,
         is_active: true,
+        post_import_action: input.post_import_action,
         created_at: now,
     })
 }
@@ -562,22 +886,13 @@ pub fn create_watch_folder(
 pub fn get_watch_folders(db: State<'_, DbConnection>) -> Result<Vec<WatchFolder>, AppError> {
     let conn = db.get()?;
 
-    let mut stmt = conn.prepare(
-        "SELECT id, path, target_folder_id, auto_analyze, auto_rename, is_active, created_at FROM watch_folders ORDER BY created_at DESC",
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM watch_folders ORDER BY created_at DESC",
+        WATCH_FOLDER_COLUMNS
+    ))?;
 
     let folders = stmt
-        .query_map([], |row| {
-            Ok(WatchFolder {
-                id: row.get(0)?,
-                path: row.get(1)?,
-                target_folder_id: row.get(2)?,
-                auto_analyze: row.get::<_, i32>(3)? != 0,
-                auto_rename: row.get::<_, i32>(4)? != 0,
-                is_active: row.get::<_, i32>(5)? != 0,
-                created_at: row.get(6)?,
-            })
-        })?
+        .query_map([], row_to_watch_folder)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(folders)
@@ -616,21 +931,33 @@ pub fn toggle_watch_folder(
         rusqlite::params![is_active as i32, watch_folder_id],
     )?;
 
-    let mut stmt = conn.prepare(
-        "SELECT id, path, target_folder_id, auto_analyze, auto_rename, is_active, created_at FROM watch_folders WHERE id = ?",
+    get_watch_folder(&conn, &watch_folder_id)
+}
+
+/// Set what happens to a watch folder's source files after they are imported
+#[tauri::command]
+pub fn set_watch_folder_post_import_action(
+    db: State<'_, DbConnection>,
+    watch_folder_id: String,
+    post_import_action: String,
+) -> Result<WatchFolder, AppError> {
+    PostImportAction::parse(&post_import_action)?;
+    let conn = db.get()?;
+
+    conn.execute(
+        "UPDATE watch_folders SET post_import_action = ? WHERE id = ?",
+        rusqlite::params![post_import_action, watch_folder_id],
     )?;
 
-    stmt.query_row([&watch_folder_id], |row| {
-        Ok(WatchFolder {
-            id: row.get(0)?,
-            path: row.get(1)?,
-            target_folder_id: row.get(2)?,
-            auto_analyze: row.get::<_, i32>(3)? != 0,
-            auto_rename: row.get::<_, i32>(4)? != 0,
-            is_active: row.get::<_, i32>(5)? != 0,
-            created_at: row.get(6)?,
-        })
-    })
+    get_watch_folder(&conn, &watch_folder_id)
+}
+
+fn get_watch_folder(conn: &rusqlite::Connection, watch_folder_id: &str) -> Result<WatchFolder, AppError> {
+    conn.query_row(
+        &format!("SELECT {} FROM watch_folders WHERE id = ?", WATCH_FOLDER_COLUMNS),
+        [watch_folder_id],
+        row_to_watch_folder,
+    )
     .map_err(|_| AppError::NotFound(format!("Watch folder not found: {}", watch_folder_id)))
 }
 
@@ -645,24 +972,7 @@ pub async fn start_watching(
     let conn = db.get()?;
 
     // Get watch folder config
-    let watch_folder: WatchFolder = {
-        let mut stmt = conn.prepare(
-            "SELECT id, path, target_folder_id, auto_analyze, auto_rename, is_active, created_at FROM watch_folders WHERE id = ?",
-        )?;
-
-        stmt.query_row([&watch_folder_id], |row| {
-            Ok(WatchFolder {
-                id: row.get(0)?,
-                path: row.get(1)?,
-                target_folder_id: row.get(2)?,
-                auto_analyze: row.get::<_, i32>(3)? != 0,
-                auto_rename: row.get::<_, i32>(4)? != 0,
-                is_active: row.get::<_, i32>(5)? != 0,
-                created_at: row.get(6)?,
-            })
-        })
-        .map_err(|_| AppError::NotFound(format!("Watch folder not found: {}", watch_folder_id)))?
-    };
+    let watch_folder = get_watch_folder(&conn, &watch_folder_id)?;
 
     if !watch_folder.is_active {
         return Err(AppError::Validation("Watch folder is not active".to_string()));
@@ -780,11 +1090,12 @@ pub fn stop_watching(
     Ok(())
 }
 
-/// Scan a watch folder for existing PDFs
+/// Scan a watch folder for PDFs it hasn't imported yet (or all PDFs with `include_imported`)
 #[tauri::command]
 pub fn scan_watch_folder(
     db: State<'_, DbConnection>,
     watch_folder_id: String,
+    include_imported: Option<bool>,
 ) -> Result<Vec<String>, AppError> {
     let conn = db.get()?;
 
@@ -803,6 +1114,20 @@ pub fn scan_watch_folder(
         )));
     }
 
+    // Files this folder already imported are left out unless explicitly requested
+    let imported: std::collections::HashSet<String> = if include_imported.unwrap_or(false) {
+        Default::default()
+    } else {
+        let mut stmt = conn.prepare(
+            r#"SELECT DISTINCT source_path FROM watch_folder_imports
+               WHERE watch_folder_id = ? AND status IN ('imported', 'already_imported')"#,
+        )?;
+        let paths = stmt
+            .query_map([&watch_folder_id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        paths
+    };
+
     let mut pdfs = Vec::new();
 
     if let Ok(entries) = std::fs::read_dir(&path) {
@@ -810,8 +1135,10 @@ pub fn scan_watch_folder(
             let entry_path = entry.path();
             if entry_path.is_file() {
                 if let Some(ext) = entry_path.extension() {
-                    if ext.to_string_lossy().to_lowercase() == "pdf" {
-                        pdfs.push(entry_path.to_string_lossy().to_string());
+                    let is_pdf = ext.to_string_lossy().to_lowercase() == "pdf";
+                    let entry_path = entry_path.to_string_lossy().to_string();
+                    if is_pdf && !imported.contains(&entry_path) {
+                        pdfs.push(entry_path);
                     }
                 }
             }
@@ -821,22 +1148,20 @@ pub fn scan_watch_folder(
     Ok(pdfs)
 }
 
-/// Import a PDF from a watch folder
+/// Import a PDF from a watch folder, skipping it when it is already in the library
 #[tauri::command]
 pub fn import_from_watch_folder(
     app: AppHandle,
     db: State<'_, DbConnection>,
+    analysis_queue: State<'_, AnalysisQueue>,
     watch_folder_id: String,
     file_path: String,
-) -> Result<Paper, AppError> {
+) -> Result<WatchImportResult, AppError> {
     let conn = db.get()?;
 
     // Get watch folder config
-    let (target_folder_id, auto_analyze, _auto_rename): (String, bool, bool) = conn.query_row(
-        "SELECT target_folder_id, auto_analyze, auto_rename FROM watch_folders WHERE id = ?",
-        [&watch_folder_id],
-        |row| Ok((row.get(0)?, row.get::<_, i32>(1)? != 0, row.get::<_, i32>(2)? != 0)),
-    ).map_err(|_| AppError::NotFound(format!("Watch folder not found: {}", watch_folder_id)))?;
+    let watch_folder = get_watch_folder(&conn, &watch_folder_id)?;
+    let target_folder_id = watch_folder.target_folder_id;
 
     let source_path = PathBuf::from(&file_path);
     let file_name = source_path
@@ -844,58 +1169,176 @@ pub fn import_from_watch_folder(
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or_else(|| "paper.pdf".to_string());
 
-    // Extract title from filename (remove .pdf extension)
+    // Files this watch folder already imported are not imported again, so a re-scan can't duplicate papers
+    if let Some(paper_id) = find_watch_import(&conn, &watch_folder_id, &file_path)? {
+        let paper = match paper_id {
+            Some(paper_id) => Some(crate::db::papers::get_paper(&conn, &paper_id)?),
+            None => None,
+        };
+        return Ok(WatchImportResult::already_imported(paper));
+    }
+
+    // The same PDF may already be in the library, e.g. imported by hand or by a
+    // watcher event that fired twice
+    let existing = match crate::commands::pdf::find_stored_pdf(&conn, &source_path)? {
+        Some(stored) => crate::db::papers::find_paper_by_pdf_path(&conn, &stored)?,
+        None => None,
+    };
     let title = source_path
         .file_stem()
         .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_else(|| "Untitled".to_string());
-
-    // Create paper entry
-    let input = crate::models::CreatePaperInput {
-        folder_id: target_folder_id.clone(),
-        title,
-        author: None,
-        year: None,
-        pdf_path: None,
-        pdf_filename: Some(file_name.clone()),
-    };
+        .unwrap_or_default();
+    if existing.is_some() || crate::db::papers::check_duplicate(&conn, &title)? {
+        let result = Ok(WatchImportResult::already_imported(existing));
+        record_watch_import(&conn, &watch_folder_id, &file_name, &file_path, &result)?;
+        return result;
+    }
+
+    let result = crate::commands::pdf::import_pdf_file(&app, &conn, &target_folder_id, &source_path)
+        .map(WatchImportResult::imported);
+    record_watch_import(&conn, &watch_folder_id, &file_name, &file_path, &result)?;
+    let result = result?;
 
-    let paper = crate::db::papers::create_paper(&conn, input)?;
+    // The paper and its stored PDF are committed, so the source can go without risk of losing it
+    if let Err(e) = PostImportAction::parse(&watch_folder.post_import_action)
+        .and_then(|action| apply_post_import_action(&action, Path::new(&watch_folder.path), &source_path))
+    {
+        log::warn!("Post-import action failed for {}: {}", file_path, e);
+    }
 
-    // Import the PDF file
-    let pdf_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| AppError::Io(e.to_string()))?
-        .join("pdfs");
+    // Emit event
+    let _ = app.emit("papers-changed", &target_folder_id);
 
-    if !pdf_dir.exists() {
-        std::fs::create_dir_all(&pdf_dir)?;
+    // Queue rather than analyze immediately, so a burst of imports doesn't hit Gemini all at once
+    if let Some(paper) = result.paper.as_ref().filter(|_| watch_folder.auto_analyze) {
+        analysis_queue.enqueue(paper.id.clone());
     }
 
-    let dest_filename = format!("{}_{}", paper.id, file_name);
-    let dest_path = pdf_dir.join(&dest_filename);
+    Ok(result)
+}
+
+/// Delete or archive a watch folder file once it has been imported
+fn apply_post_import_action(
+    action: &PostImportAction,
+    watch_path: &Path,
+    source_path: &Path,
+) -> Result<(), AppError> {
+    match action {
+        PostImportAction::Leave => Ok(()),
+        PostImportAction::Delete => Ok(std::fs::remove_file(source_path)?),
+        PostImportAction::MoveTo(dir) => {
+            let dir = if dir.is_absolute() { dir.clone() } else { watch_path.join(dir) };
+            std::fs::create_dir_all(&dir)?;
+
+            let file_name = source_path
+                .file_name()
+                .ok_or_else(|| AppError::Validation("Source has no file name".to_string()))?;
+            let mut dest = dir.join(file_name);
+            let mut counter = 1;
+            while dest.exists() {
+                let stem = source_path.file_stem().unwrap_or_default().to_string_lossy();
+                dest = dir.join(format!("{}_{}.pdf", stem, counter));
+                counter += 1;
+            }
+
+            // rename fails across filesystems, so fall back to copy + delete
+            if std::fs::rename(source_path, &dest).is_err() {
+                std::fs::copy(source_path, &dest)?;
+                std::fs::remove_file(source_path)?;
+            }
+            Ok(())
+        }
+    }
+}
 
-    std::fs::copy(&source_path, &dest_path)?;
+/// Look up a previous successful import of `source_path` into a watch folder.
+/// The inner value is the paper it created, or `None` if that paper was deleted.
+fn find_watch_import(
+    conn: &rusqlite::Connection,
+    watch_folder_id: &str,
+    source_path: &str,
+) -> Result<Option<Option<String>>, AppError> {
+    let paper_id = conn
+        .query_row(
+            r#"SELECT paper_id FROM watch_folder_imports
+               WHERE watch_folder_id = ? AND source_path = ? AND status = 'imported'
+               ORDER BY imported_at DESC LIMIT 1"#,
+            [watch_folder_id, source_path],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(paper_id)
+}
 
-    // Update paper with PDF path
-    let update_input = crate::models::UpdatePaperInput {
-        pdf_path: Some(dest_path.to_string_lossy().to_string()),
-        pdf_filename: Some(file_name),
-        ..Default::default()
+/// Log the outcome of a watch folder import in its history
+fn record_watch_import(
+    conn: &rusqlite::Connection,
+    watch_folder_id: &str,
+    file_name: &str,
+    source_path: &str,
+    result: &Result<WatchImportResult, AppError>,
+) -> Result<(), AppError> {
+    let (paper_id, status, error) = match result {
+        Ok(imported) => (
+            imported.paper.as_ref().map(|paper| paper.id.clone()),
+            imported.status.as_str(),
+            None,
+        ),
+        Err(e) => (None, "failed", Some(e.to_string())),
     };
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
-    let paper = crate::db::papers::update_paper(&conn, &paper.id, update_input)?;
+    conn.execute(
+        r#"INSERT INTO watch_folder_imports
+           (id, watch_folder_id, file_name, source_path, paper_id, status, error, imported_at)
+           VALUES (?, ?, ?, ?, ?, ?, ?, ?)"#,
+        rusqlite::params![
+            uuid::Uuid::new_v4().to_string(),
+            watch_folder_id,
+            file_name,
+            source_path,
+            paper_id,
+            status,
+            error,
+            now
+        ],
+    )?;
+    Ok(())
+}
 
-    // Emit event
-    let _ = app.emit("papers-changed", &target_folder_id);
+/// Get the most recent imports from a watch folder, newest first
+#[tauri::command]
+pub fn get_watch_folder_history(
+    db: State<'_, DbConnection>,
+    watch_folder_id: String,
+    limit: Option<i32>,
+) -> Result<Vec<WatchFolderImport>, AppError> {
+    let conn = db.get()?;
 
-    // If auto_analyze is enabled, emit an event to trigger analysis
-    if auto_analyze {
-        let _ = app.emit("auto-analyze-paper", &paper.id);
-    }
+    let mut stmt = conn.prepare(
+        r#"SELECT id, watch_folder_id, file_name, source_path, paper_id, status, error, imported_at
+           FROM watch_folder_imports
+           WHERE watch_folder_id = ?
+           ORDER BY imported_at DESC
+           LIMIT ?"#,
+    )?;
 
-    Ok(paper)
+    let imports = stmt
+        .query_map(rusqlite::params![watch_folder_id, limit.unwrap_or(50)], |row| {
+            Ok(WatchFolderImport {
+                id: row.get(0)?,
+                watch_folder_id: row.get(1)?,
+                file_name: row.get(2)?,
+                source_path: row.get(3)?,
+                paper_id: row.get(4)?,
+                status: row.get(5)?,
+                error: row.get(6)?,
+                imported_at: row.get(7)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(imports)
 }
 
 // ============================================================================
@@ -911,16 +1354,45 @@ pub fn generate_paper_filename(
 ) -> Result<String, AppError> {
     let conn = db.get()?;
     let paper = crate::db::papers::get_paper(&conn, &paper_id)?;
+    let collection = paper_collection(&conn, &paper)?;
 
     let config = config.unwrap_or_default();
 
-    let filename = generate_filename_from_paper(&paper, &config);
+    let filename = generate_filename_from_paper(&paper, &collection, &config);
 
     Ok(filename)
 }
 
+/// Names of the folder a paper is in and of that folder's topic, for the
+/// `{folder}` and `{topic}` rename tokens
+struct PaperCollection {
+    folder: String,
+    topic: String,
+}
+
+fn paper_collection(
+    conn: &rusqlite::Connection,
+    paper: &Paper,
+) -> Result<PaperCollection, AppError> {
+    let names = conn
+        .query_row(
+            "SELECT f.name, t.name FROM folders f
+             JOIN topics t ON t.id = f.topic_id
+             WHERE f.id = ?",
+            [&paper.folder_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let (folder, topic) = names.unwrap_or_default();
+    Ok(PaperCollection { folder, topic })
+}
+
 /// Generate filename from paper metadata
-fn generate_filename_from_paper(paper: &Paper, config: &RenameConfig) -> String {
+fn generate_filename_from_paper(
+    paper: &Paper,
+    collection: &PaperCollection,
+    config: &RenameConfig,
+) -> String {
     let mut filename = config.pattern.clone();
 
     // Replace author placeholder
@@ -967,6 +1439,12 @@ fn generate_filename_from_paper(paper: &Paper, config: &RenameConfig) -> String
     // Replace publisher placeholder if present
     filename = filename.replace("{publisher}", &sanitize_filename_part(&paper.publisher, &config.space_replacement));
 
+    // Replace collection placeholders if present
+    for (token, name) in [("{folder}", &collection.folder), ("{topic}", &collection.topic)] {
+        let part = sanitize_filename_part(name, &config.space_replacement);
+        filename = filename.replace(token, &part);
+    }
+
     // Apply lowercase if configured
     if config.lowercase {
         filename = filename.to_lowercase();
@@ -1012,7 +1490,7 @@ pub fn rename_paper_pdf(
         return Err(AppError::Validation("Paper has no PDF attached".to_string()));
     }
 
-    let old_path = PathBuf::from(&paper.pdf_path);
+    let old_path = crate::commands::pdf::resolve_pdf_path(&app, &paper.pdf_path)?;
     if !old_path.exists() {
         return Err(AppError::NotFound(format!(
             "PDF file not found: {}",
@@ -1020,8 +1498,9 @@ pub fn rename_paper_pdf(
         )));
     }
 
+    let collection = paper_collection(&conn, &paper)?;
     let config = config.unwrap_or_default();
-    let new_filename = generate_filename_from_paper(&paper, &config);
+    let new_filename = generate_filename_from_paper(&paper, &collection, &config);
 
     // Preserve the paper ID prefix for uniqueness
     let id_prefix = paper.id.split('-').next().unwrap_or(&paper.id);
@@ -1048,9 +1527,12 @@ pub fn rename_paper_pdf(
     if new_path != old_path {
         std::fs::rename(&old_path, &new_path)?;
 
+        // The file may be shared by duplicate imports, so repoint every paper using it
+        let new_stored = crate::commands::pdf::to_stored_pdf_path(&app, &new_path)?;
+        crate::db::pdf_files::rename_path(&conn, &old_path_str, &new_stored)?;
+
         // Update paper record
         let update_input = crate::models::UpdatePaperInput {
-            pdf_path: Some(new_path.to_string_lossy().to_string()),
             pdf_filename: Some(final_filename.clone()),
             ..Default::default()
         };
@@ -1072,18 +1554,25 @@ pub fn rename_paper_pdf(
     })
 }
 
-/// Batch rename multiple papers' PDFs
-#[tauri::command]
+/// Batch rename multiple papers' PDFs. Runs off the main thread so
+/// `cancel_operation` can stop it.
+#[tauri::command(async)]
 pub fn batch_rename_pdfs(
     app: AppHandle,
     db: State<'_, DbConnection>,
+    cancellation: State<'_, CancellationState>,
     paper_ids: Vec<String>,
     config: Option<RenameConfig>,
+    operation_id: Option<String>,
 ) -> Result<Vec<RenameResult>, AppError> {
     let config = config.unwrap_or_default();
+    let token = cancellation.register(operation_id);
     let mut results = Vec::new();
 
     for paper_id in paper_ids {
+        if token.is_cancelled() {
+            break;
+        }
         match rename_paper_pdf(app.clone(), db.clone(), paper_id.clone(), Some(config.clone())) {
             Ok(result) => results.push(result),
             Err(e) => {
@@ -1099,6 +1588,24 @@ pub fn batch_rename_pdfs(
             }
         }
     }
+    cancellation.finish(&token);
+
+    let mut renames = Vec::new();
+    for result in results.iter().filter(|r| r.success) {
+        let new_path = crate::commands::pdf::to_stored_pdf_path(&app, Path::new(&result.new_path))?;
+        if new_path != result.old_path {
+            renames.push(PdfRename {
+                paper_id: result.paper_id.clone(),
+                old_path: result.old_path.clone(),
+                new_path,
+                old_filename: result.old_filename.clone(),
+            });
+        }
+    }
+    if !renames.is_empty() {
+        let conn = db.get()?;
+        record_operation(&conn, &UndoableOperation::BatchRename { renames })?;
+    }
 
     Ok(results)
 }
@@ -1107,19 +1614,22 @@ pub fn batch_rename_pdfs(
 #[tauri::command]
 pub fn get_rename_config(db: State<'_, DbConnection>) -> Result<RenameConfig, AppError> {
     let conn = db.get()?;
+    load_rename_config(&conn)
+}
 
-    let pattern = crate::db::settings::get_setting(&conn, "rename_pattern")?
+/// Read the saved rename configuration, falling back to the defaults
+pub(crate) fn load_rename_config(conn: &rusqlite::Connection) -> Result<RenameConfig, AppError> {
+    let pattern = crate::db::settings::get_setting_string(conn, "rename_pattern")?
         .unwrap_or_else(|| "{author}_{year}_{title}".to_string());
 
-    let max_title_length = crate::db::settings::get_setting(&conn, "rename_max_title_length")?
-        .and_then(|s| s.parse().ok())
+    let max_title_length = crate::db::settings::get_setting_i32(conn, "rename_max_title_length")?
+        .map(|n| n as usize)
         .unwrap_or(50);
 
-    let space_replacement = crate::db::settings::get_setting(&conn, "rename_space_replacement")?
+    let space_replacement = crate::db::settings::get_setting(conn, "rename_space_replacement")?
         .unwrap_or_else(|| "_".to_string());
 
-    let lowercase = crate::db::settings::get_setting(&conn, "rename_lowercase")?
-        .map(|s| s == "true")
+    let lowercase = crate::db::settings::get_setting_bool(conn, "rename_lowercase")?
         .unwrap_or(false);
 
     Ok(RenameConfig {
@@ -1153,8 +1663,9 @@ pub fn preview_rename(
     let conn = db.get()?;
     let paper = crate::db::papers::get_paper(&conn, &paper_id)?;
 
+    let collection = paper_collection(&conn, &paper)?;
     let config = config.unwrap_or_default();
-    let new_filename = generate_filename_from_paper(&paper, &config);
+    let new_filename = generate_filename_from_paper(&paper, &collection, &config);
 
     let id_prefix = paper.id.split('-').next().unwrap_or(&paper.id);
     let final_filename = format!("{}_{}", id_prefix, new_filename);
@@ -1179,3 +1690,186 @@ pub fn preview_rename(
         error: None,
     })
 }
+
+/// A path in `dir` for `filename` that doesn't exist yet, adding ` (2)`, ` (3)`, ...
+/// before the extension as needed
+fn unused_path(dir: &Path, filename: &str) -> PathBuf {
+    let path = dir.join(filename);
+    if !path.exists() {
+        return path;
+    }
+    let stem = Path::new(filename)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    (2..)
+        .map(|n| dir.join(format!("{} ({}).pdf", stem, n)))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+fn export_pdf(
+    app: &AppHandle,
+    conn: &rusqlite::Connection,
+    paper_id: &str,
+    destination: &Path,
+    config: Option<&RenameConfig>,
+) -> Result<RenameResult, AppError> {
+    let paper = crate::db::papers::get_paper(conn, paper_id)?;
+    if paper.pdf_path.is_empty() {
+        return Err(AppError::Validation("Paper has no PDF attached".to_string()));
+    }
+
+    let source = crate::commands::pdf::resolve_pdf_path(app, &paper.pdf_path)?;
+    if !source.exists() {
+        return Err(AppError::NotFound(format!(
+            "PDF file not found: {}",
+            paper.pdf_path
+        )));
+    }
+
+    let filename = match config {
+        Some(config) => {
+            let collection = paper_collection(conn, &paper)?;
+            generate_filename_from_paper(&paper, &collection, config)
+        }
+        None if !paper.pdf_filename.is_empty() => paper.pdf_filename.clone(),
+        None => source
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| format!("{}.pdf", paper.id)),
+    };
+
+    let target = unused_path(destination, &filename);
+    std::fs::copy(&source, &target)?;
+
+    Ok(RenameResult {
+        paper_id: paper.id,
+        old_path: source.to_string_lossy().to_string(),
+        new_path: target.to_string_lossy().to_string(),
+        old_filename: paper.pdf_filename,
+        new_filename: target
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        success: true,
+        error: None,
+    })
+}
+
+/// Copy papers' PDFs into `destination_dir`, named with the saved rename pattern
+/// when `use_rename_pattern` is set. Existing files are never overwritten; papers
+/// without a PDF are reported as failed results.
+#[tauri::command]
+pub fn export_pdfs(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_ids: Vec<String>,
+    destination_dir: String,
+    use_rename_pattern: bool,
+) -> Result<Vec<RenameResult>, AppError> {
+    let destination = PathBuf::from(destination_dir);
+    std::fs::create_dir_all(&destination)?;
+
+    let conn = db.get()?;
+    let config = if use_rename_pattern {
+        Some(load_rename_config(&conn)?)
+    } else {
+        None
+    };
+
+    let results = paper_ids
+        .into_iter()
+        .map(|paper_id| {
+            export_pdf(&app, &conn, &paper_id, &destination, config.as_ref()).unwrap_or_else(|e| {
+                RenameResult {
+                    paper_id,
+                    old_path: String::new(),
+                    new_path: String::new(),
+                    old_filename: String::new(),
+                    new_filename: String::new(),
+                    success: false,
+                    error: Some(e.to_string()),
+                }
+            })
+        })
+        .collect();
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn group(
+        mode: &str,
+        criteria: Vec<SmartGroupCriteria>,
+        subgroups: Vec<CriteriaGroup>,
+    ) -> CriteriaGroup {
+        CriteriaGroup {
+            mode: mode.to_string(),
+            criteria,
+            subgroups,
+        }
+    }
+
+    #[test]
+    fn test_matches_group() {
+        let mut paper = crate::commands::citations::tests::create_test_paper();
+        paper.tags = vec!["nlp".to_string()];
+
+        // (year = 2023) AND (tag = ml OR author contains smith)
+        let tree = group(
+            "and",
+            vec![SmartGroupCriteria::ByYear(2023)],
+            vec![group(
+                "or",
+                vec![
+                    SmartGroupCriteria::ByTag("ml".to_string()),
+                    SmartGroupCriteria::ByAuthor("smith".to_string()),
+                ],
+                Vec::new(),
+            )],
+        );
+        assert!(matches_group(&paper, &tree));
+        paper.year = 2020;
+        assert!(!matches_group(&paper, &tree));
+
+        // An empty subgroup must not make an "or" group match every paper
+        let with_empty = group(
+            "or",
+            vec![SmartGroupCriteria::ByTag("ml".to_string())],
+            vec![group("and", Vec::new(), vec![group("or", Vec::new(), Vec::new())])],
+        );
+        assert!(!matches_group(&paper, &with_empty));
+        paper.tags.push("ML".to_string());
+        assert!(matches_group(&paper, &with_empty));
+
+        assert!(matches_group(&paper, &group("and", Vec::new(), Vec::new())));
+    }
+
+    #[test]
+    fn test_parse_stored_criteria() {
+        let flat =
+            parse_stored_criteria(r#"[{"type":"byTag","value":"ml"},{"type":"favorites"}]"#, "or");
+        assert_eq!(flat.mode, "or");
+        assert_eq!(flat.criteria.len(), 2);
+        assert!(flat.subgroups.is_empty());
+
+        let nested = parse_stored_criteria(
+            r#"{"mode":"and","criteria":[{"type":"byYear","value":2023}],
+                "subgroups":[{"mode":"or","criteria":[{"type":"byAuthor","value":"smith"}]}]}"#,
+            "or",
+        );
+        assert_eq!(nested.mode, "and");
+        assert!(matches!(nested.criteria[..], [SmartGroupCriteria::ByYear(2023)]));
+        assert_eq!(nested.subgroups.len(), 1);
+        assert_eq!(nested.subgroups[0].mode, "or");
+        assert!(nested.subgroups[0].subgroups.is_empty());
+
+        let malformed = parse_stored_criteria("{not json", "and");
+        assert_eq!(malformed.mode, "and");
+        assert!(is_empty_group(&malformed));
+    }
+}
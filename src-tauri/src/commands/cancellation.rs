@@ -0,0 +1,83 @@
+//! Cooperative cancellation for long-running batch operations
+//!
+//! Batch commands accept an optional operation ID. While they run, the
+//! frontend can call `cancel_operation` with the same ID; the batch loop
+//! checks the flag between items and returns the results gathered so far.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use crate::error::AppError;
+
+/// State tracking cancellation flags for in-flight operations
+#[derive(Default)]
+pub struct CancellationState {
+    /// Map of operation ID to its cancellation flag
+    flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+/// Handle held by a running operation to poll for cancellation
+pub struct CancellationToken {
+    operation_id: Option<String>,
+    flag: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::SeqCst)
+    }
+}
+
+impl CancellationState {
+    /// Register an operation and get a token to poll.
+    /// Operations started without an ID can never be cancelled.
+    pub fn register(&self, operation_id: Option<String>) -> CancellationToken {
+        let flag = Arc::new(AtomicBool::new(false));
+
+        if let Some(id) = &operation_id {
+            if let Ok(mut flags) = self.flags.lock() {
+                flags.insert(id.clone(), flag.clone());
+            }
+        }
+
+        CancellationToken { operation_id, flag }
+    }
+
+    /// Request cancellation; returns false if no such operation is running
+    pub fn cancel(&self, operation_id: &str) -> bool {
+        match self.flags.lock() {
+            Ok(flags) => match flags.get(operation_id) {
+                Some(flag) => {
+                    flag.store(true, Ordering::SeqCst);
+                    true
+                }
+                None => false,
+            },
+            Err(_) => false,
+        }
+    }
+
+    /// Remove a finished operation's flag
+    pub fn finish(&self, token: &CancellationToken) {
+        if let Some(id) = &token.operation_id {
+            if let Ok(mut flags) = self.flags.lock() {
+                flags.remove(id);
+            }
+        }
+    }
+}
+
+/// Cancel a running batch operation by its ID
+#[tauri::command]
+pub fn cancel_operation(
+    cancellation: State<'_, CancellationState>,
+    operation_id: String,
+) -> Result<bool, AppError> {
+    let cancelled = cancellation.cancel(&operation_id);
+    if cancelled {
+        log::info!("Cancellation requested for operation: {}", operation_id);
+    }
+    Ok(cancelled)
+}
@@ -1,9 +1,14 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
 use crate::db::DbConnection;
 use crate::error::AppError;
-use crate::models::paper::Paper;
+use crate::models::paper::{CreatePaperInput, Paper, PaperFilter, UpdatePaperInput, BIBTEX_ENTRY_TYPES};
+use crate::models::Highlight;
 
 /// Citation style enum for formatting
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +20,49 @@ pub enum CitationStyle {
     Harvard,
 }
 
+/// Setting holding the style used when a command is called without one
+const DEFAULT_CITATION_STYLE_SETTING: &str = "default_citation_style";
+
+impl CitationStyle {
+    fn name(&self) -> &'static str {
+        match self {
+            CitationStyle::Apa => "apa",
+            CitationStyle::Mla => "mla",
+            CitationStyle::Chicago => "chicago",
+            CitationStyle::Harvard => "harvard",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "apa" => Some(CitationStyle::Apa),
+            "mla" => Some(CitationStyle::Mla),
+            "chicago" => Some(CitationStyle::Chicago),
+            "harvard" => Some(CitationStyle::Harvard),
+            _ => None,
+        }
+    }
+}
+
+/// The stored default citation style, APA when none is set
+pub(crate) fn default_citation_style(conn: &Connection) -> Result<CitationStyle, AppError> {
+    let stored = crate::db::settings::get_setting_string(conn, DEFAULT_CITATION_STYLE_SETTING)?;
+    Ok(stored
+        .and_then(|name| CitationStyle::from_name(&name))
+        .unwrap_or(CitationStyle::Apa))
+}
+
+/// `style` if given, otherwise the stored default
+fn resolve_style(
+    db: &DbConnection,
+    style: Option<CitationStyle>,
+) -> Result<CitationStyle, AppError> {
+    match style {
+        Some(style) => Ok(style),
+        None => default_citation_style(&*db.get()?),
+    }
+}
+
 /// Citation export result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -33,59 +81,6 @@ pub struct BatchCitationExport {
     pub paper_count: usize,
 }
 
-/// Helper function to get paper by ID
-fn get_paper_by_id(db: &DbConnection, paper_id: &str) -> Result<Paper, AppError> {
-    let conn = db.get()?;
-    let mut stmt = conn.prepare(
-        "SELECT id, folder_id, paper_number, keywords, author, year, title, publisher, subject,
-                purposes, is_qualitative, is_quantitative, qual_tools,
-                vars_independent, vars_dependent, vars_moderator, vars_mediator, vars_others,
-                quant_techniques, results, limitations, implications, future_plans,
-                pdf_path, pdf_filename, user_notes, tags, is_read, importance,
-                created_at, updated_at, last_analyzed_at
-         FROM papers WHERE id = ?1",
-    )?;
-
-    let paper = stmt.query_row([paper_id], |row| {
-        Ok(Paper {
-            id: row.get(0)?,
-            folder_id: row.get(1)?,
-            paper_number: row.get(2)?,
-            keywords: row.get(3)?,
-            author: row.get(4)?,
-            year: row.get(5)?,
-            title: row.get(6)?,
-            publisher: row.get(7)?,
-            subject: row.get(8)?,
-            purposes: serde_json::from_str(&row.get::<_, String>(9)?).unwrap_or_default(),
-            is_qualitative: row.get(10)?,
-            is_quantitative: row.get(11)?,
-            qual_tools: serde_json::from_str(&row.get::<_, String>(12)?).unwrap_or_default(),
-            vars_independent: serde_json::from_str(&row.get::<_, String>(13)?).unwrap_or_default(),
-            vars_dependent: serde_json::from_str(&row.get::<_, String>(14)?).unwrap_or_default(),
-            vars_moderator: serde_json::from_str(&row.get::<_, String>(15)?).unwrap_or_default(),
-            vars_mediator: serde_json::from_str(&row.get::<_, String>(16)?).unwrap_or_default(),
-            vars_others: serde_json::from_str(&row.get::<_, String>(17)?).unwrap_or_default(),
-            quant_techniques: serde_json::from_str(&row.get::<_, String>(18)?).unwrap_or_default(),
-            results: serde_json::from_str(&row.get::<_, String>(19)?).unwrap_or_default(),
-            limitations: serde_json::from_str(&row.get::<_, String>(20)?).unwrap_or_default(),
-            implications: serde_json::from_str(&row.get::<_, String>(21)?).unwrap_or_default(),
-            future_plans: serde_json::from_str(&row.get::<_, String>(22)?).unwrap_or_default(),
-            pdf_path: row.get(23)?,
-            pdf_filename: row.get(24)?,
-            user_notes: row.get(25)?,
-            tags: serde_json::from_str(&row.get::<_, String>(26)?).unwrap_or_default(),
-            is_read: row.get(27)?,
-            importance: row.get(28)?,
-            created_at: row.get(29)?,
-            updated_at: row.get(30)?,
-            last_analyzed_at: row.get(31)?,
-        })
-    })?;
-
-    Ok(paper)
-}
-
 /// Generate a citation key for BibTeX (e.g., "smith2023")
 fn generate_citation_key(paper: &Paper) -> String {
     let author_part = paper
@@ -110,6 +105,66 @@ fn generate_citation_key(paper: &Paper) -> String {
     format!("{}{}", author_part, year_part)
 }
 
+/// Letters appended to clashing keys: a, b, ..., z, aa, ab, ...
+fn key_suffix(mut n: usize) -> String {
+    let mut suffix = Vec::new();
+    loop {
+        suffix.push(b'a' + (n % 26) as u8);
+        if n < 26 {
+            break;
+        }
+        n = n / 26 - 1;
+    }
+    suffix.reverse();
+    String::from_utf8(suffix).unwrap_or_default()
+}
+
+/// Find a key no other paper uses, starting from `preferred` (or the generated
+/// key) and adding a letter on a clash: `smith2023`, `smith2023a`, ...
+fn unique_citation_key(
+    conn: &rusqlite::Connection,
+    paper: &Paper,
+    preferred: Option<&str>,
+) -> Result<String, AppError> {
+    let base = preferred
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .unwrap_or_else(|| generate_citation_key(paper));
+    let mut key = base.clone();
+    let mut n = 0;
+    while crate::db::papers::citation_key_taken(conn, &key, &paper.id)? {
+        key = format!("{}{}", base, key_suffix(n));
+        n += 1;
+    }
+    Ok(key)
+}
+
+/// Give a paper a citation key if it has none yet; once set it is reused on
+/// every export
+pub(crate) fn ensure_citation_key(
+    conn: &rusqlite::Connection,
+    paper: &mut Paper,
+) -> Result<(), AppError> {
+    if paper.citation_key.is_empty() {
+        let key = unique_citation_key(conn, paper, None)?;
+        crate::db::papers::set_citation_key(conn, &paper.id, &key)?;
+        paper.citation_key = key;
+    }
+    Ok(())
+}
+
+/// Assign a key to a new paper when its author or year is known. Papers
+/// created without either get one on first export instead.
+pub(crate) fn assign_initial_citation_key(
+    conn: &rusqlite::Connection,
+    paper: &mut Paper,
+) -> Result<(), AppError> {
+    if !paper.author.trim().is_empty() || paper.year > 0 {
+        ensure_citation_key(conn, paper)?;
+    }
+    Ok(())
+}
+
 /// Escape special BibTeX characters
 fn escape_bibtex(text: &str) -> String {
     text.replace('&', r"\&")
@@ -123,48 +178,136 @@ fn escape_bibtex(text: &str) -> String {
         .replace('^', r"\textasciicircum{}")
 }
 
-/// Format a single paper as BibTeX
-fn format_bibtex(paper: &Paper) -> String {
-    let citation_key = generate_citation_key(paper);
-    let mut bibtex = format!("@article{{{},\n", citation_key);
+/// Extra fields added to every BibTeX entry, one `name = value` per line.
+/// Values may use `{doi}`, `{url}`, `{tags}`, `{notes}` and the other paper
+/// fields below; a field whose value comes out empty is left out.
+const BIBTEX_FIELDS_SETTING: &str = "bibtex_custom_fields";
+
+/// Use the requested entry type, or `article` when none is given
+fn bibtex_entry_type(entry_type: Option<String>) -> Result<String, AppError> {
+    let entry_type = entry_type
+        .map(|t| t.trim().to_lowercase())
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| "article".to_string());
+    if !BIBTEX_ENTRY_TYPES.contains(&entry_type.as_str()) {
+        return Err(AppError::Validation(format!(
+            "Unknown BibTeX entry type: {}",
+            entry_type
+        )));
+    }
+    Ok(entry_type)
+}
+
+/// Parse the custom field template into (field name, value template) pairs
+fn parse_bibtex_field_template(template: &str) -> Vec<(String, String)> {
+    template
+        .lines()
+        .filter_map(|line| {
+            let (name, value) = line.split_once('=')?;
+            let name = name.trim().to_lowercase();
+            let valid = !name.is_empty()
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            valid.then(|| (name, value.trim().to_string()))
+        })
+        .collect()
+}
+
+fn bibtex_custom_fields(db: &DbConnection) -> Result<Vec<(String, String)>, AppError> {
+    let conn = db.get()?;
+    let template = crate::db::settings::get_setting_string(&conn, BIBTEX_FIELDS_SETTING)?;
+    Ok(template.map(|t| parse_bibtex_field_template(&t)).unwrap_or_default())
+}
+
+/// Fill a custom field template; substituted values are escaped, the
+/// template's own text is kept as raw BibTeX
+fn fill_bibtex_template(template: &str, paper: &Paper) -> String {
+    let year = if paper.year > 0 { paper.year.to_string() } else { String::new() };
+    let tags = paper.tags.join(", ");
+    let placeholders = [
+        ("{title}", paper.title.as_str()),
+        ("{author}", paper.author.as_str()),
+        ("{year}", year.as_str()),
+        ("{publisher}", paper.publisher.as_str()),
+        ("{doi}", paper.doi.as_str()),
+        ("{url}", paper.url.as_str()),
+        ("{keywords}", paper.keywords.as_str()),
+        ("{abstract}", paper.abstract_text.as_str()),
+        ("{tags}", tags.as_str()),
+        ("{notes}", paper.user_notes.as_str()),
+    ];
+
+    let mut value = template.to_string();
+    for (placeholder, field) in placeholders {
+        value = value.replace(placeholder, &escape_bibtex(field.trim()));
+    }
+    value.trim().to_string()
+}
+
+/// Format a single paper as BibTeX. The paper's own entry type wins over
+/// `default_type`, and custom fields replace built-in ones of the same name.
+fn format_bibtex(paper: &Paper, default_type: &str, custom_fields: &[(String, String)]) -> String {
+    let citation_key = if paper.citation_key.is_empty() {
+        generate_citation_key(paper)
+    } else {
+        paper.citation_key.clone()
+    };
+    let entry_type = if paper.bibtex_type.is_empty() {
+        default_type
+    } else {
+        &paper.bibtex_type
+    };
 
     // Title (required)
-    bibtex.push_str(&format!("  title = {{{}}},\n", escape_bibtex(&paper.title)));
+    let mut fields = vec![("title".to_string(), escape_bibtex(&paper.title))];
 
     // Author
     if !paper.author.is_empty() {
-        bibtex.push_str(&format!("  author = {{{}}},\n", escape_bibtex(&paper.author)));
+        fields.push(("author".to_string(), escape_bibtex(&paper.author)));
     }
 
     // Year
     if paper.year > 0 {
-        bibtex.push_str(&format!("  year = {{{}}},\n", paper.year));
+        fields.push(("year".to_string(), paper.year.to_string()));
     }
 
-    // Publisher/Journal
+    // Publisher/Journal, named for where the entry type expects it
     if !paper.publisher.is_empty() {
-        bibtex.push_str(&format!(
-            "  journal = {{{}}},\n",
-            escape_bibtex(&paper.publisher)
-        ));
+        let venue_field = match entry_type {
+            "article" => "journal",
+            "inproceedings" | "incollection" => "booktitle",
+            "misc" => "howpublished",
+            "phdthesis" | "mastersthesis" => "school",
+            "techreport" => "institution",
+            _ => "publisher",
+        };
+        fields.push((venue_field.to_string(), escape_bibtex(&paper.publisher)));
     }
 
     // Keywords
     if !paper.keywords.is_empty() {
-        bibtex.push_str(&format!(
-            "  keywords = {{{}}},\n",
-            escape_bibtex(&paper.keywords)
-        ));
+        fields.push(("keywords".to_string(), escape_bibtex(&paper.keywords)));
     }
 
     // Subject as abstract or note
     if !paper.subject.is_empty() {
-        bibtex.push_str(&format!(
-            "  abstract = {{{}}},\n",
-            escape_bibtex(&paper.subject)
-        ));
+        fields.push(("abstract".to_string(), escape_bibtex(&paper.subject)));
+    }
+
+    for (name, template) in custom_fields {
+        let value = fill_bibtex_template(template, paper);
+        if value.is_empty() {
+            continue;
+        }
+        match fields.iter_mut().find(|(field, _)| field == name) {
+            Some(field) => field.1 = value,
+            None => fields.push((name.clone(), value)),
+        }
     }
 
+    let mut bibtex = format!("@{}{{{},\n", entry_type, citation_key);
+    for (name, value) in fields {
+        bibtex.push_str(&format!("  {} = {{{}}},\n", name, value));
+    }
     bibtex.push('}');
     bibtex
 }
@@ -224,7 +367,7 @@ fn format_ris(paper: &Paper) -> String {
 
 /// Parse author string into structured format
 /// Handles formats like "Smith, John" or "John Smith" or "Smith, J."
-fn parse_authors(author_str: &str) -> Vec<(String, String)> {
+pub(crate) fn parse_authors(author_str: &str) -> Vec<(String, String)> {
     let mut authors = Vec::new();
 
     for author in author_str.split(';').chain(
@@ -552,11 +695,18 @@ fn format_harvard(paper: &Paper) -> String {
     citation
 }
 
-/// Export a single paper as BibTeX
+/// Export a single paper as BibTeX, as `entry_type` unless the paper sets its own
 #[tauri::command]
-pub async fn export_bibtex(paper_id: String, db: State<'_, DbConnection>) -> Result<CitationExport, AppError> {
-    let paper = get_paper_by_id(&db, &paper_id)?;
-    let content = format_bibtex(&paper);
+pub async fn export_bibtex(
+    paper_id: String,
+    entry_type: Option<String>,
+    db: State<'_, DbConnection>,
+) -> Result<CitationExport, AppError> {
+    let entry_type = bibtex_entry_type(entry_type)?;
+    let custom_fields = bibtex_custom_fields(&db)?;
+    let mut paper = crate::db::papers::get_paper(&*db.get()?, &paper_id)?;
+    ensure_citation_key(&*db.get()?, &mut paper)?;
+    let content = format_bibtex(&paper, &entry_type, &custom_fields);
 
     Ok(CitationExport {
         format: "bibtex".to_string(),
@@ -569,13 +719,17 @@ pub async fn export_bibtex(paper_id: String, db: State<'_, DbConnection>) -> Res
 #[tauri::command]
 pub async fn export_bibtex_batch(
     paper_ids: Vec<String>,
+    entry_type: Option<String>,
     db: State<'_, DbConnection>,
 ) -> Result<BatchCitationExport, AppError> {
+    let entry_type = bibtex_entry_type(entry_type)?;
+    let custom_fields = bibtex_custom_fields(&db)?;
     let mut bibtex_entries = Vec::new();
 
     for paper_id in &paper_ids {
-        let paper = get_paper_by_id(&db, paper_id)?;
-        bibtex_entries.push(format_bibtex(&paper));
+        let mut paper = crate::db::papers::get_paper(&*db.get()?, paper_id)?;
+        ensure_citation_key(&*db.get()?, &mut paper)?;
+        bibtex_entries.push(format_bibtex(&paper, &entry_type, &custom_fields));
     }
 
     Ok(BatchCitationExport {
@@ -588,7 +742,7 @@ pub async fn export_bibtex_batch(
 /// Export a single paper as RIS
 #[tauri::command]
 pub async fn export_ris(paper_id: String, db: State<'_, DbConnection>) -> Result<CitationExport, AppError> {
-    let paper = get_paper_by_id(&db, &paper_id)?;
+    let paper = crate::db::papers::get_paper(&*db.get()?, &paper_id)?;
     let content = format_ris(&paper);
 
     Ok(CitationExport {
@@ -607,7 +761,7 @@ pub async fn export_ris_batch(
     let mut ris_entries = Vec::new();
 
     for paper_id in &paper_ids {
-        let paper = get_paper_by_id(&db, paper_id)?;
+        let paper = crate::db::papers::get_paper(&*db.get()?, paper_id)?;
         ris_entries.push(format_ris(&paper));
     }
 
@@ -618,70 +772,920 @@ pub async fn export_ris_batch(
     })
 }
 
-/// Generate a formatted citation in the specified style
+/// Helper function to get all papers in a folder
+fn get_folder_papers(db: &DbConnection, folder_id: &str) -> Result<Vec<Paper>, AppError> {
+    let conn = db.get()?;
+    crate::db::folders::get_folder(&conn, folder_id)?;
+    let filter = PaperFilter {
+        folder_id: Some(folder_id.to_string()),
+        ..Default::default()
+    };
+    crate::db::papers::get_papers(&conn, &filter, Some("name".to_string()), None, None)
+}
+
+/// Export all papers in a folder as a single BibTeX file
 #[tauri::command]
-pub async fn generate_citation(
+pub async fn export_folder_bibtex(
+    folder_id: String,
+    entry_type: Option<String>,
+    db: State<'_, DbConnection>,
+) -> Result<BatchCitationExport, AppError> {
+    let entry_type = bibtex_entry_type(entry_type)?;
+    let custom_fields = bibtex_custom_fields(&db)?;
+    let mut papers = get_folder_papers(&db, &folder_id)?;
+    {
+        let conn = db.get()?;
+        for paper in papers.iter_mut() {
+            ensure_citation_key(&conn, paper)?;
+        }
+    }
+    let bibtex_entries: Vec<String> = papers
+        .iter()
+        .map(|paper| format_bibtex(paper, &entry_type, &custom_fields))
+        .collect();
+
+    Ok(BatchCitationExport {
+        format: "bibtex".to_string(),
+        content: bibtex_entries.join("\n\n"),
+        paper_count: papers.len(),
+    })
+}
+
+/// Replace a paper's citation key with a fresh one generated from its current
+/// author and year
+#[tauri::command]
+pub async fn regenerate_citation_key(
+    app: AppHandle,
     paper_id: String,
-    style: CitationStyle,
     db: State<'_, DbConnection>,
-) -> Result<CitationExport, AppError> {
-    let paper = get_paper_by_id(&db, &paper_id)?;
+) -> Result<Paper, AppError> {
+    let conn = db.get()?;
+    let paper = crate::db::papers::get_paper(&conn, &paper_id)?;
+    let key = unique_citation_key(&conn, &paper, None)?;
+    crate::db::papers::set_citation_key(&conn, &paper_id, &key)?;
+
+    let paper = crate::db::papers::get_paper(&conn, &paper_id)?;
+    let _ = app.emit("papers-changed", &paper.folder_id);
+    Ok(paper)
+}
+
+/// Export all papers in a folder as a single RIS file
+#[tauri::command]
+pub async fn export_folder_ris(
+    folder_id: String,
+    db: State<'_, DbConnection>,
+) -> Result<BatchCitationExport, AppError> {
+    let papers = get_folder_papers(&db, &folder_id)?;
+    let ris_entries: Vec<String> = papers.iter().map(format_ris).collect();
+
+    Ok(BatchCitationExport {
+        format: "ris".to_string(),
+        content: ris_entries.join("\n"),
+        paper_count: papers.len(),
+    })
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+const CSV_HEADERS: [&str; 27] = [
+    "paper_number",
+    "title",
+    "author",
+    "year",
+    "publisher",
+    "subject",
+    "keywords",
+    "doi",
+    "purposes",
+    "is_qualitative",
+    "is_quantitative",
+    "qual_tools",
+    "vars_independent",
+    "vars_dependent",
+    "vars_moderator",
+    "vars_mediator",
+    "vars_others",
+    "quant_techniques",
+    "results",
+    "limitations",
+    "implications",
+    "future_plans",
+    "user_notes",
+    "tags",
+    "is_read",
+    "importance",
+    "last_analyzed_at",
+];
+
+/// One CSV row per paper, array fields joined with `; `
+fn format_csv(papers: &[Paper]) -> String {
+    let mut lines = vec![CSV_HEADERS.join(",")];
+
+    for paper in papers {
+        let fields = [
+            paper.paper_number.to_string(),
+            paper.title.clone(),
+            paper.author.clone(),
+            if paper.year > 0 { paper.year.to_string() } else { String::new() },
+            paper.publisher.clone(),
+            paper.subject.clone(),
+            paper.keywords.clone(),
+            paper.doi.clone(),
+            paper.purposes.join("; "),
+            paper.is_qualitative.to_string(),
+            paper.is_quantitative.to_string(),
+            paper.qual_tools.join("; "),
+            paper.vars_independent.join("; "),
+            paper.vars_dependent.join("; "),
+            paper.vars_moderator.join("; "),
+            paper.vars_mediator.join("; "),
+            paper.vars_others.join("; "),
+            paper.quant_techniques.join("; "),
+            paper.results.join("; "),
+            paper.limitations.join("; "),
+            paper.implications.join("; "),
+            paper.future_plans.join("; "),
+            paper.user_notes.clone(),
+            paper.tags.join("; "),
+            paper.is_read.to_string(),
+            paper.importance.to_string(),
+            paper.last_analyzed_at.clone().unwrap_or_default(),
+        ];
+        lines.push(fields.iter().map(|f| escape_csv(f)).collect::<Vec<_>>().join(","));
+    }
 
-    let content = match style {
-        CitationStyle::Apa => format_apa(&paper),
-        CitationStyle::Mla => format_mla(&paper),
-        CitationStyle::Chicago => format_chicago(&paper),
-        CitationStyle::Harvard => format_harvard(&paper),
+    // CRLF line endings, as RFC 4180 and spreadsheet apps expect
+    let mut csv = lines.join("\r\n");
+    csv.push_str("\r\n");
+    csv
+}
+
+/// Export papers' bibliographic and analysis fields as CSV, for one folder or the whole library
+#[tauri::command]
+pub async fn export_papers_csv(
+    folder_id: Option<String>,
+    db: State<'_, DbConnection>,
+) -> Result<String, AppError> {
+    let papers = match folder_id {
+        Some(folder_id) => get_folder_papers(&db, &folder_id)?,
+        None => {
+            let conn = db.get()?;
+            crate::db::papers::get_papers(&conn, &PaperFilter::default(), Some("name".to_string()), None, None)?
+        }
     };
+    Ok(format_csv(&papers))
+}
+
+/// Bibliographic fields read from one BibTeX or RIS entry
+#[derive(Debug, Default, PartialEq)]
+struct ImportedEntry {
+    /// Key from the source file, kept when no other paper uses it
+    citation_key: String,
+    title: String,
+    authors: Vec<String>,
+    year: Option<i32>,
+    journal: String,
+    keywords: Vec<String>,
+    doi: String,
+    abstract_text: String,
+}
+
+/// Read the first plausible four-digit year from a date-like field
+fn parse_entry_year(value: &str) -> Option<i32> {
+    value
+        .as_bytes()
+        .windows(4)
+        .position(|w| w.iter().all(u8::is_ascii_digit))
+        .and_then(|start| value[start..start + 4].parse().ok())
+}
+
+/// Turn common LaTeX markup in a BibTeX value back into plain text
+fn unescape_bibtex(text: &str) -> String {
+    const ACCENTS: [(char, &str, &str); 6] = [
+        ('\'', "aeiouyAEIOUY", "áéíóúýÁÉÍÓÚÝ"),
+        ('`', "aeiouAEIOU", "àèìòùÀÈÌÒÙ"),
+        ('"', "aeiouyAEIOU", "äëïöüÿÄËÏÖÜ"),
+        ('^', "aeiouAEIOU", "âêîôûÂÊÎÔÛ"),
+        ('~', "anoANO", "ãñõÃÑÕ"),
+        ('c', "cC", "çÇ"),
+    ];
+
+    let text = text
+        .replace(r"\textasciitilde{}", "~")
+        .replace(r"\textasciicircum{}", "^");
+
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(special @ ('&' | '%' | '$' | '#' | '_' | '{' | '}')) => out.push(special),
+                Some(accent) => {
+                    let table = ACCENTS.iter().find(|(a, _, _)| *a == accent);
+                    // Accent commands may wrap their letter in braces or follow it with a space: \"{o}, \c c
+                    while matches!(chars.peek(), Some('{') | Some(' ')) {
+                        chars.next();
+                    }
+                    match (table, chars.peek().copied()) {
+                        (Some((_, plain, accented)), Some(letter)) if plain.contains(letter) => {
+                            chars.next();
+                            let index = plain.chars().position(|p| p == letter).unwrap();
+                            out.extend(accented.chars().nth(index));
+                        }
+                        // Unknown command such as \emph: drop the command name, keep its argument
+                        _ if accent.is_ascii_alphabetic() => {
+                            while chars.peek().is_some_and(|c| c.is_ascii_alphabetic()) {
+                                chars.next();
+                            }
+                        }
+                        _ => out.push(accent),
+                    }
+                }
+                None => {}
+            },
+            '{' | '}' => {}
+            '~' => out.push(' '),
+            _ => out.push(c),
+        }
+    }
+
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Read one BibTeX field value: a braced or quoted string, a number, or a
+/// `@string` macro, optionally concatenated with `#`. Returns the raw value and
+/// the remaining input.
+fn read_bibtex_value<'a>(input: &'a str, macros: &HashMap<String, String>) -> (String, &'a str) {
+    let mut value = String::new();
+    let mut rest = input.trim_start();
+
+    loop {
+        if let Some(body) = rest.strip_prefix('{') {
+            let mut depth = 1;
+            let end = body
+                .char_indices()
+                .find(|&(_, c)| {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    depth == 0
+                })
+                .map(|(i, _)| i)
+                .unwrap_or(body.len());
+            value.push_str(&body[..end]);
+            rest = body.get(end + 1..).unwrap_or("");
+        } else if let Some(body) = rest.strip_prefix('"') {
+            let mut depth = 0;
+            let end = body
+                .char_indices()
+                .find(|&(_, c)| {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        '"' if depth == 0 => return true,
+                        _ => {}
+                    }
+                    false
+                })
+                .map(|(i, _)| i)
+                .unwrap_or(body.len());
+            value.push_str(&body[..end]);
+            rest = body.get(end + 1..).unwrap_or("");
+        } else {
+            let end = rest
+                .find(|c: char| c == ',' || c == '#' || c == '}' || c == ')' || c.is_whitespace())
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            match macros.get(&word.to_lowercase()) {
+                Some(expanded) => value.push_str(expanded),
+                None => value.push_str(word),
+            }
+            rest = &rest[end..];
+        }
+
+        rest = rest.trim_start();
+        match rest.strip_prefix('#') {
+            Some(next) => rest = next.trim_start(),
+            None => return (value, rest),
+        }
+    }
+}
+
+/// Parse the `name = value` fields of one entry body, stopping at its closing delimiter
+fn read_bibtex_fields<'a>(
+    mut rest: &'a str,
+    macros: &HashMap<String, String>,
+) -> (Vec<(String, String)>, &'a str) {
+    let mut fields = Vec::new();
+
+    loop {
+        rest = rest.trim_start().trim_start_matches(',').trim_start();
+        if rest.is_empty() {
+            return (fields, rest);
+        }
+        if let Some(after) = rest.strip_prefix('}').or_else(|| rest.strip_prefix(')')) {
+            return (fields, after);
+        }
 
-    let format_name = match style {
-        CitationStyle::Apa => "apa",
-        CitationStyle::Mla => "mla",
-        CitationStyle::Chicago => "chicago",
-        CitationStyle::Harvard => "harvard",
+        let Some(eq) = rest.find('=') else {
+            return (fields, "");
+        };
+        let name = rest[..eq].trim().to_lowercase();
+        let (value, after) = read_bibtex_value(&rest[eq + 1..], macros);
+        fields.push((name, value));
+        rest = after;
+    }
+}
+
+/// Parse every entry in a BibTeX file. `@string` macros are expanded;
+/// `@comment` and `@preamble` blocks are skipped.
+fn parse_bibtex(content: &str) -> Vec<ImportedEntry> {
+    let mut entries = Vec::new();
+    let mut macros: HashMap<String, String> = HashMap::new();
+    let mut rest = content;
+
+    while let Some(at) = rest.find('@') {
+        rest = &rest[at + 1..];
+        let Some(open) = rest.find(['{', '(']) else {
+            break;
+        };
+        let entry_type = rest[..open].trim().to_lowercase();
+        let body = &rest[open + 1..];
+
+        match entry_type.as_str() {
+            "comment" | "preamble" => {
+                // Skip the balanced block without interpreting it
+                let (_, after) = read_bibtex_value(&rest[open..], &macros);
+                rest = after;
+            }
+            "string" => {
+                let (fields, after) = read_bibtex_fields(body, &macros);
+                for (name, value) in fields {
+                    macros.insert(name, value);
+                }
+                rest = after;
+            }
+            _ => {
+                let key_end = body.find([',', '}', ')']).unwrap_or(body.len());
+                let (fields, after) = read_bibtex_fields(&body[key_end..], &macros);
+                rest = after;
+
+                let mut entry = ImportedEntry {
+                    citation_key: body[..key_end].trim().to_string(),
+                    ..Default::default()
+                };
+                let mut publisher = String::new();
+                let mut date = String::new();
+                for (name, value) in fields {
+                    let value = unescape_bibtex(&value);
+                    match name.as_str() {
+                        "title" => entry.title = value,
+                        "author" => {
+                            entry.authors = value
+                                .split(" and ")
+                                .map(|a| a.trim().to_string())
+                                .filter(|a| !a.is_empty())
+                                .collect()
+                        }
+                        "year" => entry.year = parse_entry_year(&value),
+                        "date" => date = value,
+                        "journal" | "journaltitle" | "booktitle" => entry.journal = value,
+                        "publisher" => publisher = value,
+                        "keywords" => {
+                            entry.keywords = value
+                                .split([',', ';'])
+                                .map(|k| k.trim().to_string())
+                                .filter(|k| !k.is_empty())
+                                .collect()
+                        }
+                        "doi" => entry.doi = value,
+                        "abstract" => entry.abstract_text = value,
+                        _ => {}
+                    }
+                }
+                if entry.year.is_none() {
+                    entry.year = parse_entry_year(&date);
+                }
+                if entry.journal.is_empty() {
+                    entry.journal = publisher;
+                }
+                entries.push(entry);
+            }
+        }
+    }
+
+    entries
+}
+
+/// Parse every `TY`..`ER` record in a RIS file. Lines without a tag continue
+/// the previous field.
+fn parse_ris(content: &str) -> Vec<ImportedEntry> {
+    let mut entries = Vec::new();
+    let mut fields: Vec<(String, String)> = Vec::new();
+    let mut in_record = false;
+
+    let finish = |fields: &mut Vec<(String, String)>| {
+        let mut entry = ImportedEntry::default();
+        let mut publisher = String::new();
+        for (tag, value) in fields.drain(..) {
+            let value = value.trim().to_string();
+            if value.is_empty() {
+                continue;
+            }
+            match tag.as_str() {
+                "TI" | "T1" if entry.title.is_empty() => entry.title = value,
+                "AU" | "A1" => entry.authors.push(value),
+                "PY" | "Y1" | "DA" if entry.year.is_none() => entry.year = parse_entry_year(&value),
+                "JO" | "JF" | "T2" | "JA" if entry.journal.is_empty() => entry.journal = value,
+                "PB" => publisher = value,
+                "KW" => entry.keywords.push(value),
+                "DO" => entry.doi = value,
+                "AB" | "N2" if entry.abstract_text.is_empty() => entry.abstract_text = value,
+                _ => {}
+            }
+        }
+        if entry.journal.is_empty() {
+            entry.journal = publisher;
+        }
+        entry
+    };
+
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        let tagged = line.len() >= 5
+            && line.is_char_boundary(2)
+            && line[..2].chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+            && line[2..].starts_with("  -");
+
+        if !tagged {
+            // Continuation of a wrapped value
+            if let Some((_, value)) = fields.last_mut().filter(|_| in_record) {
+                if !line.trim().is_empty() {
+                    value.push(' ');
+                    value.push_str(line.trim());
+                }
+            }
+            continue;
+        }
+
+        let tag = &line[..2];
+        let value = line[5..].trim().to_string();
+        match tag {
+            "TY" => {
+                fields.clear();
+                in_record = true;
+            }
+            "ER" => {
+                if in_record {
+                    entries.push(finish(&mut fields));
+                }
+                in_record = false;
+            }
+            _ if in_record => fields.push((tag.to_string(), value)),
+            _ => {}
+        }
+    }
+
+    entries
+}
+
+/// Read a CSL-JSON string field, which some exporters wrap in an array
+fn csl_string(item: &serde_json::Value, key: &str) -> String {
+    match item.get(key) {
+        Some(serde_json::Value::String(s)) => s.trim().to_string(),
+        Some(serde_json::Value::Array(values)) => values
+            .iter()
+            .find_map(|v| v.as_str())
+            .unwrap_or_default()
+            .trim()
+            .to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Parse CSL-JSON as exported by Zotero and most reference managers: a single
+/// item or an array of items
+fn parse_csl_json(content: &str) -> Vec<ImportedEntry> {
+    let items = match serde_json::from_str::<serde_json::Value>(content) {
+        Ok(serde_json::Value::Array(items)) => items,
+        Ok(item @ serde_json::Value::Object(_)) => vec![item],
+        _ => return Vec::new(),
+    };
+
+    items
+        .iter()
+        .map(|item| {
+            let authors = item
+                .get("author")
+                .and_then(|a| a.as_array())
+                .map(|authors| {
+                    authors
+                        .iter()
+                        .filter_map(|a| {
+                            let family = a.get("family").and_then(|v| v.as_str()).unwrap_or("").trim();
+                            let given = a.get("given").and_then(|v| v.as_str()).unwrap_or("").trim();
+                            match (family, given) {
+                                ("", "") => a.get("literal").and_then(|v| v.as_str()).map(str::to_string),
+                                (family, "") => Some(family.to_string()),
+                                ("", given) => Some(given.to_string()),
+                                (family, given) => Some(format!("{}, {}", family, given)),
+                            }
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let issued = item.get("issued");
+            let year = issued
+                .and_then(|d| d.pointer("/date-parts/0/0"))
+                .and_then(|y| y.as_i64().map(|y| y.to_string()).or_else(|| y.as_str().map(str::to_string)))
+                .or_else(|| issued.and_then(|d| d.get("raw")).and_then(|r| r.as_str()).map(str::to_string))
+                .and_then(|date| parse_entry_year(&date));
+
+            let keywords = csl_string(item, "keyword")
+                .split([',', ';'])
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+                .collect();
+
+            ImportedEntry {
+                citation_key: csl_string(item, "id"),
+                title: csl_string(item, "title"),
+                authors,
+                year,
+                journal: csl_string(item, "container-title"),
+                keywords,
+                doi: csl_string(item, "DOI"),
+                abstract_text: csl_string(item, "abstract"),
+            }
+        })
+        .collect()
+}
+
+type EntryParser = fn(&str) -> Vec<ImportedEntry>;
+
+/// Read the first entry of a `.bib`, `.ris` or CSL-JSON `.json` file that sits
+/// next to a PDF under the same name
+fn read_sidecar_entry(pdf_path: &Path) -> Option<ImportedEntry> {
+    let parsers: [(&str, EntryParser); 3] = [
+        ("bib", parse_bibtex),
+        ("ris", parse_ris),
+        ("json", parse_csl_json),
+    ];
+
+    for (ext, parse) in parsers {
+        for candidate in [ext.to_string(), ext.to_uppercase()] {
+            let path = pdf_path.with_extension(candidate);
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            match parse(&content).into_iter().find(|e| !e.title.is_empty()) {
+                Some(entry) => return Some(entry),
+                None => log::warn!("No usable entry in metadata file {}", path.display()),
+            }
+        }
+    }
+    None
+}
+
+/// Fill a freshly imported paper from a metadata sidecar beside its PDF.
+/// Returns `None` when there is no usable sidecar.
+pub(crate) fn apply_sidecar_metadata(
+    conn: &rusqlite::Connection,
+    paper_id: &str,
+    pdf_path: &Path,
+) -> Result<Option<Paper>, AppError> {
+    let Some(entry) = read_sidecar_entry(pdf_path) else {
+        return Ok(None);
+    };
+
+    let non_empty = |s: String| (!s.is_empty()).then_some(s);
+    let input = UpdatePaperInput {
+        title: Some(entry.title),
+        author: non_empty(entry.authors.join("; ")),
+        year: entry.year,
+        publisher: non_empty(entry.journal),
+        keywords: non_empty(entry.keywords.join(", ")),
+        doi: non_empty(entry.doi).map(|doi| crate::commands::paper_search::normalize_doi(&doi)),
+        abstract_text: non_empty(entry.abstract_text),
+        ..Default::default()
+    };
+    crate::db::papers::update_paper(conn, paper_id, input).map(Some)
+}
+
+/// Create papers in a folder from parsed entries, in one transaction.
+/// Entries without a title are skipped.
+fn create_imported_papers(
+    db: &DbConnection,
+    folder_id: &str,
+    entries: Vec<ImportedEntry>,
+) -> Result<Vec<Paper>, AppError> {
+    let conn = db.get()?;
+    crate::db::folders::get_folder(&conn, folder_id)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let mut papers = Vec::new();
+    for entry in entries {
+        if entry.title.is_empty() {
+            log::warn!("Skipping imported entry without a title");
+            continue;
+        }
+
+        let paper = crate::db::papers::create_paper(
+            &tx,
+            CreatePaperInput {
+                folder_id: folder_id.to_string(),
+                title: entry.title,
+                author: Some(entry.authors.join("; ")),
+                year: entry.year,
+                pdf_path: None,
+                pdf_filename: None,
+            },
+        )?;
+
+        let doi = if entry.doi.is_empty() {
+            String::new()
+        } else {
+            crate::commands::paper_search::normalize_doi(&entry.doi)
+        };
+        let key = unique_citation_key(&tx, &paper, Some(&entry.citation_key))?;
+        crate::db::papers::set_citation_key(&tx, &paper.id, &key)?;
+        let paper = crate::db::papers::update_paper(
+            &tx,
+            &paper.id,
+            UpdatePaperInput {
+                publisher: Some(entry.journal),
+                keywords: Some(entry.keywords.join(", ")),
+                doi: Some(doi),
+                abstract_text: Some(entry.abstract_text),
+                ..Default::default()
+            },
+        )?;
+        papers.push(paper);
+    }
+    tx.commit()?;
+
+    Ok(papers)
+}
+
+/// Create papers in a folder from the entries of a BibTeX file
+#[tauri::command]
+pub async fn import_bibtex(
+    app: AppHandle,
+    content: String,
+    folder_id: String,
+    db: State<'_, DbConnection>,
+) -> Result<Vec<Paper>, AppError> {
+    let papers = create_imported_papers(&db, &folder_id, parse_bibtex(&content))?;
+    let _ = app.emit("papers-changed", &folder_id);
+    Ok(papers)
+}
+
+/// Create papers in a folder from the records of a RIS file
+#[tauri::command]
+pub async fn import_ris(
+    app: AppHandle,
+    content: String,
+    folder_id: String,
+    db: State<'_, DbConnection>,
+) -> Result<Vec<Paper>, AppError> {
+    let papers = create_imported_papers(&db, &folder_id, parse_ris(&content))?;
+    let _ = app.emit("papers-changed", &folder_id);
+    Ok(papers)
+}
+
+fn format_citation(paper: &Paper, style: &CitationStyle) -> String {
+    match style {
+        CitationStyle::Apa => format_apa(paper),
+        CitationStyle::Mla => format_mla(paper),
+        CitationStyle::Chicago => format_chicago(paper),
+        CitationStyle::Harvard => format_harvard(paper),
+    }
+}
+
+/// Matches a Pandoc-style citation group such as `[@smith2023]` or
+/// `[@smith2023, p. 4; @doe2021]`
+const CITATION_GROUP_PATTERN: &str = r"\[(\s*@[^\[\]]+)\]";
+
+/// Author part of an in-text citation: one or two last names, else the first
+/// followed by "et al."
+fn in_text_authors(paper: &Paper, style: &CitationStyle) -> String {
+    let names: Vec<String> = parse_authors(&paper.author)
+        .into_iter()
+        .map(|(last, _)| last)
+        .collect();
+    let and = match style {
+        CitationStyle::Apa => "&",
+        _ => "and",
+    };
+    match names.as_slice() {
+        [] => paper.title.clone(),
+        [only] => only.clone(),
+        [first, second] => format!("{} {} {}", first, and, second),
+        [first, ..] => format!("{} et al.", first),
+    }
+}
+
+/// One cited work inside an in-text citation, with its optional locator
+/// (`p. 4`). MLA and Chicago print the page number bare.
+fn format_in_text(paper: &Paper, locator: &str, style: &CitationStyle) -> String {
+    let authors = in_text_authors(paper, style);
+    let year = if paper.year > 0 {
+        paper.year.to_string()
+    } else {
+        "n.d.".to_string()
+    };
+    let bare_locator = locator
+        .trim_start_matches("pp.")
+        .trim_start_matches("p.")
+        .trim();
+
+    let mut citation = match style {
+        CitationStyle::Apa | CitationStyle::Harvard => format!("{}, {}", authors, year),
+        CitationStyle::Mla => authors,
+        CitationStyle::Chicago => format!("{} {}", authors, year),
     };
+    if !locator.is_empty() {
+        match style {
+            CitationStyle::Apa | CitationStyle::Harvard => {
+                citation.push_str(&format!(", {}", locator))
+            }
+            CitationStyle::Mla => citation.push_str(&format!(" {}", bare_locator)),
+            CitationStyle::Chicago => citation.push_str(&format!(", {}", bare_locator)),
+        }
+    }
+    citation
+}
+
+/// Replace `[@key]` citation groups in `text` with in-text citations for the
+/// papers in `papers_by_key`, returning the new text and the cited papers in
+/// order of first use. Groups naming an unknown key are left as written.
+pub(crate) fn expand_citations<'a>(
+    text: &str,
+    papers_by_key: &HashMap<String, &'a Paper>,
+    style: &CitationStyle,
+) -> (String, Vec<&'a Paper>) {
+    let re = regex::Regex::new(CITATION_GROUP_PATTERN).unwrap();
+    let mut cited: Vec<&Paper> = Vec::new();
+
+    let expanded = re.replace_all(text, |caps: &regex::Captures| {
+        let mut works = Vec::new();
+        for item in caps[1].split(';') {
+            let Some(item) = item.trim().strip_prefix('@') else {
+                return caps[0].to_string();
+            };
+            let (key, locator) = item.split_once(',').unwrap_or((item, ""));
+            let Some(paper) = papers_by_key.get(key.trim()).copied() else {
+                return caps[0].to_string();
+            };
+            works.push((paper, locator.trim()));
+        }
+
+        for (paper, _) in &works {
+            if !cited.iter().any(|p| p.id == paper.id) {
+                cited.push(paper);
+            }
+        }
+        let parts: Vec<String> = works
+            .iter()
+            .map(|(paper, locator)| format_in_text(paper, locator, style))
+            .collect();
+        format!("({})", parts.join("; "))
+    });
+
+    (expanded.into_owned(), cited)
+}
+
+/// A Markdown References section listing `papers` alphabetically in `style`
+pub(crate) fn format_references_section(papers: &[&Paper], style: &CitationStyle) -> String {
+    let mut entries: Vec<String> = papers.iter().map(|p| format_citation(p, style)).collect();
+    entries.sort_by_key(|entry| entry.to_lowercase());
+
+    let mut section = String::from("## References\n\n");
+    for entry in entries {
+        section.push_str(&format!("{}\n\n", entry));
+    }
+    section
+}
+
+/// Generate a formatted citation in the specified style, or the default style
+#[tauri::command]
+pub async fn generate_citation(
+    paper_id: String,
+    style: Option<CitationStyle>,
+    db: State<'_, DbConnection>,
+) -> Result<CitationExport, AppError> {
+    let style = resolve_style(&db, style)?;
+    let paper = crate::db::papers::get_paper(&*db.get()?, &paper_id)?;
+
+    let content = format_citation(&paper, &style);
 
     Ok(CitationExport {
-        format: format_name.to_string(),
+        format: style.name().to_string(),
         content,
         paper_id,
     })
 }
 
-/// Generate formatted citations for multiple papers
+/// Generate formatted citations for multiple papers, in the default style unless given
 #[tauri::command]
 pub async fn generate_citation_batch(
     paper_ids: Vec<String>,
-    style: CitationStyle,
+    style: Option<CitationStyle>,
     db: State<'_, DbConnection>,
 ) -> Result<BatchCitationExport, AppError> {
+    let style = resolve_style(&db, style)?;
     let mut citations = Vec::new();
 
     for paper_id in &paper_ids {
-        let paper = get_paper_by_id(&db, paper_id)?;
-        let citation = match style {
-            CitationStyle::Apa => format_apa(&paper),
-            CitationStyle::Mla => format_mla(&paper),
-            CitationStyle::Chicago => format_chicago(&paper),
-            CitationStyle::Harvard => format_harvard(&paper),
-        };
-        citations.push(citation);
+        let paper = crate::db::papers::get_paper(&*db.get()?, paper_id)?;
+        citations.push(format_citation(&paper, &style));
     }
 
-    let format_name = match style {
-        CitationStyle::Apa => "apa",
-        CitationStyle::Mla => "mla",
-        CitationStyle::Chicago => "chicago",
-        CitationStyle::Harvard => "harvard",
-    };
-
     Ok(BatchCitationExport {
-        format: format_name.to_string(),
+        format: style.name().to_string(),
         content: citations.join("\n\n"),
         paper_count: paper_ids.len(),
     })
 }
 
+/// Render one annotated bibliography entry: the citation, the reader's notes,
+/// then each highlight with its page and note
+fn format_annotated_entry(paper: &Paper, style: &CitationStyle, highlights: &[Highlight]) -> String {
+    let mut entry = format!("{}\n\n", format_citation(paper, style));
+
+    let notes = paper.user_notes.trim();
+    if !notes.is_empty() {
+        entry.push_str(&format!("{}\n\n", notes));
+    }
+
+    let highlights: Vec<&Highlight> = highlights
+        .iter()
+        .filter(|h| !h.selected_text.trim().is_empty())
+        .collect();
+    if !highlights.is_empty() {
+        entry.push_str("**Highlights**\n\n");
+        for highlight in highlights {
+            let text = highlight.selected_text.split_whitespace().collect::<Vec<_>>().join(" ");
+            entry.push_str(&format!("- \"{}\" (p. {})\n", text, highlight.page_number));
+            let note = highlight.note.trim();
+            if !note.is_empty() {
+                entry.push_str(&format!("  - {}\n", note.replace('\n', " ")));
+            }
+        }
+        entry.push('\n');
+    }
+
+    entry
+}
+
+/// Export papers as an annotated bibliography in Markdown: each citation in
+/// the given style followed by the paper's notes and highlights
+#[tauri::command]
+pub async fn export_annotated_bibliography(
+    paper_ids: Vec<String>,
+    style: CitationStyle,
+    db: State<'_, DbConnection>,
+) -> Result<String, AppError> {
+    let mut markdown = String::from("# Annotated Bibliography\n\n");
+
+    for paper_id in &paper_ids {
+        let paper = crate::db::papers::get_paper(&*db.get()?, paper_id)?;
+        let highlights = {
+            let conn = db.get()?;
+            crate::db::highlights::get_highlights(&conn, paper_id, None)?
+        };
+        markdown.push_str(&format_annotated_entry(&paper, &style, &highlights));
+    }
+
+    Ok(markdown)
+}
+
+/// Get the style used by citation commands called without one
+#[tauri::command]
+pub fn get_default_citation_style(db: State<'_, DbConnection>) -> Result<CitationStyle, AppError> {
+    default_citation_style(&*db.get()?)
+}
+
+/// Set the style used by citation commands called without one
+#[tauri::command]
+pub fn set_default_citation_style(
+    db: State<'_, DbConnection>,
+    style: CitationStyle,
+) -> Result<(), AppError> {
+    let conn = db.get()?;
+    crate::db::settings::set_setting(&conn, DEFAULT_CITATION_STYLE_SETTING, style.name())
+}
+
 /// Get all available citation styles
 #[tauri::command]
 pub async fn get_citation_styles() -> Result<Vec<String>, AppError> {
@@ -694,10 +1698,10 @@ pub async fn get_citation_styles() -> Result<Vec<String>, AppError> {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
 
-    fn create_test_paper() -> Paper {
+    pub(crate) fn create_test_paper() -> Paper {
         Paper {
             id: "test-123".to_string(),
             folder_id: "folder-1".to_string(),
@@ -708,6 +1712,9 @@ mod tests {
             title: "A Study on Machine Learning Approaches".to_string(),
             publisher: "Journal of AI Research".to_string(),
             subject: "This paper explores various ML approaches.".to_string(),
+            doi: String::new(),
+            abstract_text: String::new(),
+            url: String::new(),
             purposes: vec![],
             is_qualitative: false,
             is_quantitative: true,
@@ -731,19 +1738,41 @@ mod tests {
             created_at: String::new(),
             updated_at: String::new(),
             last_analyzed_at: None,
+            last_opened_at: None,
+            deleted_at: None,
+            bibtex_type: String::new(),
+            citation_key: String::new(),
+            subject_translated: String::new(),
+            highlight_count: 0,
         }
     }
 
     #[test]
     fn test_bibtex_format() {
         let paper = create_test_paper();
-        let bibtex = format_bibtex(&paper);
+        let bibtex = format_bibtex(&paper, "article", &[]);
         assert!(bibtex.contains("@article{smith2023"));
         assert!(bibtex.contains("title = {A Study on Machine Learning Approaches}"));
         assert!(bibtex.contains("author = {Smith, John; Doe, Jane}"));
         assert!(bibtex.contains("year = {2023}"));
     }
 
+    #[test]
+    fn test_bibtex_entry_type_and_custom_fields() {
+        let mut paper = create_test_paper();
+        paper.bibtex_type = "inproceedings".to_string();
+        paper.url = "https://example.com/paper".to_string();
+        let custom_fields = parse_bibtex_field_template("url = {url}\nnote = Read for \\emph{thesis}\ndoi = {doi}");
+
+        let bibtex = format_bibtex(&paper, "misc", &custom_fields);
+        assert!(bibtex.starts_with("@inproceedings{smith2023"));
+        assert!(bibtex.contains("booktitle = "));
+        assert!(bibtex.contains("url = {https://example.com/paper}"));
+        assert!(bibtex.contains("note = {Read for \\emph{thesis}}"));
+        // Empty after substitution, so left out
+        assert!(!bibtex.contains("doi = "));
+    }
+
     #[test]
     fn test_ris_format() {
         let paper = create_test_paper();
@@ -755,6 +1784,31 @@ mod tests {
         assert!(ris.contains("ER  -"));
     }
 
+    #[test]
+    fn test_expand_citations() {
+        let smith = create_test_paper();
+        let mut lee = create_test_paper();
+        lee.id = "test-456".to_string();
+        lee.author = "Lee, Min".to_string();
+        lee.year = 2021;
+        let papers_by_key: HashMap<String, &Paper> =
+            [("smith2023".to_string(), &smith), ("lee2021".to_string(), &lee)].into();
+
+        let text = "As shown [@smith2023, p. 4; @lee2021], and again [@lee2021]. Not [@missing].";
+        let (apa, cited) = expand_citations(text, &papers_by_key, &CitationStyle::Apa);
+        assert_eq!(
+            apa,
+            "As shown (Smith & Doe, 2023, p. 4; Lee, 2021), and again (Lee, 2021). Not [@missing]."
+        );
+        assert_eq!(cited.len(), 2);
+
+        let (mla, _) = expand_citations(text, &papers_by_key, &CitationStyle::Mla);
+        assert!(mla.starts_with("As shown (Smith and Doe 4; Lee)"));
+
+        let references = format_references_section(&cited, &CitationStyle::Apa);
+        assert!(references.find("Lee, M.").unwrap() < references.find("Smith, J.").unwrap());
+    }
+
     #[test]
     fn test_apa_format() {
         let paper = create_test_paper();
@@ -795,6 +1849,14 @@ mod tests {
         assert_eq!(key, "smith2023");
     }
 
+    #[test]
+    fn test_key_suffix() {
+        assert_eq!(key_suffix(0), "a");
+        assert_eq!(key_suffix(25), "z");
+        assert_eq!(key_suffix(26), "aa");
+        assert_eq!(key_suffix(27), "ab");
+    }
+
     #[test]
     fn test_parse_authors() {
         // Test "Last, First" format
@@ -808,4 +1870,77 @@ mod tests {
         assert_eq!(authors.len(), 1);
         assert_eq!(authors[0], ("Smith".to_string(), "John".to_string()));
     }
+
+    #[test]
+    fn test_csv_format() {
+        let mut paper = create_test_paper();
+        paper.results = vec!["Accuracy improved".to_string(), "Said \"robust\"".to_string()];
+        paper.user_notes = "line one\nline two".to_string();
+        let csv = format_csv(&[paper]);
+        let mut lines = csv.split("\r\n");
+        assert!(lines.next().unwrap().starts_with("paper_number,title,author,year"));
+        let row = lines.next().unwrap();
+        assert!(row.starts_with("1,A Study on Machine Learning Approaches,\"Smith, John; Doe, Jane\",2023,"));
+        assert!(row.contains(",\"Accuracy improved; Said \"\"robust\"\"\","));
+        assert!(csv.contains("\"line one\nline two\""));
+    }
+
+    #[test]
+    fn test_parse_bibtex() {
+        let content = r#"
+@string{jair = "Journal of AI Research"}
+@comment{exported from another manager}
+@article{smith2023,
+  title = {A Study on {Machine Learning} Approaches},
+  author = {Smith, John and Doe, Jane},
+  year = 2023,
+  journal = jair,
+  keywords = {machine learning, AI},
+  doi = {10.1000/xyz123},
+  abstract = "Results for M{\"u}ller \& co."
+}
+@inproceedings{lee2020, title={Second}, booktitle={Proc. Conf.}, date={2020-05-01}}
+"#;
+        let entries = parse_bibtex(content);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].citation_key, "smith2023");
+        assert_eq!(entries[0].title, "A Study on Machine Learning Approaches");
+        assert_eq!(entries[0].authors, vec!["Smith, John", "Doe, Jane"]);
+        assert_eq!(entries[0].year, Some(2023));
+        assert_eq!(entries[0].journal, "Journal of AI Research");
+        assert_eq!(entries[0].keywords, vec!["machine learning", "AI"]);
+        assert_eq!(entries[0].doi, "10.1000/xyz123");
+        assert_eq!(entries[0].abstract_text, "Results for Müller & co.");
+        assert_eq!(entries[1].journal, "Proc. Conf.");
+        assert_eq!(entries[1].year, Some(2020));
+    }
+
+    #[test]
+    fn test_parse_ris() {
+        let content = "TY  - JOUR\nTI  - A Study on Machine Learning\n  Approaches\nAU  - Smith, John\nAU  - Doe, Jane\nPY  - 2023///\nJO  - Journal of AI Research\nKW  - AI\nDO  - 10.1000/xyz123\nER  - \n";
+        let entries = parse_ris(content);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "A Study on Machine Learning Approaches");
+        assert_eq!(entries[0].authors, vec!["Smith, John", "Doe, Jane"]);
+        assert_eq!(entries[0].year, Some(2023));
+        assert_eq!(entries[0].journal, "Journal of AI Research");
+        assert_eq!(entries[0].keywords, vec!["AI"]);
+        assert_eq!(entries[0].doi, "10.1000/xyz123");
+    }
+
+    #[test]
+    fn test_parse_csl_json() {
+        let content = r#"[{"type": "article-journal", "title": "A Study on Machine Learning",
+            "author": [{"family": "Smith", "given": "John"}, {"literal": "AI Consortium"}],
+            "issued": {"date-parts": [[2023, 5]]}, "container-title": "Journal of AI Research",
+            "keyword": "AI, ML", "DOI": "10.1000/xyz123"}]"#;
+        let entries = parse_csl_json(content);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].title, "A Study on Machine Learning");
+        assert_eq!(entries[0].authors, vec!["Smith, John", "AI Consortium"]);
+        assert_eq!(entries[0].year, Some(2023));
+        assert_eq!(entries[0].journal, "Journal of AI Research");
+        assert_eq!(entries[0].keywords, vec!["AI", "ML"]);
+        assert_eq!(entries[0].doi, "10.1000/xyz123");
+    }
 }
@@ -0,0 +1,72 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::db::DbConnection;
+use crate::error::AppError;
+
+/// Snapshot of the app's storage and configuration for troubleshooting.
+/// Credentials are only reported as present or not.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    pub db_path: String,
+    pub db_size_bytes: u64,
+    pub schema_version: i32,
+    pub paper_count: i64,
+    pub highlight_count: i64,
+    pub indexed_page_count: i64,
+    pub google_connected: bool,
+    pub gemini_key_set: bool,
+    pub pdf_dir: String,
+    pub pdf_dir_size_bytes: u64,
+}
+
+/// Total size of the files under a directory
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+fn count_rows(conn: &rusqlite::Connection, table: &str) -> Result<i64, AppError> {
+    Ok(conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))?)
+}
+
+#[tauri::command]
+pub fn get_diagnostics(app: AppHandle, db: State<'_, DbConnection>) -> Result<Diagnostics, AppError> {
+    let app_data = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    let db_path = app_data.join("papers.db");
+    let pdf_dir = crate::commands::pdf::get_pdf_dir(&app)?;
+
+    let conn = db.get()?;
+    let schema_version = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    let has_setting = |key: &str| -> Result<bool, AppError> {
+        Ok(crate::db::settings::get_setting(&conn, key)?.is_some_and(|v| !v.trim().is_empty()))
+    };
+
+    Ok(Diagnostics {
+        db_size_bytes: std::fs::metadata(&db_path).map(|m| m.len()).unwrap_or(0),
+        db_path: db_path.to_string_lossy().to_string(),
+        schema_version,
+        paper_count: count_rows(&conn, "papers")?,
+        highlight_count: count_rows(&conn, "highlights")?,
+        indexed_page_count: count_rows(&conn, "pdf_pages")?,
+        google_connected: has_setting("google_access_token")?,
+        gemini_key_set: has_setting("gemini_api_key")?,
+        pdf_dir_size_bytes: dir_size(&pdf_dir),
+        pdf_dir: pdf_dir.to_string_lossy().to_string(),
+    })
+}
@@ -2,7 +2,7 @@ use tauri::{AppHandle, Emitter, State};
 
 use crate::db::DbConnection;
 use crate::error::AppError;
-use crate::models::{CreateFolderInput, Folder, UpdateFolderInput};
+use crate::models::{CreateFolderInput, Folder, FolderWithCount, UpdateFolderInput};
 
 #[tauri::command]
 pub fn get_folders(db: State<'_, DbConnection>, topic_id: String) -> Result<Vec<Folder>, AppError> {
@@ -10,6 +10,16 @@ pub fn get_folders(db: State<'_, DbConnection>, topic_id: String) -> Result<Vec<
     crate::db::folders::get_folders(&conn, &topic_id)
 }
 
+/// Get a topic's folders with the number of papers in each
+#[tauri::command]
+pub fn get_folders_with_counts(
+    db: State<'_, DbConnection>,
+    topic_id: String,
+) -> Result<Vec<FolderWithCount>, AppError> {
+    let conn = db.get()?;
+    crate::db::folders::get_folders_with_counts(&conn, &topic_id)
+}
+
 #[tauri::command]
 pub fn get_all_folders(db: State<'_, DbConnection>) -> Result<Vec<Folder>, AppError> {
     let conn = db.get()?;
@@ -47,6 +57,37 @@ pub fn update_folder(
     Ok(folder)
 }
 
+/// Move a folder to a different topic
+#[tauri::command]
+pub fn move_folder(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    folder_id: String,
+    target_topic_id: String,
+) -> Result<Folder, AppError> {
+    let conn = db.get()?;
+    let previous = crate::db::folders::get_folder(&conn, &folder_id)?;
+    let folder = crate::db::folders::move_folder(&conn, &folder_id, &target_topic_id)?;
+    let _ = app.emit("folders-changed", &previous.topic_id);
+    let _ = app.emit("folders-changed", &folder.topic_id);
+    let _ = app.emit("topics-changed", ());
+    Ok(folder)
+}
+
+/// Set folder sort order within a topic to match the given ID order
+#[tauri::command]
+pub fn reorder_folders(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    topic_id: String,
+    ordered_ids: Vec<String>,
+) -> Result<Vec<Folder>, AppError> {
+    let conn = db.get()?;
+    let folders = crate::db::folders::reorder_folders(&conn, &topic_id, &ordered_ids)?;
+    let _ = app.emit("folders-changed", &topic_id);
+    Ok(folders)
+}
+
 #[tauri::command]
 pub fn delete_folder(
     app: AppHandle,
@@ -1,19 +1,26 @@
-use crate::db::DbConnection;
+use crate::db::{settings, DbConnection};
 use crate::error::AppError;
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tauri::{Emitter, State};
 
-// Google OAuth configuration - loaded from environment variables at build time
-// Set these in your shell before building:
+// Google OAuth configuration - resolved at runtime from settings, then the environment,
+// then values baked in at build time. Set these in your shell before building:
 //   export GOOGLE_CLIENT_ID="your-client-id.apps.googleusercontent.com"
 //   export GOOGLE_CLIENT_SECRET="your-client-secret"
 // Or create a .env file in src-tauri/ (it will be gitignored)
-const GOOGLE_CLIENT_ID: &str = env!("GOOGLE_CLIENT_ID");
-const GOOGLE_CLIENT_SECRET: &str = env!("GOOGLE_CLIENT_SECRET");
-const REDIRECT_URI: &str = "http://localhost:8847/oauth/callback";
+const BUILD_CLIENT_ID: Option<&str> = option_env!("GOOGLE_CLIENT_ID");
+const BUILD_CLIENT_SECRET: Option<&str> = option_env!("GOOGLE_CLIENT_SECRET");
+const DEFAULT_REDIRECT_PORT: u16 = 8847;
+const REDIRECT_PATH: &str = "/oauth/callback";
+
+// Setting keys (deliberately not prefixed with `google_`, which is cleared on sign-out)
+const CLIENT_ID_SETTING: &str = "oauth_google_client_id";
+const CLIENT_SECRET_SETTING: &str = "oauth_google_client_secret";
+const REDIRECT_PORT_SETTING: &str = "oauth_redirect_port";
 const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
 const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
 const REVOKE_URL: &str = "https://oauth2.googleapis.com/revoke";
@@ -24,10 +31,71 @@ const SCOPES: &str = "openid email profile https://www.googleapis.com/auth/drive
 // OAuth state for PKCE flow
 static OAUTH_STATE: Mutex<Option<OAuthState>> = Mutex::new(None);
 
+// Running OAuth callback server and the port it is bound to
+static OAUTH_SERVER: Mutex<Option<(Arc<tiny_http::Server>, u16)>> = Mutex::new(None);
+
+const OAUTH_PORT_ATTEMPTS: u16 = 10;
+const OAUTH_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
 #[derive(Debug, Clone)]
 struct OAuthState {
     state: String,
     code_verifier: String,
+    redirect_uri: String,
+}
+
+#[derive(Debug, Clone)]
+struct OAuthConfig {
+    client_id: String,
+    client_secret: String,
+    redirect_port: u16,
+}
+
+// Treat empty values and the documented examples as "not configured"
+fn is_placeholder(value: &str) -> bool {
+    let value = value.trim();
+    value.is_empty()
+        || value.starts_with("YOUR_")
+        || value.starts_with("your-client-")
+}
+
+fn resolve_credential(
+    conn: &rusqlite::Connection,
+    setting_key: &str,
+    env_key: &str,
+    build_value: Option<&str>,
+) -> Result<Option<String>, AppError> {
+    let value = settings::get_setting(conn, setting_key)?
+        .or_else(|| std::env::var(env_key).ok())
+        .or_else(|| build_value.map(str::to_string))
+        .filter(|v| !is_placeholder(v));
+    Ok(value)
+}
+
+/// Load OAuth client credentials, failing up front if they are not configured
+fn load_oauth_config(conn: &rusqlite::Connection) -> Result<OAuthConfig, AppError> {
+    let client_id = resolve_credential(conn, CLIENT_ID_SETTING, "GOOGLE_CLIENT_ID", BUILD_CLIENT_ID)?;
+    let client_secret = resolve_credential(
+        conn,
+        CLIENT_SECRET_SETTING,
+        "GOOGLE_CLIENT_SECRET",
+        BUILD_CLIENT_SECRET,
+    )?;
+
+    let (client_id, client_secret) = match (client_id, client_secret) {
+        (Some(id), Some(secret)) => (id, secret),
+        _ => return Err(AppError::Validation("Google OAuth not configured".to_string())),
+    };
+
+    let redirect_port = settings::get_setting_i32(conn, REDIRECT_PORT_SETTING)?
+        .map(|port| port as u16)
+        .unwrap_or(DEFAULT_REDIRECT_PORT);
+
+    Ok(OAuthConfig {
+        client_id,
+        client_secret,
+        redirect_port,
+    })
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -71,7 +139,20 @@ fn generate_code_challenge(verifier: &str) -> String {
 
 /// Start the Google OAuth flow - returns the authorization URL
 #[tauri::command]
-pub async fn start_google_oauth() -> Result<String, AppError> {
+pub async fn start_google_oauth(db: State<'_, DbConnection>) -> Result<String, AppError> {
+    let config = {
+        let conn = db.get()?;
+        load_oauth_config(&conn)?
+    };
+    // Match the port the callback server actually bound to, if it is running
+    let redirect_port = OAUTH_SERVER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|(_, port)| *port)
+        .unwrap_or(config.redirect_port);
+    let redirect_uri = format!("http://localhost:{}{}", redirect_port, REDIRECT_PATH);
+
     // Generate PKCE values
     let state = generate_random_string(32);
     let code_verifier = generate_random_string(64);
@@ -83,6 +164,7 @@ pub async fn start_google_oauth() -> Result<String, AppError> {
         *oauth_state = Some(OAuthState {
             state: state.clone(),
             code_verifier,
+            redirect_uri: redirect_uri.clone(),
         });
     }
 
@@ -90,8 +172,8 @@ pub async fn start_google_oauth() -> Result<String, AppError> {
     let auth_url = format!(
         "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&state={}&code_challenge={}&code_challenge_method=S256&access_type=offline&prompt=consent",
         AUTH_URL,
-        urlencoding::encode(GOOGLE_CLIENT_ID),
-        urlencoding::encode(REDIRECT_URI),
+        urlencoding::encode(&config.client_id),
+        urlencoding::encode(&redirect_uri),
         urlencoding::encode(SCOPES),
         urlencoding::encode(&state),
         urlencoding::encode(&code_challenge)
@@ -108,25 +190,30 @@ pub async fn handle_google_oauth_callback(
     db: State<'_, DbConnection>,
 ) -> Result<GoogleTokens, AppError> {
     // Verify state
-    let code_verifier = {
+    let (code_verifier, redirect_uri) = {
         let oauth_state = OAUTH_STATE.lock().unwrap();
         match &*oauth_state {
-            Some(s) if s.state == state => s.code_verifier.clone(),
+            Some(s) if s.state == state => (s.code_verifier.clone(), s.redirect_uri.clone()),
             _ => return Err(AppError::Auth("Invalid OAuth state".to_string())),
         }
     };
 
+    let config = {
+        let conn = db.get()?;
+        load_oauth_config(&conn)?
+    };
+
     // Exchange code for tokens
-    let client = reqwest::Client::new();
+    let client = crate::commands::http::client()?;
     let token_response = client
         .post(TOKEN_URL)
         .form(&[
-            ("client_id", GOOGLE_CLIENT_ID),
-            ("client_secret", GOOGLE_CLIENT_SECRET),
+            ("client_id", config.client_id.as_str()),
+            ("client_secret", config.client_secret.as_str()),
             ("code", &code),
             ("code_verifier", &code_verifier),
             ("grant_type", "authorization_code"),
-            ("redirect_uri", REDIRECT_URI),
+            ("redirect_uri", &redirect_uri),
         ])
         .send()
         .await
@@ -202,7 +289,7 @@ pub fn get_google_tokens(db: State<'_, DbConnection>) -> Result<Option<GoogleTok
 #[tauri::command]
 pub async fn refresh_google_token(db: State<'_, DbConnection>) -> Result<GoogleTokens, AppError> {
     // Load tokens first, then drop the connection before async calls
-    let (current_tokens, refresh_token) = {
+    let (current_tokens, refresh_token, config) = {
         let conn = db.get().map_err(|e| AppError::Database(e.to_string()))?;
         let current = load_tokens(&conn)?
             .ok_or_else(|| AppError::Auth("No tokens stored".to_string()))?;
@@ -210,16 +297,16 @@ pub async fn refresh_google_token(db: State<'_, DbConnection>) -> Result<GoogleT
             .refresh_token
             .clone()
             .ok_or_else(|| AppError::Auth("No refresh token available".to_string()))?;
-        (current, refresh)
+        (current, refresh, load_oauth_config(&conn)?)
     }; // conn is dropped here
 
     // Now make the async HTTP request
-    let client = reqwest::Client::new();
+    let client = crate::commands::http::client()?;
     let token_response = client
         .post(TOKEN_URL)
         .form(&[
-            ("client_id", GOOGLE_CLIENT_ID),
-            ("client_secret", GOOGLE_CLIENT_SECRET),
+            ("client_id", config.client_id.as_str()),
+            ("client_secret", config.client_secret.as_str()),
             ("refresh_token", &refresh_token),
             ("grant_type", "refresh_token"),
         ])
@@ -229,6 +316,16 @@ pub async fn refresh_google_token(db: State<'_, DbConnection>) -> Result<GoogleT
 
     if !token_response.status().is_success() {
         let error_text = token_response.text().await.unwrap_or_default();
+
+        // A revoked or expired refresh token can't be recovered without signing in again
+        if is_invalid_grant(&error_text) {
+            let conn = db.get().map_err(|e| AppError::Database(e.to_string()))?;
+            clear_tokens(&conn)?;
+            return Err(AppError::Auth(
+                "Google account disconnected, please sign in again".to_string(),
+            ));
+        }
+
         return Err(AppError::Auth(format!("Token refresh failed: {}", error_text)));
     }
 
@@ -263,8 +360,7 @@ pub async fn revoke_google_tokens(db: State<'_, DbConnection>) -> Result<(), App
     }; // conn is dropped here
 
     // Revoke token with Google if we have one
-    if let Some(token) = access_token {
-        let client = reqwest::Client::new();
+    if let (Some(token), Ok(client)) = (access_token, crate::commands::http::client()) {
         let _ = client
             .post(REVOKE_URL)
             .form(&[("token", &token)])
@@ -274,10 +370,7 @@ pub async fn revoke_google_tokens(db: State<'_, DbConnection>) -> Result<(), App
 
     // Reconnect to clear stored tokens
     let conn = db.get().map_err(|e| AppError::Database(e.to_string()))?;
-    conn.execute("DELETE FROM settings WHERE key LIKE 'google_%'", [])
-        .map_err(|e| AppError::Database(e.to_string()))?;
-
-    Ok(())
+    clear_tokens(&conn)
 }
 
 /// OAuth callback data
@@ -288,56 +381,101 @@ pub struct OAuthCallback {
     pub state: String,
 }
 
-/// Start local OAuth callback server and wait for callback
+/// Start local OAuth callback server and wait for callback.
+/// Tries a small range of ports starting at the configured one and returns the port in use;
+/// the server shuts down after the callback or when `OAUTH_TIMEOUT` elapses.
 #[tauri::command]
-pub async fn start_oauth_server(app: tauri::AppHandle) -> Result<(), AppError> {
+pub async fn start_oauth_server(
+    app: tauri::AppHandle,
+    db: State<'_, DbConnection>,
+) -> Result<u16, AppError> {
     use std::thread;
+    use std::time::Instant;
+
+    let base_port = {
+        let conn = db.get()?;
+        load_oauth_config(&conn)?.redirect_port
+    };
 
+    // Shut down a server left over from an earlier attempt
+    if let Some((previous, _)) = OAUTH_SERVER.lock().unwrap().take() {
+        previous.unblock();
+    }
+
+    let (server, port) = (0..OAUTH_PORT_ATTEMPTS)
+        .filter_map(|offset| base_port.checked_add(offset))
+        .find_map(|port| {
+            tiny_http::Server::http(("127.0.0.1", port))
+                .ok()
+                .map(|server| (Arc::new(server), port))
+        })
+        .ok_or_else(|| {
+            AppError::Network(format!(
+                "No free port for OAuth callback server (tried {} ports from {})",
+                OAUTH_PORT_ATTEMPTS, base_port
+            ))
+        })?;
+
+    *OAUTH_SERVER.lock().unwrap() = Some((server.clone(), port));
     let app_clone = app.clone();
 
     thread::spawn(move || {
-        let server = match tiny_http::Server::http("127.0.0.1:8847") {
-            Ok(s) => s,
-            Err(e) => {
-                log::error!("Failed to start OAuth server: {}", e);
-                return;
-            }
-        };
+        let deadline = Instant::now() + OAUTH_TIMEOUT;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let request = match server.recv_timeout(remaining) {
+                Ok(Some(request)) => request,
+                Ok(None) => {
+                    // Returning early without a request means a newer server replaced this one
+                    if Instant::now() >= deadline {
+                        log::warn!("OAuth callback not received in time, stopping server");
+                        let _ = app_clone.emit("oauth-timeout", ());
+                    }
+                    break;
+                }
+                Err(e) => {
+                    log::error!("OAuth server error: {}", e);
+                    break;
+                }
+            };
 
-        // Wait for a single request (the OAuth callback)
-        if let Ok(request) = server.recv() {
             let url = request.url().to_string();
 
-            // Parse callback parameters
-            if url.starts_with("/oauth/callback") {
-                // Parse query parameters
-                let query_start = url.find('?').unwrap_or(url.len());
-                let query_string = &url[query_start..];
-
-                let mut code = None;
-                let mut state = None;
-
-                for pair in query_string.trim_start_matches('?').split('&') {
-                    let mut parts = pair.splitn(2, '=');
-                    if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
-                        match key {
-                            "code" => code = Some(urlencoding::decode(value).unwrap_or_default().to_string()),
-                            "state" => state = Some(urlencoding::decode(value).unwrap_or_default().to_string()),
-                            _ => {}
-                        }
+            // Ignore unrelated requests (e.g. favicon) and keep waiting for the callback
+            if !url.starts_with(REDIRECT_PATH) {
+                let _ = request.respond(tiny_http::Response::empty(404));
+                continue;
+            }
+
+            // Parse query parameters
+            let query_start = url.find('?').unwrap_or(url.len());
+            let query_string = &url[query_start..];
+
+            let mut code = None;
+            let mut state = None;
+
+            for pair in query_string.trim_start_matches('?').split('&') {
+                let mut parts = pair.splitn(2, '=');
+                if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+                    match key {
+                        "code" => code = Some(urlencoding::decode(value).unwrap_or_default().to_string()),
+                        "state" => state = Some(urlencoding::decode(value).unwrap_or_default().to_string()),
+                        _ => {}
                     }
                 }
+            }
 
-                if let (Some(code), Some(state)) = (code, state) {
-                    // Emit event to frontend
-                    let _ = app_clone.emit("oauth-callback", OAuthCallback {
-                        code: code.clone(),
-                        state: state.clone(),
-                    });
+            if let (Some(code), Some(state)) = (code, state) {
+                // Emit event to frontend
+                let _ = app_clone.emit("oauth-callback", OAuthCallback {
+                    code: code.clone(),
+                    state: state.clone(),
+                });
 
-                    // Send success response to browser
-                    let response = tiny_http::Response::from_string(
-                        r#"<!DOCTYPE html>
+                // Send success response to browser
+                let response = tiny_http::Response::from_string(
+                    r#"<!DOCTYPE html>
 <html>
 <head><title>Authentication Successful</title></head>
 <body style="font-family: sans-serif; text-align: center; padding: 50px;">
@@ -346,15 +484,15 @@ pub async fn start_oauth_server(app: tauri::AppHandle) -> Result<(), AppError> {
     <script>setTimeout(() => window.close(), 2000);</script>
 </body>
 </html>"#,
-                    )
-                    .with_header(
-                        tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html"[..]).unwrap(),
-                    );
-                    let _ = request.respond(response);
-                } else {
-                    // Send error response
-                    let response = tiny_http::Response::from_string(
-                        r#"<!DOCTYPE html>
+                )
+                .with_header(
+                    tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html"[..]).unwrap(),
+                );
+                let _ = request.respond(response);
+            } else {
+                // Send error response
+                let response = tiny_http::Response::from_string(
+                    r#"<!DOCTYPE html>
 <html>
 <head><title>Authentication Failed</title></head>
 <body style="font-family: sans-serif; text-align: center; padding: 50px;">
@@ -362,81 +500,71 @@ pub async fn start_oauth_server(app: tauri::AppHandle) -> Result<(), AppError> {
     <p>Missing authorization code or state. Please try again.</p>
 </body>
 </html>"#,
-                    )
-                    .with_header(
-                        tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html"[..]).unwrap(),
-                    );
-                    let _ = request.respond(response);
-                }
+                )
+                .with_header(
+                    tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/html"[..]).unwrap(),
+                );
+                let _ = request.respond(response);
             }
+            break;
+        }
+
+        // Release the server unless a newer one has already taken its place
+        let mut current = OAUTH_SERVER.lock().unwrap();
+        if current.as_ref().is_some_and(|(s, _)| Arc::ptr_eq(s, &server)) {
+            *current = None;
         }
     });
 
-    Ok(())
+    Ok(port)
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenErrorResponse {
+    error: Option<String>,
+}
+
+// Whether Google's token endpoint rejected the refresh token itself
+fn is_invalid_grant(error_body: &str) -> bool {
+    serde_json::from_str::<TokenErrorResponse>(error_body)
+        .ok()
+        .and_then(|e| e.error)
+        .is_some_and(|e| e == "invalid_grant")
 }
 
 // Helper functions for token storage
+fn clear_tokens(conn: &rusqlite::Connection) -> Result<(), AppError> {
+    conn.execute("DELETE FROM settings WHERE key LIKE 'google_%'", [])
+        .map_err(|e| AppError::Database(e.to_string()))?;
+    Ok(())
+}
+
 fn store_tokens(conn: &rusqlite::Connection, tokens: &GoogleTokens) -> Result<(), AppError> {
-    conn.execute(
-        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('google_access_token', ?1, datetime('now'))",
-        [&tokens.access_token],
-    ).map_err(|e| AppError::Database(e.to_string()))?;
+    // Access and refresh tokens are encrypted by the settings layer
+    settings::set_setting(conn, "google_access_token", &tokens.access_token)?;
 
     if let Some(refresh_token) = &tokens.refresh_token {
-        conn.execute(
-            "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('google_refresh_token', ?1, datetime('now'))",
-            [refresh_token],
-        ).map_err(|e| AppError::Database(e.to_string()))?;
+        settings::set_setting(conn, "google_refresh_token", refresh_token)?;
     }
 
-    conn.execute(
-        "INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ('google_token_expires_at', ?1, datetime('now'))",
-        [&tokens.expires_at.to_string()],
-    ).map_err(|e| AppError::Database(e.to_string()))?;
+    settings::set_setting(conn, "google_token_expires_at", &tokens.expires_at.to_string())?;
 
     Ok(())
 }
 
 fn load_tokens(conn: &rusqlite::Connection) -> Result<Option<GoogleTokens>, AppError> {
-    let access_token: Option<String> = conn
-        .query_row(
-            "SELECT value FROM settings WHERE key = 'google_access_token'",
-            [],
-            |row| row.get(0),
-        )
-        .ok();
-
-    let access_token = match access_token {
+    let access_token = match settings::get_setting(conn, "google_access_token")? {
         Some(t) => t,
         None => return Ok(None),
     };
 
-    let refresh_token: Option<String> = conn
-        .query_row(
-            "SELECT value FROM settings WHERE key = 'google_refresh_token'",
-            [],
-            |row| row.get(0),
-        )
-        .ok();
-
-    let expires_at: i64 = conn
-        .query_row(
-            "SELECT value FROM settings WHERE key = 'google_token_expires_at'",
-            [],
-            |row| {
-                let val: String = row.get(0)?;
-                Ok(val.parse().unwrap_or(0))
-            },
-        )
+    let refresh_token = settings::get_setting(conn, "google_refresh_token")?;
+
+    let expires_at: i64 = settings::get_setting(conn, "google_token_expires_at")?
+        .and_then(|v| v.parse().ok())
         .unwrap_or(0);
 
-    let email: Option<String> = conn
-        .query_row(
-            "SELECT value FROM settings WHERE key = 'google_account_email'",
-            [],
-            |row| row.get(0),
-        )
-        .ok();
+    let email = settings::get_setting(conn, "google_account_email")?;
 
     Ok(Some(GoogleTokens {
         access_token,
@@ -1,8 +1,9 @@
 use crate::db::DbConnection;
 use crate::error::AppError;
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 const DRIVE_API_BASE: &str = "https://www.googleapis.com/drive/v3";
 const DRIVE_UPLOAD_BASE: &str = "https://www.googleapis.com/upload/drive/v3";
@@ -10,6 +11,12 @@ const DRIVE_UPLOAD_BASE: &str = "https://www.googleapis.com/upload/drive/v3";
 // Folder name in Google Drive for app data
 const APP_FOLDER_NAME: &str = "PaperManager";
 
+// Suffix of the metadata file stored alongside a backup, e.g. `papers.db.meta.json`
+const BACKUP_METADATA_SUFFIX: &str = ".meta.json";
+
+// Marker for a 401 from Drive, so the request can be retried with a refreshed token
+const TOKEN_REJECTED: &str = "Google Drive rejected the access token";
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DriveFile {
@@ -35,6 +42,29 @@ struct DriveFileResponse {
     size: Option<String>,
 }
 
+/// Summary uploaded next to each database backup, so a backup can be
+/// described without downloading it
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackupMetadata {
+    paper_count: i32,
+    backed_up_at: String,
+    schema_version: i32,
+}
+
+/// What a database backup on Drive contains
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriveBackupInfo {
+    pub file_id: String,
+    pub name: String,
+    pub paper_count: i32,
+    /// Falls back to the file's modified time for backups made before
+    /// metadata was stored
+    pub backed_up_at: Option<String>,
+    pub schema_version: i32,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SyncStatus {
@@ -44,27 +74,90 @@ pub struct SyncStatus {
     pub total_pdfs: i32,
 }
 
-/// Get access token from stored tokens, refreshing if needed
-async fn get_valid_token(db: &State<'_, DbConnection>) -> Result<String, AppError> {
+// Tokens expiring within this many seconds are refreshed before a Drive operation
+const TOKEN_EXPIRY_BUFFER_SECS: i64 = 300;
+
+// Slowest transfer rate assumed when estimating how long an upload or download takes
+const MIN_TRANSFER_BYTES_PER_SEC: u64 = 128 * 1024;
+
+/// Token lifetime needed to move `bytes` to or from Drive at the slowest
+/// expected rate, on top of the usual expiry buffer
+fn transfer_lifetime_secs(bytes: u64) -> i64 {
+    let secs = bytes / MIN_TRANSFER_BYTES_PER_SEC;
+    TOKEN_EXPIRY_BUFFER_SECS.saturating_add(i64::try_from(secs).unwrap_or(i64::MAX))
+}
+
+/// Get access token from stored tokens, refreshing it up front unless it
+/// stays valid for at least `min_lifetime_secs`
+async fn get_valid_token(
+    db: &State<'_, DbConnection>,
+    min_lifetime_secs: i64,
+) -> Result<String, AppError> {
     use super::google_auth::{get_google_tokens, refresh_google_token};
 
     let tokens = get_google_tokens(db.clone())?
         .ok_or_else(|| AppError::Auth("No Google account connected".to_string()))?;
 
-    // Check if token is expired (with 5 min buffer)
     let now = chrono::Utc::now().timestamp();
-    if tokens.expires_at < now + 300 {
-        // Token expired or expiring soon, refresh it
+    if tokens.expires_at < now.saturating_add(min_lifetime_secs) {
+        // Expired, or would expire before the operation finishes
         let new_tokens = refresh_google_token(db.clone()).await?;
+        if new_tokens.expires_at < now.saturating_add(min_lifetime_secs) {
+            log::warn!(
+                "Drive operation may outlast the access token ({}s needed)",
+                min_lifetime_secs
+            );
+        }
         Ok(new_tokens.access_token)
     } else {
         Ok(tokens.access_token)
     }
 }
 
+/// Run a Drive operation with a token valid for at least `min_lifetime_secs`,
+/// refreshing and retrying once on a 401
+async fn with_valid_token<T, F, Fut>(
+    db: &State<'_, DbConnection>,
+    min_lifetime_secs: i64,
+    operation: F,
+) -> Result<T, AppError>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<T, AppError>>,
+{
+    use super::google_auth::refresh_google_token;
+
+    let access_token = get_valid_token(db, min_lifetime_secs).await?;
+
+    match operation(access_token).await {
+        Err(AppError::Auth(message)) if message == TOKEN_REJECTED => {
+            let new_tokens = refresh_google_token(db.clone()).await?;
+            operation(new_tokens.access_token).await
+        }
+        result => result,
+    }
+}
+
+/// Turn an unsuccessful Drive response into an error, flagging rejected tokens
+async fn ensure_success(
+    response: reqwest::Response,
+    context: &str,
+) -> Result<reqwest::Response, AppError> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(AppError::Auth(TOKEN_REJECTED.to_string()));
+    }
+
+    let error = response.text().await.unwrap_or_default();
+    Err(AppError::Network(format!("{}: {}", context, error)))
+}
+
 /// Find or create the app folder in Google Drive
 async fn get_or_create_app_folder(access_token: &str) -> Result<String, AppError> {
-    let client = reqwest::Client::new();
+    let client = crate::commands::http::client()?;
 
     // Search for existing folder
     let search_url = format!(
@@ -79,10 +172,7 @@ async fn get_or_create_app_folder(access_token: &str) -> Result<String, AppError
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
-    if !response.status().is_success() {
-        let error = response.text().await.unwrap_or_default();
-        return Err(AppError::Network(format!("Failed to search Drive: {}", error)));
-    }
+    let response = ensure_success(response, "Failed to search Drive").await?;
 
     let list: DriveListResponse = response.json().await
         .map_err(|e| AppError::Parse(e.to_string()))?;
@@ -107,10 +197,7 @@ async fn get_or_create_app_folder(access_token: &str) -> Result<String, AppError
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
-    if !create_response.status().is_success() {
-        let error = create_response.text().await.unwrap_or_default();
-        return Err(AppError::Network(format!("Failed to create folder: {}", error)));
-    }
+    let create_response = ensure_success(create_response, "Failed to create folder").await?;
 
     #[derive(Deserialize)]
     struct CreateResponse {
@@ -130,12 +217,22 @@ async fn upload_file(
     file_path: &PathBuf,
     file_name: &str,
 ) -> Result<String, AppError> {
-    let client = reqwest::Client::new();
-
     // Read file content
     let file_content = std::fs::read(file_path)
         .map_err(|e| AppError::Io(e.to_string()))?;
 
+    upload_content(access_token, folder_id, file_content, file_name).await
+}
+
+/// Upload content to Google Drive, replacing any file of the same name in the folder
+async fn upload_content(
+    access_token: &str,
+    folder_id: &str,
+    file_content: Vec<u8>,
+    file_name: &str,
+) -> Result<String, AppError> {
+    let client = crate::commands::http::client()?;
+
     // Check if file already exists in folder
     let search_url = format!(
         "{}/files?q=name='{}' and '{}' in parents and trashed=false&fields=files(id)",
@@ -149,6 +246,7 @@ async fn upload_file(
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
+    let search_response = ensure_success(search_response, "Failed to search Drive").await?;
     let existing: DriveListResponse = search_response.json().await.unwrap_or(DriveListResponse { files: vec![] });
 
     if let Some(existing_file) = existing.files.first() {
@@ -167,10 +265,7 @@ async fn upload_file(
             .await
             .map_err(|e| AppError::Network(e.to_string()))?;
 
-        if !response.status().is_success() {
-            let error = response.text().await.unwrap_or_default();
-            return Err(AppError::Network(format!("Failed to update file: {}", error)));
-        }
+        ensure_success(response, "Failed to update file").await?;
 
         Ok(existing_file.id.clone())
     } else {
@@ -205,10 +300,7 @@ async fn upload_file(
             .await
             .map_err(|e| AppError::Network(e.to_string()))?;
 
-        if !response.status().is_success() {
-            let error = response.text().await.unwrap_or_default();
-            return Err(AppError::Network(format!("Failed to upload file: {}", error)));
-        }
+        let response = ensure_success(response, "Failed to upload file").await?;
 
         #[derive(Deserialize)]
         struct UploadResponse {
@@ -228,7 +320,23 @@ async fn download_file(
     file_id: &str,
     destination: &PathBuf,
 ) -> Result<(), AppError> {
-    let client = reqwest::Client::new();
+    let content = fetch_file(access_token, file_id).await?;
+
+    // Ensure parent directory exists
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| AppError::Io(e.to_string()))?;
+    }
+
+    std::fs::write(destination, content)
+        .map_err(|e| AppError::Io(e.to_string()))?;
+
+    Ok(())
+}
+
+/// Get a file's content from Google Drive
+async fn fetch_file(access_token: &str, file_id: &str) -> Result<Vec<u8>, AppError> {
+    let client = crate::commands::http::client()?;
 
     let url = format!("{}/files/{}?alt=media", DRIVE_API_BASE, file_id);
 
@@ -239,24 +347,12 @@ async fn download_file(
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
-    if !response.status().is_success() {
-        let error = response.text().await.unwrap_or_default();
-        return Err(AppError::Network(format!("Failed to download file: {}", error)));
-    }
+    let response = ensure_success(response, "Failed to download file").await?;
 
     let content = response.bytes().await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
-    // Ensure parent directory exists
-    if let Some(parent) = destination.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| AppError::Io(e.to_string()))?;
-    }
-
-    std::fs::write(destination, content)
-        .map_err(|e| AppError::Io(e.to_string()))?;
-
-    Ok(())
+    Ok(content.to_vec())
 }
 
 /// Backup database to Google Drive
@@ -265,9 +361,6 @@ pub async fn backup_to_drive(
     app: AppHandle,
     db: State<'_, DbConnection>,
 ) -> Result<String, AppError> {
-    let access_token = get_valid_token(&db).await?;
-    let folder_id = get_or_create_app_folder(&access_token).await?;
-
     // Get database path
     let app_data = app.path().app_data_dir()
         .map_err(|e| AppError::Io(e.to_string()))?;
@@ -277,8 +370,31 @@ pub async fn backup_to_drive(
         return Err(AppError::NotFound("Database file not found".to_string()));
     }
 
-    // Upload database
-    let file_id = upload_file(&access_token, &folder_id, &db_path, "papers.db").await?;
+    let metadata = {
+        let conn = db.get()?;
+        BackupMetadata {
+            paper_count: crate::db::papers::count_papers(&conn, &Default::default())?,
+            backed_up_at: chrono::Utc::now().to_rfc3339(),
+            schema_version: crate::db::migrations::SCHEMA_VERSION,
+        }
+    };
+    let metadata = serde_json::to_vec(&metadata)?;
+
+    // Make sure the token outlives the upload rather than expiring halfway through
+    let db_size = std::fs::metadata(&db_path)?.len();
+    let min_lifetime = transfer_lifetime_secs(db_size);
+
+    // Upload database, then its metadata
+    let db_path = &db_path;
+    let metadata = &metadata;
+    let file_id = with_valid_token(&db, min_lifetime, |access_token| async move {
+        let folder_id = get_or_create_app_folder(&access_token).await?;
+        let file_id = upload_file(&access_token, &folder_id, db_path, "papers.db").await?;
+        let metadata_name = format!("papers.db{}", BACKUP_METADATA_SUFFIX);
+        upload_content(&access_token, &folder_id, metadata.clone(), &metadata_name).await?;
+        Ok(file_id)
+    })
+    .await?;
 
     // Update last sync timestamp
     let conn = db.get().map_err(|e| AppError::Database(e.to_string()))?;
@@ -290,49 +406,53 @@ pub async fn backup_to_drive(
     Ok(file_id)
 }
 
-/// Restore database from Google Drive
+/// Restore database from Google Drive, then emit `database-restored` so the UI reloads
 #[tauri::command]
 pub async fn restore_from_drive(
     app: AppHandle,
     db: State<'_, DbConnection>,
 ) -> Result<(), AppError> {
-    let access_token = get_valid_token(&db).await?;
-    let folder_id = get_or_create_app_folder(&access_token).await?;
-
-    let client = reqwest::Client::new();
-
-    // Find database file in Drive
-    let search_url = format!(
-        "{}/files?q=name='papers.db' and '{}' in parents and trashed=false&fields=files(id,modifiedTime)",
-        DRIVE_API_BASE, folder_id
-    );
+    let app_data = app.path().app_data_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    let temp_path = app_data.join("papers_restore.db");
 
-    let response = client
-        .get(&search_url)
-        .bearer_auth(&access_token)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(e.to_string()))?;
+    // Download to temp location first
+    let temp_path_ref = &temp_path;
+    with_valid_token(&db, TOKEN_EXPIRY_BUFFER_SECS, |access_token| async move {
+        let folder_id = get_or_create_app_folder(&access_token).await?;
+        let client = crate::commands::http::client()?;
+
+        // Find database file in Drive
+        let search_url = format!(
+            "{}/files?q=name='papers.db' and '{}' in parents and trashed=false&fields=files(id,modifiedTime)",
+            DRIVE_API_BASE, folder_id
+        );
 
-    let list: DriveListResponse = response.json().await
-        .map_err(|e| AppError::Parse(e.to_string()))?;
+        let response = client
+            .get(&search_url)
+            .bearer_auth(&access_token)
+            .send()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+        let response = ensure_success(response, "Failed to search Drive").await?;
 
-    let file = list.files.first()
-        .ok_or_else(|| AppError::NotFound("No backup found in Drive".to_string()))?;
+        let list: DriveListResponse = response.json().await
+            .map_err(|e| AppError::Parse(e.to_string()))?;
 
-    // Download to temp location first
-    let app_data = app.path().app_data_dir()
-        .map_err(|e| AppError::Io(e.to_string()))?;
-    let temp_path = app_data.join("papers_restore.db");
-    let db_path = app_data.join("papers.db");
+        let file = list.files.first()
+            .ok_or_else(|| AppError::NotFound("No backup found in Drive".to_string()))?;
 
-    download_file(&access_token, &file.id, &temp_path).await?;
+        download_file(&access_token, &file.id, temp_path_ref).await
+    })
+    .await?;
 
-    // Close current connection and replace database
-    // Note: In a real implementation, you'd want to properly close the connection
-    std::fs::rename(&temp_path, &db_path)
-        .map_err(|e| AppError::Io(e.to_string()))?;
+    // Swap the file in under the connection lock and reopen it
+    db.replace_file(&temp_path)?;
+    if let Err(e) = crate::commands::http::load_settings(&*db.get()?) {
+        log::warn!("Failed to load HTTP settings: {}", e);
+    }
 
+    let _ = app.emit("database-restored", ());
     Ok(())
 }
 
@@ -356,7 +476,8 @@ pub fn get_sync_status(
     // Count PDFs
     let total_pdfs: i32 = conn
         .query_row(
-            "SELECT COUNT(*) FROM papers WHERE pdf_path IS NOT NULL AND pdf_path != ''",
+            "SELECT COUNT(*) FROM papers
+             WHERE pdf_path IS NOT NULL AND pdf_path != '' AND deleted_at IS NULL",
             [],
             |row| row.get(0),
         )
@@ -377,30 +498,27 @@ pub fn get_sync_status(
 pub async fn list_drive_files(
     db: State<'_, DbConnection>,
 ) -> Result<Vec<DriveFile>, AppError> {
-    let access_token = get_valid_token(&db).await?;
-    let folder_id = get_or_create_app_folder(&access_token).await?;
-
-    let client = reqwest::Client::new();
+    let list = with_valid_token(&db, TOKEN_EXPIRY_BUFFER_SECS, |access_token| async move {
+        let folder_id = get_or_create_app_folder(&access_token).await?;
+        let client = crate::commands::http::client()?;
 
-    let url = format!(
-        "{}/files?q='{}' in parents and trashed=false&fields=files(id,name,mimeType,modifiedTime,size)",
-        DRIVE_API_BASE, folder_id
-    );
-
-    let response = client
-        .get(&url)
-        .bearer_auth(&access_token)
-        .send()
-        .await
-        .map_err(|e| AppError::Network(e.to_string()))?;
+        let url = format!(
+            "{}/files?q='{}' in parents and trashed=false&fields=files(id,name,mimeType,modifiedTime,size)",
+            DRIVE_API_BASE, folder_id
+        );
 
-    if !response.status().is_success() {
-        let error = response.text().await.unwrap_or_default();
-        return Err(AppError::Network(format!("Failed to list files: {}", error)));
-    }
+        let response = client
+            .get(&url)
+            .bearer_auth(&access_token)
+            .send()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+        let response = ensure_success(response, "Failed to list files").await?;
 
-    let list: DriveListResponse = response.json().await
-        .map_err(|e| AppError::Parse(e.to_string()))?;
+        response.json::<DriveListResponse>().await
+            .map_err(|e| AppError::Parse(e.to_string()))
+    })
+    .await?;
 
     Ok(list.files.into_iter().map(|f| DriveFile {
         id: f.id,
@@ -410,3 +528,193 @@ pub async fn list_drive_files(
         size: f.size,
     }).collect())
 }
+
+/// Report the paper count and backup time of a database backup on Drive.
+/// Reads the metadata file stored with the backup, and only downloads the
+/// database itself for older backups that have none.
+#[tauri::command]
+pub async fn describe_drive_backup(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    file_id: String,
+) -> Result<DriveBackupInfo, AppError> {
+    let file_id = &file_id;
+    let (file, metadata) = with_valid_token(&db, TOKEN_EXPIRY_BUFFER_SECS, |access_token| async move {
+        let client = crate::commands::http::client()?;
+
+        let url = format!(
+            "{}/files/{}?fields=id,name,mimeType,modifiedTime,size",
+            DRIVE_API_BASE, file_id
+        );
+        let response = client
+            .get(&url)
+            .bearer_auth(&access_token)
+            .send()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+        let response = ensure_success(response, "Failed to get file").await?;
+        let file: DriveFileResponse = response.json().await
+            .map_err(|e| AppError::Parse(e.to_string()))?;
+
+        let folder_id = get_or_create_app_folder(&access_token).await?;
+        let search_url = format!(
+            "{}/files?q=name='{}{}' and '{}' in parents and trashed=false&fields=files(id,name)",
+            DRIVE_API_BASE, file.name, BACKUP_METADATA_SUFFIX, folder_id
+        );
+        let response = client
+            .get(&search_url)
+            .bearer_auth(&access_token)
+            .send()
+            .await
+            .map_err(|e| AppError::Network(e.to_string()))?;
+        let response = ensure_success(response, "Failed to search Drive").await?;
+        let list: DriveListResponse = response.json().await
+            .map_err(|e| AppError::Parse(e.to_string()))?;
+
+        let metadata = match list.files.first() {
+            Some(meta_file) => {
+                let content = fetch_file(&access_token, &meta_file.id).await?;
+                serde_json::from_slice::<BackupMetadata>(&content).ok()
+            }
+            None => None,
+        };
+        Ok((file, metadata))
+    })
+    .await?;
+
+    if let Some(metadata) = metadata {
+        return Ok(DriveBackupInfo {
+            file_id: file.id,
+            name: file.name,
+            paper_count: metadata.paper_count,
+            backed_up_at: Some(metadata.backed_up_at),
+            schema_version: metadata.schema_version,
+        });
+    }
+
+    // No metadata: download the backup and read it directly
+    let app_data = app.path().app_data_dir()
+        .map_err(|e| AppError::Io(e.to_string()))?;
+    let temp_path = app_data.join("papers_describe.db");
+    let temp_path_ref = &temp_path;
+    let size = file.size.as_deref().and_then(|s| s.parse().ok()).unwrap_or(0);
+    with_valid_token(&db, transfer_lifetime_secs(size), |access_token| async move {
+        download_file(&access_token, file_id, temp_path_ref).await
+    })
+    .await?;
+
+    let inspected = inspect_backup(&temp_path);
+    let _ = std::fs::remove_file(&temp_path);
+    let (paper_count, schema_version) = inspected?;
+
+    Ok(DriveBackupInfo {
+        file_id: file.id,
+        name: file.name,
+        paper_count,
+        backed_up_at: file.modified_time,
+        schema_version,
+    })
+}
+
+/// Read (paper count, schema version) from a downloaded database backup
+fn inspect_backup(path: &PathBuf) -> Result<(i32, i32), AppError> {
+    let conn = rusqlite::Connection::open_with_flags(
+        path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+    )?;
+
+    // Backups from before the trash existed have no `deleted_at`
+    let paper_count = conn
+        .query_row("SELECT COUNT(*) FROM papers WHERE deleted_at IS NULL", [], |row| row.get(0))
+        .or_else(|_| conn.query_row("SELECT COUNT(*) FROM papers", [], |row| row.get(0)))?;
+    let schema_version = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+    Ok((paper_count, schema_version))
+}
+
+/// Hours between automatic backups; 0 or unset disables them
+const AUTO_BACKUP_INTERVAL_SETTING: &str = "auto_backup_interval_hours";
+
+/// When the next automatic backup is due, persisted so restarts don't trigger one
+const AUTO_BACKUP_NEXT_RUN_SETTING: &str = "auto_backup_next_run";
+
+/// How often the scheduler wakes up to check whether a backup is due
+const AUTO_BACKUP_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// Spawn the background task that backs the database up to Drive every
+/// `auto_backup_interval_hours`, emitting `auto-backup-done` with the file id
+/// or `auto-backup-failed` with the error message.
+pub fn spawn_auto_backup(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(AUTO_BACKUP_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let due = match auto_backup_due(&app.state::<DbConnection>()) {
+                Ok(due) => due,
+                Err(e) => {
+                    log::warn!("Failed to check auto backup schedule: {}", e);
+                    continue;
+                }
+            };
+            if !due {
+                continue;
+            }
+
+            match backup_to_drive(app.clone(), app.state::<DbConnection>()).await {
+                Ok(file_id) => {
+                    log::info!("Automatic backup to Google Drive completed");
+                    let _ = app.emit("auto-backup-done", &file_id);
+                }
+                Err(e) => {
+                    log::warn!("Automatic backup to Google Drive failed: {}", e);
+                    let _ = app.emit("auto-backup-failed", e.to_string());
+                }
+            }
+        }
+    });
+}
+
+/// Whether an automatic backup should run now. Schedules the next run as a
+/// side effect, so a failing backup is retried on the next interval rather
+/// than on every check.
+fn auto_backup_due(db: &DbConnection) -> Result<bool, AppError> {
+    use crate::db::settings;
+
+    let conn = db.get()?;
+    let hours = settings::get_setting_i32(&conn, AUTO_BACKUP_INTERVAL_SETTING)?
+        .map(i64::from)
+        .unwrap_or(0);
+    if hours <= 0 || settings::get_setting(&conn, "google_refresh_token")?.is_none() {
+        return Ok(false);
+    }
+
+    let now = chrono::Utc::now();
+    let interval = chrono::Duration::hours(hours);
+    let next_run = settings::get_setting(&conn, AUTO_BACKUP_NEXT_RUN_SETTING)?
+        .and_then(|v| chrono::DateTime::parse_from_rfc3339(&v).ok())
+        .map(|t| t.with_timezone(&chrono::Utc));
+
+    let schedule_next = || {
+        settings::set_setting(&conn, AUTO_BACKUP_NEXT_RUN_SETTING, &(now + interval).to_rfc3339())
+    };
+
+    match next_run {
+        Some(next_run) if next_run > now => {
+            // A shortened interval takes effect without waiting out the old one
+            if next_run > now + interval {
+                schedule_next()?;
+            }
+            Ok(false)
+        }
+        Some(_) => {
+            schedule_next()?;
+            Ok(true)
+        }
+        // First check after enabling: start counting a full interval from now
+        None => {
+            schedule_next()?;
+            Ok(false)
+        }
+    }
+}
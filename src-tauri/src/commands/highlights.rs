@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use tauri::{AppHandle, Emitter, State};
 
 use crate::db::DbConnection;
@@ -14,6 +16,16 @@ pub fn get_highlights(
     crate::db::highlights::get_highlights(&conn, &paper_id, page_number)
 }
 
+/// Highlight count per paper, for badges on papers loaded without one
+#[tauri::command]
+pub fn get_highlight_counts(
+    db: State<'_, DbConnection>,
+    paper_ids: Vec<String>,
+) -> Result<HashMap<String, i32>, AppError> {
+    let conn = db.get()?;
+    crate::db::highlights::count_highlights(&conn, &paper_ids)
+}
+
 #[tauri::command]
 pub fn get_highlight(
     db: State<'_, DbConnection>,
@@ -60,3 +72,32 @@ pub fn delete_highlight(
     let _ = app.emit("highlights-changed", &highlight.paper_id);
     Ok(())
 }
+
+/// Export all highlights of a paper as JSON, including page numbers and rects
+#[tauri::command]
+pub fn export_highlights_json(
+    db: State<'_, DbConnection>,
+    paper_id: String,
+) -> Result<String, AppError> {
+    let conn = db.get()?;
+    crate::db::papers::get_paper(&conn, &paper_id)?;
+    let highlights = crate::db::highlights::get_highlights(&conn, &paper_id, None)?;
+    Ok(serde_json::to_string_pretty(&highlights)?)
+}
+
+/// Import highlights exported by `export_highlights_json` onto a paper
+#[tauri::command]
+pub fn import_highlights_json(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_id: String,
+    json: String,
+) -> Result<Vec<Highlight>, AppError> {
+    let highlights: Vec<Highlight> = serde_json::from_str(&json)?;
+
+    let conn = db.get()?;
+    crate::db::papers::get_paper(&conn, &paper_id)?;
+    let imported = crate::db::highlights::import_highlights(&conn, &paper_id, highlights)?;
+    let _ = app.emit("highlights-changed", &paper_id);
+    Ok(imported)
+}
@@ -0,0 +1,108 @@
+use rusqlite::Connection;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::db::settings;
+use crate::error::AppError;
+
+/// Setting key for the connect/read timeout in seconds
+pub const TIMEOUT_SETTING: &str = "http_timeout_secs";
+/// Setting key for an optional HTTP/HTTPS proxy URL
+pub const PROXY_SETTING: &str = "http_proxy";
+/// Setting key for the contact email sent to APIs that ask for a `mailto:`
+pub const CONTACT_EMAIL_SETTING: &str = "contact_email";
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const USER_AGENT: &str = "PaperManager/1.0";
+const DEFAULT_CONTACT_EMAIL: &str = "contact@papermanager.app";
+
+#[derive(Debug, Clone)]
+struct HttpConfig {
+    timeout_secs: u64,
+    proxy: Option<String>,
+    contact_email: Option<String>,
+}
+
+// Network settings shared by every outgoing request, refreshed whenever settings change
+static HTTP_CONFIG: Mutex<HttpConfig> = Mutex::new(HttpConfig {
+    timeout_secs: DEFAULT_TIMEOUT_SECS,
+    proxy: None,
+    contact_email: None,
+});
+
+/// Reload timeout and proxy configuration from the settings table
+pub fn load_settings(conn: &Connection) -> Result<(), AppError> {
+    let timeout_secs = settings::get_setting_i32(conn, TIMEOUT_SETTING)?
+        .map(|secs| secs as u64)
+        .unwrap_or(DEFAULT_TIMEOUT_SECS);
+
+    let proxy = settings::get_setting_string(conn, PROXY_SETTING)?;
+
+    let contact_email = settings::get_setting_string(conn, CONTACT_EMAIL_SETTING)?;
+
+    let mut config = HTTP_CONFIG.lock().unwrap();
+    config.timeout_secs = timeout_secs;
+    config.proxy = proxy;
+    config.contact_email = contact_email;
+    Ok(())
+}
+
+/// User-Agent for API requests, including the user's contact email when configured
+pub fn user_agent() -> String {
+    match HTTP_CONFIG.lock().unwrap().contact_email.as_deref() {
+        Some(email) => format!("{} (mailto:{})", USER_AGENT, email),
+        None => USER_AGENT.to_string(),
+    }
+}
+
+/// User-Agent for APIs with a "polite pool" (e.g. Crossref) that always carries a contact
+pub fn polite_user_agent() -> String {
+    let config = HTTP_CONFIG.lock().unwrap();
+    let email = config
+        .contact_email
+        .as_deref()
+        .unwrap_or(DEFAULT_CONTACT_EMAIL);
+    format!("{} (mailto:{})", USER_AGENT, email)
+}
+
+/// Client builder with the configured timeout and proxy applied
+pub fn client_builder() -> Result<reqwest::ClientBuilder, AppError> {
+    let config = HTTP_CONFIG.lock().unwrap().clone();
+    let timeout = Duration::from_secs(config.timeout_secs);
+
+    let mut builder = reqwest::Client::builder()
+        .connect_timeout(timeout)
+        .read_timeout(timeout);
+
+    if let Some(proxy_url) = &config.proxy {
+        let proxy = reqwest::Proxy::all(proxy_url)
+            .map_err(|e| AppError::Validation(format!("Invalid proxy URL: {}", e)))?;
+        builder = builder.proxy(proxy);
+    }
+
+    Ok(builder)
+}
+
+/// Build an HTTP client using the configured timeout and proxy
+pub fn client() -> Result<reqwest::Client, AppError> {
+    client_builder()?
+        .build()
+        .map_err(|e| AppError::Network(e.to_string()))
+}
+
+/// Turn an HTTP 429 response into `AppError::RateLimited`
+pub fn check_rate_limit(response: &reqwest::Response, service: &str) -> Result<(), AppError> {
+    if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Ok(());
+    }
+
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok());
+
+    Err(AppError::RateLimited(match retry_after {
+        Some(secs) => format!("{} rate limit reached, retry after {}s", service, secs),
+        None => format!("{} rate limit reached, try again later", service),
+    }))
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::db::DbConnection;
+use crate::error::AppError;
+use crate::models::UpdatePaperInput;
+
+const DEFAULT_KEYWORD_COUNT: usize = 10;
+const MAX_KEYWORD_COUNT: usize = 50;
+
+/// Longest key phrase, in words
+const MAX_PHRASE_WORDS: usize = 3;
+
+/// Words that split candidate phrases: function words plus the filler common
+/// to every paper
+const STOPWORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "also", "although", "am", "among",
+    "an", "and", "any", "are", "as", "at", "be", "because", "been", "before", "being", "below",
+    "between", "both", "but", "by", "can", "could", "did", "do", "does", "doing", "down", "due",
+    "during", "each", "either", "et", "al", "etc", "few", "fig", "figure", "first", "for", "from",
+    "further", "had", "has", "have", "having", "he", "her", "here", "hers", "him", "his", "how",
+    "however", "i", "if", "in", "into", "is", "it", "its", "itself", "may", "might", "more",
+    "most", "must", "my", "no", "nor", "not", "of", "off", "on", "once", "one", "only", "or",
+    "other", "our", "ours", "out", "over", "own", "paper", "per", "same", "second", "she",
+    "should", "shown", "since", "so", "some", "study", "such", "table", "than", "that", "the",
+    "their", "them", "then", "there", "therefore", "these", "they", "this", "those", "through",
+    "thus", "to", "too", "two", "under", "until", "up", "upon", "us", "use", "used", "using",
+    "very", "via", "was", "we", "were", "what", "when", "where", "whether", "which", "while",
+    "who", "whom", "why", "will", "with", "within", "without", "would", "you", "your",
+];
+
+fn is_keyword_word(word: &str) -> bool {
+    let chars = word.chars().count();
+    let is_hangul = word.chars().any(|c| ('\u{AC00}'..='\u{D7A3}').contains(&c));
+    let min_chars = if is_hangul { 2 } else { 3 };
+    chars >= min_chars && word.chars().any(char::is_alphabetic) && !STOPWORDS.contains(&word)
+}
+
+/// Split text into runs of content words, broken at punctuation and stopwords
+fn word_runs(text: &str) -> Vec<Vec<String>> {
+    let mut runs = Vec::new();
+    for fragment in text.split(|c: char| !(c.is_alphanumeric() || c.is_whitespace() || c == '-')) {
+        let mut run: Vec<String> = Vec::new();
+        for word in fragment.split_whitespace() {
+            let word = word.trim_matches('-').to_lowercase();
+            if is_keyword_word(&word) {
+                run.push(word);
+                continue;
+            }
+            if !run.is_empty() {
+                runs.push(std::mem::take(&mut run));
+            }
+        }
+        if !run.is_empty() {
+            runs.push(run);
+        }
+    }
+    runs
+}
+
+/// Rank key phrases: as in RAKE, candidates are the phrases of up to
+/// `MAX_PHRASE_WORDS` content words between stopwords, scored by how often
+/// they occur times their length so recurring multi-word terms beat the
+/// single words they contain
+pub(crate) fn extract_keywords_from_text(text: &str, max: usize) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for run in word_runs(text) {
+        for len in 1..=MAX_PHRASE_WORDS.min(run.len()) {
+            for window in run.windows(len) {
+                *counts.entry(window.join(" ")).or_default() += 1;
+            }
+        }
+    }
+
+    let mut scored: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(phrase, count)| {
+            let words = phrase.split(' ').count();
+            (phrase, count * words)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let mut keywords: Vec<String> = Vec::new();
+    for (phrase, _) in scored {
+        // Skip a phrase already covered by a higher-ranked one ("learning" after "machine learning")
+        let covered = |k: &String| phrase.split(' ').all(|w| k.split(' ').any(|kw| kw == w));
+        if keywords.iter().any(covered) {
+            continue;
+        }
+        keywords.push(phrase);
+        if keywords.len() == max {
+            break;
+        }
+    }
+    keywords
+}
+
+/// Extract the top `max` key phrases from a paper's indexed full text without
+/// AI. With `save`, they replace the paper's keywords.
+#[tauri::command]
+pub fn extract_keywords(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_id: String,
+    max: Option<usize>,
+    save: Option<bool>,
+) -> Result<Vec<String>, AppError> {
+    let max = max.unwrap_or(DEFAULT_KEYWORD_COUNT).clamp(1, MAX_KEYWORD_COUNT);
+    let conn = db.get()?;
+
+    let pages = crate::db::pdf_content::get_pdf_pages(&conn, &paper_id)?;
+    if pages.is_empty() {
+        // Distinguish a missing paper from one that just isn't indexed yet
+        crate::db::papers::get_paper(&conn, &paper_id)?;
+        return Err(AppError::Validation(
+            "이 논문은 아직 색인되지 않았습니다. 먼저 PDF를 색인해주세요.".to_string(),
+        ));
+    }
+
+    let text = pages
+        .iter()
+        .map(|page| page.text_content.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let keywords = extract_keywords_from_text(&text, max);
+
+    if save.unwrap_or(false) && !keywords.is_empty() {
+        let input = UpdatePaperInput {
+            keywords: Some(keywords.join(", ")),
+            ..Default::default()
+        };
+        let paper = crate::db::papers::update_paper(&conn, &paper_id, input)?;
+        let _ = app.emit("papers-changed", &paper.folder_id);
+    }
+
+    Ok(keywords)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_keywords_from_text() {
+        let text = "We show that machine learning models can improve crop yield prediction. \
+            We compare machine learning models with regression baselines. \
+            Crop yield prediction is harder in drought years, and the models are trained on \
+            satellite imagery. Machine learning models outperform the baselines.";
+        let keywords = extract_keywords_from_text(text, 3);
+        assert_eq!(keywords.len(), 3);
+        assert_eq!(keywords[0], "machine learning models");
+        assert!(keywords.contains(&"crop yield prediction".to_string()));
+        assert!(keywords.iter().all(|k| !k.contains("the") && !k.contains("with")));
+    }
+}
@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::cancellation::CancellationState;
+use crate::db::DbConnection;
+use crate::error::AppError;
+use crate::models::{Paper, PaperFilter, UpdatePaperInput};
+
+/// Result of requesting one of a paper's links
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkCheck {
+    pub url: String,
+    /// `url` for the stored link, `doi` for the DOI resolver
+    pub kind: String,
+    pub status: Option<u16>,
+    pub alive: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaperLinkReport {
+    pub paper_id: String,
+    pub title: String,
+    pub links: Vec<LinkCheck>,
+    /// Where the DOI now leads, when the stored link is dead or missing
+    pub resolved_url: Option<String>,
+}
+
+impl PaperLinkReport {
+    fn has_dead_link(&self) -> bool {
+        self.links.iter().any(|link| !link.alive)
+    }
+}
+
+/// Request a link, returning the check and the URL it finally redirected to.
+/// Servers that refuse HEAD are retried with GET.
+async fn check_link(client: &reqwest::Client, url: &str, kind: &str) -> (LinkCheck, Option<String>) {
+    let mut response = client
+        .head(url)
+        .header("User-Agent", crate::commands::http::user_agent())
+        .send()
+        .await;
+
+    if let Ok(r) = &response {
+        if matches!(r.status().as_u16(), 403 | 405 | 501) {
+            response = client
+                .get(url)
+                .header("User-Agent", crate::commands::http::user_agent())
+                .send()
+                .await;
+        }
+    }
+
+    match response {
+        Ok(r) => {
+            let alive = r.status().is_success();
+            let check = LinkCheck {
+                url: url.to_string(),
+                kind: kind.to_string(),
+                status: Some(r.status().as_u16()),
+                alive,
+                error: None,
+            };
+            (check, alive.then(|| r.url().to_string()))
+        }
+        Err(e) => {
+            let check = LinkCheck {
+                url: url.to_string(),
+                kind: kind.to_string(),
+                status: None,
+                alive: false,
+                error: Some(e.to_string()),
+            };
+            (check, None)
+        }
+    }
+}
+
+async fn check_paper_links(client: &reqwest::Client, paper: &Paper) -> PaperLinkReport {
+    let mut links = Vec::new();
+    let mut url_alive = false;
+
+    let url = paper.url.trim();
+    if !url.is_empty() {
+        let (check, _) = check_link(client, url, "url").await;
+        url_alive = check.alive;
+        links.push(check);
+    }
+
+    let mut resolved_url = None;
+    if !paper.doi.trim().is_empty() {
+        let doi_url = format!(
+            "https://doi.org/{}",
+            crate::commands::paper_search::normalize_doi(&paper.doi)
+        );
+        let (check, final_url) = check_link(client, &doi_url, "doi").await;
+        links.push(check);
+        if !url_alive {
+            resolved_url = final_url.filter(|u| u != url);
+        }
+    }
+
+    PaperLinkReport {
+        paper_id: paper.id.clone(),
+        title: paper.title.clone(),
+        links,
+        resolved_url,
+    }
+}
+
+/// Replace a paper's dead or missing link with the one its DOI resolved to
+fn apply_resolved_url(db: &DbConnection, report: &PaperLinkReport) -> Result<(), AppError> {
+    if let Some(url) = &report.resolved_url {
+        let conn = db.get()?;
+        crate::db::papers::update_paper(
+            &conn,
+            &report.paper_id,
+            UpdatePaperInput {
+                url: Some(url.clone()),
+                ..Default::default()
+            },
+        )?;
+    }
+    Ok(())
+}
+
+/// Check that a paper's stored link and DOI still resolve. With `resolve`,
+/// a dead or missing link is replaced by the page the DOI now leads to.
+#[tauri::command]
+pub async fn validate_paper_links(
+    db: State<'_, DbConnection>,
+    paper_id: String,
+    resolve: Option<bool>,
+) -> Result<PaperLinkReport, AppError> {
+    let paper = {
+        let conn = db.get()?;
+        crate::db::papers::get_paper(&conn, &paper_id)?
+    };
+
+    let client = crate::commands::http::client()?;
+    let report = check_paper_links(&client, &paper).await;
+    if resolve.unwrap_or(false) {
+        apply_resolved_url(&db, &report)?;
+    }
+    Ok(report)
+}
+
+/// Check the links of every paper that has a link or DOI. Returns reports for
+/// the papers with at least one dead link.
+#[tauri::command]
+pub async fn validate_all_links(
+    db: State<'_, DbConnection>,
+    cancellation: State<'_, CancellationState>,
+    resolve: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<Vec<PaperLinkReport>, AppError> {
+    let papers: Vec<Paper> = {
+        let conn = db.get()?;
+        crate::db::papers::get_papers(&conn, &PaperFilter::default(), None, None, None)?
+            .into_iter()
+            .filter(|p| !p.url.trim().is_empty() || !p.doi.trim().is_empty())
+            .collect()
+    };
+
+    let client = crate::commands::http::client()?;
+    let token = cancellation.register(operation_id);
+    let mut reports = Vec::new();
+
+    for paper in &papers {
+        if token.is_cancelled() {
+            break;
+        }
+
+        let report = check_paper_links(&client, paper).await;
+        if !report.has_dead_link() && report.resolved_url.is_none() {
+            continue;
+        }
+        if resolve.unwrap_or(false) {
+            if let Err(e) = apply_resolved_url(&db, &report) {
+                log::warn!("Failed to update link for {}: {}", report.paper_id, e);
+            }
+        }
+        reports.push(report);
+    }
+    cancellation.finish(&token);
+
+    Ok(reports)
+}
@@ -0,0 +1,266 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use rusqlite::Connection;
+use tauri::State;
+
+use crate::commands::paper_search::normalize_doi;
+use crate::db::DbConnection;
+use crate::error::AppError;
+use crate::models::paper::{Paper, PaperFilter};
+
+/// Longest note name written to disk, in characters
+const MAX_NOTE_NAME_CHARS: usize = 120;
+
+/// Quote a value for YAML front matter
+fn yaml_string(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', " ")
+        .replace('\r', "");
+    format!("\"{}\"", escaped)
+}
+
+fn render_front_matter(paper: &Paper) -> String {
+    let mut yaml = String::from("---\n");
+    yaml.push_str(&format!("title: {}\n", yaml_string(&paper.title)));
+    if !paper.author.is_empty() {
+        yaml.push_str(&format!("author: {}\n", yaml_string(&paper.author)));
+    }
+    if paper.year > 0 {
+        yaml.push_str(&format!("year: {}\n", paper.year));
+    }
+    if !paper.doi.is_empty() {
+        yaml.push_str(&format!("doi: {}\n", yaml_string(&paper.doi)));
+    }
+    if paper.tags.is_empty() {
+        yaml.push_str("tags: []\n");
+    } else {
+        yaml.push_str("tags:\n");
+        for tag in &paper.tags {
+            yaml.push_str(&format!("  - {}\n", yaml_string(tag)));
+        }
+    }
+    yaml.push_str("---\n\n");
+    yaml
+}
+
+/// Append a `##` section with one bullet per item, skipping it when there are none
+fn push_list_section(markdown: &mut String, heading: &str, items: &[String]) {
+    let items: Vec<&str> = items
+        .iter()
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .collect();
+    if items.is_empty() {
+        return;
+    }
+
+    markdown.push_str(&format!("## {}\n\n", heading));
+    for item in items {
+        markdown.push_str(&format!("- {}\n", item));
+    }
+    markdown.push('\n');
+}
+
+fn push_text_section(markdown: &mut String, heading: &str, text: &str) {
+    let text = text.trim();
+    if !text.is_empty() {
+        markdown.push_str(&format!("## {}\n\n{}\n\n", heading, text));
+    }
+}
+
+/// Render a paper and its analysis as a standalone Markdown note.
+/// `related` holds `(note name, title)` pairs rendered as wiki-links.
+pub(crate) fn render_paper_markdown(paper: &Paper, related: &[(String, String)]) -> String {
+    let mut markdown = render_front_matter(paper);
+    markdown.push_str(&format!("# {}\n\n", paper.title));
+
+    push_text_section(&mut markdown, "Abstract", &paper.abstract_text);
+
+    let keywords: Vec<String> = paper
+        .keywords
+        .split([',', ';'])
+        .map(|k| k.trim().to_string())
+        .collect();
+    push_list_section(&mut markdown, "Keywords", &keywords);
+    push_list_section(&mut markdown, "Purposes", &paper.purposes);
+
+    let mut methods = Vec::new();
+    if paper.is_qualitative {
+        methods.extend(paper.qual_tools.iter().map(|t| format!("Qualitative: {}", t)));
+    }
+    if paper.is_quantitative {
+        methods.extend(paper.quant_techniques.iter().map(|t| format!("Quantitative: {}", t)));
+    }
+    push_list_section(&mut markdown, "Methods", &methods);
+
+    let variables: Vec<String> = [
+        ("Independent", &paper.vars_independent),
+        ("Dependent", &paper.vars_dependent),
+        ("Moderator", &paper.vars_moderator),
+        ("Mediator", &paper.vars_mediator),
+        ("Other", &paper.vars_others),
+    ]
+    .iter()
+    .filter(|(_, vars)| !vars.is_empty())
+    .map(|(kind, vars)| format!("**{}:** {}", kind, vars.join(", ")))
+    .collect();
+    push_list_section(&mut markdown, "Variables", &variables);
+
+    push_list_section(&mut markdown, "Results", &paper.results);
+    push_list_section(&mut markdown, "Limitations", &paper.limitations);
+    push_list_section(&mut markdown, "Implications", &paper.implications);
+    push_list_section(&mut markdown, "Future Research", &paper.future_plans);
+    push_text_section(&mut markdown, "Notes", &paper.user_notes);
+
+    let links: Vec<String> = related
+        .iter()
+        .map(|(name, title)| {
+            if name == title {
+                format!("[[{}]]", name)
+            } else {
+                format!("[[{}|{}]]", name, title)
+            }
+        })
+        .collect();
+    push_list_section(&mut markdown, "Related Papers", &links);
+
+    markdown
+}
+
+/// Turn a title into a file name Obsidian can link to
+fn sanitize_note_name(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .filter(|c| !matches!(c, '\\' | '/' | ':' | '*' | '?' | '"' | '<' | '>' | '|' | '#' | '^' | '[' | ']'))
+        .filter(|c| !c.is_control())
+        .take(MAX_NOTE_NAME_CHARS)
+        .collect();
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches('.')
+        .to_string()
+}
+
+/// Load the whole library with a unique note name per paper, in a stable order
+fn library_notes(conn: &Connection) -> Result<Vec<(Paper, String)>, AppError> {
+    let mut papers = crate::db::papers::get_papers(
+        conn,
+        &PaperFilter::default(),
+        Some("name".to_string()),
+        None,
+        None,
+    )?;
+    papers.sort_by(|a, b| {
+        (&a.title, &a.created_at, &a.id).cmp(&(&b.title, &b.created_at, &b.id))
+    });
+
+    let mut used = HashSet::new();
+    let notes = papers
+        .into_iter()
+        .map(|paper| {
+            let base = match sanitize_note_name(&paper.title) {
+                name if name.is_empty() => paper.id.clone(),
+                name => name,
+            };
+            let mut name = base.clone();
+            let mut n = 2;
+            while !used.insert(name.to_lowercase()) {
+                name = format!("{} ({})", base, n);
+                n += 1;
+            }
+            (paper, name)
+        })
+        .collect();
+    Ok(notes)
+}
+
+/// Links between library papers found in the cached citation links:
+/// paper id to the ids of library papers it references or is cited by
+fn library_links(
+    conn: &Connection,
+    notes: &[(Paper, String)],
+) -> Result<HashMap<String, Vec<String>>, AppError> {
+    let by_doi: HashMap<String, &str> = notes
+        .iter()
+        .filter(|(p, _)| !p.doi.trim().is_empty())
+        .map(|(p, _)| (normalize_doi(&p.doi), p.id.as_str()))
+        .collect();
+
+    let mut links: HashMap<String, Vec<String>> = HashMap::new();
+    for (doi, paper_id) in &by_doi {
+        let Some((references, citations)) = crate::db::citation_cache::get_links(conn, doi)? else {
+            continue;
+        };
+
+        let linked = references
+            .iter()
+            .chain(citations.iter())
+            .filter_map(|d| by_doi.get(&normalize_doi(d)))
+            .filter(|other| *other != paper_id);
+        for other in linked {
+            // Link both ways so each note shows the connection
+            for (from, to) in [(*paper_id, *other), (*other, *paper_id)] {
+                let entry = links.entry(from.to_string()).or_default();
+                if !entry.iter().any(|id| id == to) {
+                    entry.push(to.to_string());
+                }
+            }
+        }
+    }
+    Ok(links)
+}
+
+/// `(note name, title)` pairs for the papers linked to `paper_id`
+fn related_notes(
+    paper_id: &str,
+    notes: &[(Paper, String)],
+    links: &HashMap<String, Vec<String>>,
+) -> Vec<(String, String)> {
+    let Some(linked) = links.get(paper_id) else {
+        return Vec::new();
+    };
+    notes
+        .iter()
+        .filter(|(p, _)| linked.contains(&p.id))
+        .map(|(p, name)| (name.clone(), p.title.clone()))
+        .collect()
+}
+
+/// Export a paper's metadata and analysis as Markdown with YAML front matter
+#[tauri::command]
+pub fn export_paper_markdown(
+    db: State<'_, DbConnection>,
+    paper_id: String,
+) -> Result<String, AppError> {
+    let conn = db.get()?;
+    let paper = crate::db::papers::get_paper(&conn, &paper_id)?;
+    let notes = library_notes(&conn)?;
+    let links = library_links(&conn, &notes)?;
+    Ok(render_paper_markdown(&paper, &related_notes(&paper.id, &notes, &links)))
+}
+
+/// Write one Markdown note per paper into `dir`, wiki-linking papers that
+/// cite each other. Returns the number of notes written.
+#[tauri::command]
+pub fn export_library_obsidian(
+    db: State<'_, DbConnection>,
+    dir: String,
+) -> Result<usize, AppError> {
+    let dir = PathBuf::from(dir);
+    std::fs::create_dir_all(&dir)?;
+
+    let conn = db.get()?;
+    let notes = library_notes(&conn)?;
+    let links = library_links(&conn, &notes)?;
+
+    for (paper, name) in &notes {
+        let markdown = render_paper_markdown(paper, &related_notes(&paper.id, &notes, &links));
+        std::fs::write(dir.join(format!("{}.md", name)), markdown)?;
+    }
+
+    Ok(notes.len())
+}
@@ -9,6 +9,15 @@ pub mod google_drive;
 pub mod ai_analysis;
 pub mod highlights;
 pub mod pdf_indexing;
+pub mod pdf_metadata;
+pub mod pdf_edit;
 pub mod citations;
+pub mod markdown;
 pub mod automation;
 pub mod writing;
+pub mod cancellation;
+pub mod http;
+pub mod diagnostics;
+pub mod links;
+pub mod undo;
+pub mod keywords;
@@ -61,9 +61,10 @@ fn strip_namespaces(xml: &str) -> String {
 }
 
 pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
-    let client = reqwest::Client::new();
+    let client = crate::commands::http::client()?;
     let limit = query.limit.unwrap_or(10).min(100);
     let offset = query.offset.unwrap_or(0);
+    let years = super::parse_year_filter(query.year.as_deref().unwrap_or_default())?;
 
     let search_query = format!("all:{}", query.query);
 
@@ -77,11 +78,13 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
 
     let response = client
         .get(&url)
-        .header("User-Agent", "PaperManager/1.0")
+        .header("User-Agent", crate::commands::http::user_agent())
         .send()
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
+    crate::commands::http::check_rate_limit(&response, "arXiv")?;
+
     if !response.status().is_success() {
         return Err(AppError::Network("arXiv search failed".to_string()));
     }
@@ -109,28 +112,13 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
                 .and_then(|p| p.get(0..4))
                 .and_then(|y| y.parse::<i32>().ok());
 
-            if let Some(year_filter) = &query.year {
-                if let Some(paper_year) = year {
-                    if year_filter.contains('-') {
-                        let parts: Vec<&str> = year_filter.split('-').collect();
-                        if parts.len() == 2 {
-                            let start: i32 = parts[0].parse().unwrap_or(0);
-                            let end: i32 = parts[1].parse().unwrap_or(9999);
-                            if paper_year < start || paper_year > end {
-                                return None;
-                            }
-                        }
-                    } else if let Ok(filter_year) = year_filter.parse::<i32>() {
-                        if paper_year != filter_year {
-                            return None;
-                        }
-                    }
-                }
+            if year.is_some_and(|y| !years.contains(y)) {
+                return None;
             }
 
             let authors: Vec<Author> = entry.author
                 .into_iter()
-                .map(|a| Author { author_id: None, name: a.name })
+                .map(|a| Author { author_id: None, name: super::normalize_author_name(&a.name) })
                 .collect();
 
             let pdf_url = entry.link
@@ -163,6 +151,7 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
                     pubmed: None,
                     pubmed_central: None,
                 }),
+                tldr: None,
             })
         })
         .collect();
@@ -1,5 +1,6 @@
 use crate::error::AppError;
 use crate::models::paper_search::{Author, ExternalIds, OpenAccessPdf, SearchQuery, SearchResponse, SearchResult};
+use regex::Regex;
 use serde::Deserialize;
 
 const API_URL: &str = "https://api.crossref.org/works";
@@ -9,6 +10,11 @@ struct Response {
     message: Message,
 }
 
+#[derive(Debug, Deserialize)]
+struct WorkResponse {
+    message: Item,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 struct Message {
@@ -25,6 +31,7 @@ struct Item {
     author: Option<Vec<ItemAuthor>>,
     published_print: Option<ItemDate>,
     published_online: Option<ItemDate>,
+    issued: Option<ItemDate>,
     container_title: Option<Vec<String>>,
     #[serde(rename = "abstract")]
     abstract_text: Option<String>,
@@ -53,36 +60,52 @@ struct ItemLink {
 }
 
 pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
-    let client = reqwest::Client::new();
+    let client = crate::commands::http::client()?;
     let limit = query.limit.unwrap_or(10).min(100);
     let offset = query.offset.unwrap_or(0);
 
-    let mut url = format!(
-        "{}?query={}&rows={}&offset={}",
-        API_URL,
-        urlencoding::encode(&query.query),
-        limit,
-        offset
-    );
-
-    if let Some(year) = &query.year {
-        if year.contains('-') {
-            let parts: Vec<&str> = year.split('-').collect();
-            if parts.len() == 2 {
-                url.push_str(&format!("&filter=from-pub-date:{},until-pub-date:{}", parts[0], parts[1]));
-            }
-        } else {
-            url.push_str(&format!("&filter=from-pub-date:{},until-pub-date:{}", year, year));
+    let mut url = format!("{}?rows={}&offset={}", API_URL, limit, offset);
+
+    // The plain query searches bibliographic metadata, alongside any explicit one
+    let bibliographic = [Some(query.query.as_str()), query.bibliographic.as_deref()]
+        .into_iter()
+        .flatten()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let field_queries = [
+        ("query.bibliographic", Some(bibliographic.as_str())),
+        ("query.author", query.author.as_deref()),
+        ("query.title", query.title_query.as_deref()),
+    ];
+    for (param, value) in field_queries {
+        if let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) {
+            url.push_str(&format!("&{}={}", param, urlencoding::encode(value)));
         }
     }
 
+    let years = super::parse_year_filter(query.year.as_deref().unwrap_or_default())?;
+    let date_filters: Vec<String> = [
+        years.start.map(|y| format!("from-pub-date:{}", y)),
+        years.end.map(|y| format!("until-pub-date:{}", y)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if !date_filters.is_empty() {
+        url.push_str(&format!("&filter={}", date_filters.join(",")));
+    }
+
     let response = client
         .get(&url)
-        .header("User-Agent", "PaperManager/1.0 (mailto:contact@papermanager.app)")
+        .header("User-Agent", crate::commands::http::polite_user_agent())
         .send()
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
+    crate::commands::http::check_rate_limit(&response, "Crossref")?;
+
     if !response.status().is_success() {
         let status = response.status();
         return Err(AppError::Network(format!("Crossref search failed ({})", status)));
@@ -97,64 +120,7 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
         .message
         .items
         .into_iter()
-        .map(|item| {
-            let title = item.title
-                .and_then(|t| t.into_iter().next())
-                .unwrap_or_else(|| "Unknown".to_string());
-
-            let authors: Vec<Author> = item.author
-                .unwrap_or_default()
-                .into_iter()
-                .map(|a| {
-                    let name = if let Some(n) = a.name {
-                        n
-                    } else {
-                        let given = a.given.unwrap_or_default();
-                        let family = a.family.unwrap_or_default();
-                        if given.is_empty() {
-                            family
-                        } else if family.is_empty() {
-                            given
-                        } else {
-                            format!("{} {}", given, family)
-                        }
-                    };
-                    Author { author_id: None, name }
-                })
-                .collect();
-
-            let year = item.published_print
-                .or(item.published_online)
-                .and_then(|d| d.date_parts)
-                .and_then(|dp| dp.into_iter().next())
-                .and_then(|parts| parts.into_iter().next());
-
-            let venue = item.container_title.and_then(|v| v.into_iter().next());
-
-            let pdf_url = item.link.and_then(|links| {
-                links.into_iter()
-                    .find(|l| l.content_type.as_ref().map(|c| c.contains("pdf")).unwrap_or(false))
-                    .map(|l| l.url)
-            });
-
-            SearchResult {
-                paper_id: format!("DOI:{}", item.doi),
-                title,
-                authors,
-                year,
-                abstract_text: item.abstract_text,
-                venue,
-                citation_count: item.is_referenced_by_count,
-                url: Some(format!("https://doi.org/{}", item.doi)),
-                open_access_pdf: pdf_url.map(|url| OpenAccessPdf { url: Some(url), status: None }),
-                external_ids: Some(ExternalIds {
-                    doi: Some(item.doi),
-                    arxiv_id: None,
-                    pubmed: None,
-                    pubmed_central: None,
-                }),
-            }
-        })
+        .map(to_search_result)
         .collect();
 
     Ok(SearchResponse {
@@ -162,3 +128,154 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
         results,
     })
 }
+
+/// Look up a single work by DOI
+pub async fn get_by_doi(doi: &str) -> Result<SearchResult, AppError> {
+    let client = crate::commands::http::client()?;
+    // Keep the DOI's slashes as path separators, as Crossref expects
+    let url = format!("{}/{}", API_URL, urlencoding::encode(doi).replace("%2F", "/"));
+
+    let response = client
+        .get(&url)
+        .header("User-Agent", crate::commands::http::polite_user_agent())
+        .send()
+        .await
+        .map_err(|e| AppError::Network(e.to_string()))?;
+
+    crate::commands::http::check_rate_limit(&response, "Crossref")?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(AppError::NotFound(format!("DOI not found in Crossref: {}", doi)));
+    }
+    if !response.status().is_success() {
+        let status = response.status();
+        return Err(AppError::Network(format!("Crossref lookup failed ({})", status)));
+    }
+
+    let work: WorkResponse = response
+        .json()
+        .await
+        .map_err(|e| AppError::Parse(e.to_string()))?;
+
+    Ok(to_search_result(work.message))
+}
+
+/// Crossref abstracts are JATS XML (sometimes HTML); reduce them to plain text
+fn clean_abstract(jats: &str) -> String {
+    // Block-level tags separate words; inline ones (italic, sup, ...) must not
+    let blocks = Regex::new(r"(?i)</?(?:jats:)?(?:p|title|sec|list|list-item|br|div)\b[^>]*>").unwrap();
+    let text = blocks.replace_all(jats, " ");
+    let tags = Regex::new(r"<[^>]+>").unwrap();
+    let text = tags.replace_all(&text, "");
+    let text = decode_entities(&text);
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    text.strip_prefix("Abstract ").unwrap_or(&text).to_string()
+}
+
+/// Decode XML entities plus the HTML ones that show up in Crossref abstracts
+fn decode_entities(text: &str) -> String {
+    let entity = Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+    entity
+        .replace_all(text, |caps: &regex::Captures| {
+            let name = &caps[1];
+            let decoded = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(dec) = name.strip_prefix('#') {
+                dec.parse().ok().and_then(char::from_u32)
+            } else {
+                match name {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    "ndash" => Some('–'),
+                    "mdash" => Some('—'),
+                    "hellip" => Some('…'),
+                    _ => None,
+                }
+            };
+            decoded.map(String::from).unwrap_or_else(|| caps[0].to_string())
+        })
+        .into_owned()
+}
+
+fn to_search_result(item: Item) -> SearchResult {
+    let title = item.title
+        .and_then(|t| t.into_iter().next())
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    let authors: Vec<Author> = item.author
+        .unwrap_or_default()
+        .into_iter()
+        .map(|a| {
+            let name = if let Some(n) = a.name {
+                super::normalize_author_name(&n)
+            } else {
+                let given = a.given.unwrap_or_default();
+                let family = a.family.unwrap_or_default();
+                if given.is_empty() {
+                    family
+                } else if family.is_empty() {
+                    given
+                } else {
+                    format!("{}, {}", family.trim(), given.trim())
+                }
+            };
+            Author { author_id: None, name }
+        })
+        .collect();
+
+    let year = item.published_print
+        .or(item.published_online)
+        .or(item.issued)
+        .and_then(|d| d.date_parts)
+        .and_then(|dp| dp.into_iter().next())
+        .and_then(|parts| parts.into_iter().next());
+
+    let venue = item.container_title.and_then(|v| v.into_iter().next());
+
+    let pdf_url = item.link.and_then(|links| {
+        links.into_iter()
+            .find(|l| l.content_type.as_ref().map(|c| c.contains("pdf")).unwrap_or(false))
+            .map(|l| l.url)
+    });
+
+    SearchResult {
+        paper_id: format!("DOI:{}", item.doi),
+        title,
+        authors,
+        year,
+        abstract_text: item.abstract_text.as_deref().map(clean_abstract),
+        venue,
+        citation_count: item.is_referenced_by_count,
+        url: Some(format!("https://doi.org/{}", item.doi)),
+        open_access_pdf: pdf_url.map(|url| OpenAccessPdf { url: Some(url), status: None }),
+        external_ids: Some(ExternalIds {
+            doi: Some(item.doi),
+            arxiv_id: None,
+            pubmed: None,
+            pubmed_central: None,
+        }),
+        tldr: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_abstract() {
+        let jats = "<jats:title>Abstract</jats:title><jats:p>We study <jats:italic>in vitro</jats:italic> \
+            growth of H<jats:sub>2</jats:sub>O crystals &amp; their effects.</jats:p>\
+            <jats:p>Results: p &lt; 0.05&#8212;&nbsp;overall.</jats:p>";
+        let text = clean_abstract(jats);
+        assert!(!text.contains("jats"));
+        assert_eq!(
+            text,
+            "We study in vitro growth of H2O crystals & their effects. Results: p < 0.05— overall."
+        );
+    }
+}
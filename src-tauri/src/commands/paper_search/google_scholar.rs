@@ -1,10 +1,311 @@
 use crate::error::AppError;
-use crate::models::paper_search::{Author, OpenAccessPdf, SearchQuery, SearchResponse, SearchResult};
+use crate::models::paper_search::{Author, ExternalIds, OpenAccessPdf, SearchQuery, SearchResponse, SearchResult};
+use regex::Regex;
 use scraper::{Html, Selector};
+use serde::Deserialize;
+use std::time::Duration;
 
+pub const SERPAPI_KEY_SETTING: &str = "serpapi_key";
+
+const SERPAPI_URL: &str = "https://serpapi.com/search.json";
+
+/// Scraping gives up after this long; Scholar can stall for 30s+ behind its robot check
+const SCHOLAR_TIMEOUT: Duration = Duration::from_secs(15);
+
+const SCHOLAR_TIMED_OUT: &str = "Google Scholar timed out";
+
+/// Text of Scholar's "Please show you're not a robot" interstitial, lowercased
+const ROBOT_CHECK_MARKERS: [&str; 4] =
+    ["gs_captcha", "not a robot", "unusual traffic", "recaptcha"];
+
+#[derive(Debug, Deserialize)]
+struct SerpResponse {
+    error: Option<String>,
+    search_information: Option<SerpSearchInformation>,
+    #[serde(default)]
+    organic_results: Vec<SerpResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SerpSearchInformation {
+    total_results: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SerpResult {
+    title: String,
+    result_id: Option<String>,
+    link: Option<String>,
+    snippet: Option<String>,
+    publication_info: Option<SerpPublicationInfo>,
+    #[serde(default)]
+    resources: Vec<SerpResource>,
+    inline_links: Option<SerpInlineLinks>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SerpPublicationInfo {
+    summary: Option<String>,
+    #[serde(default)]
+    authors: Vec<SerpAuthor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SerpAuthor {
+    name: String,
+    author_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SerpResource {
+    file_format: Option<String>,
+    link: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SerpInlineLinks {
+    cited_by: Option<SerpCitedBy>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SerpCitedBy {
+    total: Option<i32>,
+    cites_id: Option<String>,
+}
+
+/// Find a DOI in a publisher URL, e.g. `https://onlinelibrary.wiley.com/doi/full/10.1002/abc.123`
+fn doi_from_url(url: &str) -> Option<String> {
+    let url = urlencoding::decode(url).map(|u| u.into_owned()).unwrap_or_else(|_| url.to_string());
+    let path = url.split(['?', '#', '&']).next().unwrap_or_default();
+    let doi = crate::commands::pdf_metadata::find_doi(path)?;
+    let doi = [".pdf", "/full", "/abstract", "/pdf", "/epdf"]
+        .iter()
+        .fold(doi, |doi, suffix| doi.strip_suffix(suffix).map(str::to_string).unwrap_or(doi));
+    Some(doi)
+}
+
+fn arxiv_id_from_url(url: &str) -> Option<String> {
+    let re = Regex::new(r"arxiv\.org/(?:abs|pdf)/([0-9]{4}\.[0-9]{4,5}|[a-z\-]+(?:\.[A-Z]{2})?/[0-9]{7})").unwrap();
+    re.captures(url).map(|caps| caps[1].to_string())
+}
+
+/// Scholar's cluster id, from the `cluster=` or `cites=` parameter of a result's links
+fn cluster_id_from_url(url: &str) -> Option<String> {
+    let re = Regex::new(r"[?&](?:cluster|cites)=([0-9]+)").unwrap();
+    re.captures(url).map(|caps| caps[1].to_string())
+}
+
+/// Find a four-digit publication year in Scholar's "authors - venue, year - host" line
+fn parse_year(text: &str) -> Option<i32> {
+    text.chars()
+        .collect::<Vec<_>>()
+        .windows(4)
+        .find_map(|window| {
+            let s: String = window.iter().collect();
+            if s.chars().all(|c| c.is_ascii_digit()) {
+                let y: i32 = s.parse().ok()?;
+                if (1900..=2030).contains(&y) {
+                    return Some(y);
+                }
+            }
+            None
+        })
+}
+
+/// Identify a result: DOI from the publisher link, else arXiv id, else Scholar's own ids
+fn identify_result(
+    links: [Option<&str>; 2],
+    cluster_id: Option<String>,
+    position: usize,
+) -> (String, Option<ExternalIds>) {
+    let doi = links.iter().flatten().find_map(|link| doi_from_url(link));
+    let arxiv_id = links.iter().flatten().find_map(|link| arxiv_id_from_url(link));
+
+    let paper_id = match (&doi, &arxiv_id, cluster_id) {
+        (Some(doi), _, _) => format!("DOI:{}", doi),
+        (None, Some(arxiv_id), _) => format!("ARXIV:{}", arxiv_id),
+        (None, None, Some(cid)) => format!("GS:{}", cid),
+        _ => format!("GS:{}", position),
+    };
+    let external_ids = (doi.is_some() || arxiv_id.is_some()).then_some(ExternalIds {
+        doi,
+        arxiv_id,
+        pubmed: None,
+        pubmed_central: None,
+    });
+    (paper_id, external_ids)
+}
+
+/// Append Scholar's year range parameters
+fn push_year_range(url: &mut String, year: Option<&str>) -> Result<(), AppError> {
+    let years = super::parse_year_filter(year.unwrap_or_default())?;
+    if let Some(start) = years.start {
+        url.push_str(&format!("&as_ylo={}", start));
+    }
+    if let Some(end) = years.end {
+        url.push_str(&format!("&as_yhi={}", end));
+    }
+    Ok(())
+}
+
+/// Search Google Scholar through SerpAPI, which returns structured results without CAPTCHAs
+pub async fn search_serpapi(query: SearchQuery, api_key: &str) -> Result<SearchResponse, AppError> {
+    let client = crate::commands::http::client()?;
+    let limit = query.limit.unwrap_or(10).min(20);
+    let offset = query.offset.unwrap_or(0);
+
+    let mut url = format!(
+        "{}?engine=google_scholar&q={}&start={}&num={}&api_key={}",
+        SERPAPI_URL,
+        urlencoding::encode(&query.query),
+        offset,
+        limit,
+        urlencoding::encode(api_key)
+    );
+    push_year_range(&mut url, query.year.as_deref())?;
+
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| AppError::Network(e.to_string()))?;
+
+    crate::commands::http::check_rate_limit(&response, "SerpAPI")?;
+
+    let status = response.status();
+    let serp: SerpResponse = response
+        .json()
+        .await
+        .map_err(|e| AppError::Parse(e.to_string()))?;
+
+    if let Some(error) = serp.error {
+        // SerpAPI reports "no results" as an error too
+        if status.is_success() && error.contains("hasn't returned any results") {
+            return Ok(SearchResponse { total: 0, results: Vec::new() });
+        }
+        return Err(match status {
+            reqwest::StatusCode::UNAUTHORIZED => AppError::Auth(format!("SerpAPI: {}", error)),
+            _ => AppError::Network(format!("SerpAPI search failed: {}", error)),
+        });
+    }
+
+    let results: Vec<SearchResult> = serp
+        .organic_results
+        .into_iter()
+        .enumerate()
+        .map(|(idx, item)| {
+            let summary = item
+                .publication_info
+                .as_ref()
+                .and_then(|info| info.summary.clone())
+                .unwrap_or_default();
+            let parts: Vec<&str> = summary.split(" - ").collect();
+
+            let authors: Vec<Author> = match item.publication_info.map(|info| info.authors) {
+                Some(authors) if !authors.is_empty() => authors
+                    .into_iter()
+                    .map(|a| Author { author_id: a.author_id, name: super::normalize_author_name(&a.name) })
+                    .collect(),
+                _ => parts
+                    .first()
+                    .map(|s| {
+                        s.split(',')
+                            .map(|name| name.trim().trim_end_matches('…').trim())
+                            .filter(|name| !name.is_empty())
+                            .map(|name| Author { author_id: None, name: super::normalize_author_name(name) })
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            };
+
+            // "Venue, 2020" - the venue is everything before the year
+            let venue = parts.get(1).map(|s| {
+                s.rsplit_once(',')
+                    .filter(|(_, year)| parse_year(year).is_some())
+                    .map(|(venue, _)| venue)
+                    .unwrap_or(s)
+                    .trim()
+                    .to_string()
+            });
+
+            let pdf_url = item
+                .resources
+                .iter()
+                .find(|r| r.file_format.as_deref() == Some("PDF"))
+                .and_then(|r| r.link.clone());
+
+            let cited_by = item.inline_links.and_then(|links| links.cited_by);
+            let cluster_id = cited_by
+                .as_ref()
+                .and_then(|c| c.cites_id.clone())
+                .or(item.result_id);
+
+            let (paper_id, external_ids) = identify_result(
+                [item.link.as_deref(), pdf_url.as_deref()],
+                cluster_id,
+                idx + offset as usize,
+            );
+
+            SearchResult {
+                paper_id,
+                title: item.title.trim().to_string(),
+                authors,
+                year: parse_year(&summary),
+                abstract_text: item.snippet,
+                venue,
+                citation_count: cited_by.and_then(|c| c.total),
+                url: item.link,
+                open_access_pdf: pdf_url.map(|u| OpenAccessPdf {
+                    url: Some(u),
+                    status: Some("green".to_string()),
+                }),
+                external_ids,
+                tldr: None,
+            }
+        })
+        .collect();
+
+    let total = serp
+        .search_information
+        .and_then(|info| info.total_results)
+        .unwrap_or(results.len() as i32);
+
+    Ok(SearchResponse { total, results })
+}
+
+fn scholar_network_error(e: reqwest::Error) -> AppError {
+    if e.is_timeout() {
+        AppError::Network(SCHOLAR_TIMED_OUT.to_string())
+    } else {
+        AppError::Network(e.to_string())
+    }
+}
+
+/// Whether Scholar answered with its robot check rather than a results page.
+/// A results page can mention CAPTCHAs in titles and snippets, so only pages
+/// without the results list are checked for the interstitial's text.
+fn is_robot_check(final_url: &str, html: &str) -> bool {
+    if final_url.contains("/sorry/") {
+        return true;
+    }
+    if html.contains("id=\"gs_res_ccl") {
+        return false;
+    }
+    let html = html.to_lowercase();
+    ROBOT_CHECK_MARKERS.iter().any(|marker| html.contains(marker))
+}
+
+fn robot_check_error() -> AppError {
+    AppError::RateLimited(
+        "Google Scholar requires CAPTCHA verification. Try again later, or add a SerpAPI key in Settings.".to_string()
+    )
+}
+
+/// Scrape Google Scholar's HTML results; used when no SerpAPI key is configured
 pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
-    let client = reqwest::Client::builder()
+    let client = crate::commands::http::client_builder()?
         .user_agent("Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36")
+        .timeout(SCHOLAR_TIMEOUT)
         .build()
         .map_err(|e| AppError::Network(e.to_string()))?;
 
@@ -18,16 +319,7 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
         limit
     );
 
-    if let Some(year) = &query.year {
-        if year.contains('-') {
-            let parts: Vec<&str> = year.split('-').collect();
-            if parts.len() == 2 {
-                url.push_str(&format!("&as_ylo={}&as_yhi={}", parts[0], parts[1]));
-            }
-        } else {
-            url.push_str(&format!("&as_ylo={}&as_yhi={}", year, year));
-        }
-    }
+    push_year_range(&mut url, query.year.as_deref())?;
 
     let response = client
         .get(&url)
@@ -36,7 +328,13 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
         .header("Connection", "keep-alive")
         .send()
         .await
-        .map_err(|e| AppError::Network(e.to_string()))?;
+        .map_err(scholar_network_error)?;
+
+    // Blocked clients are redirected to google.com/sorry/
+    if is_robot_check(response.url().as_str(), "") {
+        return Err(robot_check_error());
+    }
+    crate::commands::http::check_rate_limit(&response, "Google Scholar")?;
 
     if !response.status().is_success() {
         return Err(AppError::Network(format!(
@@ -45,15 +343,17 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
         )));
     }
 
-    let html_text = response
-        .text()
-        .await
-        .map_err(|e| AppError::Parse(e.to_string()))?;
+    let final_url = response.url().to_string();
+    let html_text = response.text().await.map_err(|e| {
+        if e.is_timeout() {
+            scholar_network_error(e)
+        } else {
+            AppError::Parse(e.to_string())
+        }
+    })?;
 
-    if html_text.contains("CAPTCHA") || html_text.contains("unusual traffic") {
-        return Err(AppError::Network(
-            "Google Scholar requires CAPTCHA verification. Try again later.".to_string()
-        ));
+    if is_robot_check(&final_url, &html_text) {
+        return Err(robot_check_error());
     }
 
     let document = Html::parse_document(&html_text);
@@ -98,26 +398,13 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
                     .take(3)
                     .map(|name| Author {
                         author_id: None,
-                        name: name.trim().to_string(),
+                        name: super::normalize_author_name(name.trim_end_matches('…')),
                     })
                     .collect()
             })
             .unwrap_or_default();
 
-        let year: Option<i32> = author_info
-            .chars()
-            .collect::<Vec<_>>()
-            .windows(4)
-            .find_map(|window| {
-                let s: String = window.iter().collect();
-                if s.chars().all(|c| c.is_ascii_digit()) {
-                    let y: i32 = s.parse().ok()?;
-                    if (1900..=2030).contains(&y) {
-                        return Some(y);
-                    }
-                }
-                None
-            });
+        let year = parse_year(&author_info);
 
         let venue = parts.get(1).map(|s| s.trim().to_string());
 
@@ -144,8 +431,25 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
             .and_then(|e| e.value().attr("href"))
             .map(|s| s.to_string());
 
+        let cluster_id = element
+            .value()
+            .attr("data-cid")
+            .map(|cid| cid.to_string())
+            .or_else(|| {
+                element
+                    .select(&cite_selector)
+                    .filter_map(|e| e.value().attr("href"))
+                    .find_map(cluster_id_from_url)
+            });
+
+        let (paper_id, external_ids) = identify_result(
+            [url.as_deref(), pdf_url.as_deref()],
+            cluster_id,
+            idx + offset as usize,
+        );
+
         results.push(SearchResult {
-            paper_id: format!("GS:{}", idx + offset as usize),
+            paper_id,
             title: title.trim().to_string(),
             authors,
             year,
@@ -157,7 +461,8 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
                 url: Some(u),
                 status: Some("green".to_string()),
             }),
-            external_ids: None,
+            external_ids,
+            tldr: None,
         });
     }
 
@@ -165,3 +470,20 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
 
     Ok(SearchResponse { total, results })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_robot_check() {
+        let results = r#"<div id="gs_res_ccl_mid"><div class="gs_r gs_or gs_scl">
+            <h3 class="gs_rt"><a href="https://example.com">Breaking CAPTCHA with unusual traffic</a></h3>
+            </div></div>"#;
+        assert!(!is_robot_check("https://scholar.google.com/scholar?q=captcha", results));
+
+        let interstitial = r#"<form id="gs_captcha_f"><h1>Please show you're not a robot</h1></form>"#;
+        assert!(is_robot_check("https://scholar.google.com/scholar?q=x", interstitial));
+        assert!(is_robot_check("https://www.google.com/sorry/index?continue=x", ""));
+    }
+}
@@ -19,6 +19,34 @@ struct OutputData {
     records: Option<Vec<Record>>,
 }
 
+/// KCI's error envelope: an `<error>` element or a `<result>` with a non-zero
+/// `<resultCode>`, either at the root or inside `outputData`
+#[derive(Debug, Deserialize)]
+struct ErrorEnvelope {
+    error: Option<KciError>,
+    result: Option<KciResult>,
+    #[serde(rename = "outputData")]
+    output_data: Option<Box<ErrorEnvelope>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KciError {
+    #[serde(rename = "code", alias = "resultCode")]
+    code: Option<String>,
+    #[serde(rename = "message", alias = "resultMsg")]
+    message: Option<String>,
+    #[serde(rename = "$text")]
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KciResult {
+    #[serde(rename = "resultCode")]
+    code: Option<String>,
+    #[serde(rename = "resultMsg")]
+    message: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct Record {
     #[serde(rename = "articleId")]
@@ -35,8 +63,65 @@ struct Record {
     url: Option<String>,
 }
 
+/// Find KCI's error message, if the response is an error envelope
+fn find_error(xml: &str) -> Option<(Option<String>, String)> {
+    let mut envelope: ErrorEnvelope = xml_from_str(xml).ok()?;
+    loop {
+        if let Some(error) = envelope.error {
+            let message = error
+                .message
+                .or(error.text)
+                .map(|m| m.trim().to_string())
+                .filter(|m| !m.is_empty())
+                .unwrap_or_else(|| "unknown error".to_string());
+            return Some((error.code, message));
+        }
+        if let Some(result) = envelope.result {
+            let code = result.code.map(|c| c.trim().to_string());
+            // All-zero codes ("0", "00", "0000") mean success
+            if let Some(code) = code.filter(|c| !c.is_empty() && !c.chars().all(|ch| ch == '0')) {
+                let message = result.message.unwrap_or_default().trim().to_string();
+                return Some((Some(code), message));
+            }
+        }
+        envelope = *envelope.output_data?;
+    }
+}
+
+fn is_key_error(code: Option<&str>, message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("key")
+        || message.contains("인증")
+        || message.contains("키")
+        || code.is_some_and(|c| c.eq_ignore_ascii_case("E0001"))
+}
+
+/// Parse a KCI search response, turning its error envelope into a readable error
+fn parse_response(xml: &str) -> Result<OutputData, AppError> {
+    if let Some((code, message)) = find_error(xml) {
+        if is_key_error(code.as_deref(), &message) {
+            return Err(AppError::Network(format!(
+                "KCI API key invalid or missing: {}",
+                message
+            )));
+        }
+        return Err(AppError::Network(match code {
+            Some(code) => format!("KCI error {}: {}", code, message),
+            None => format!("KCI error: {}", message),
+        }));
+    }
+
+    let kci_response: Response = xml_from_str(xml)
+        .map_err(|e| AppError::Parse(format!("Failed to parse KCI response: {}", e)))?;
+
+    Ok(kci_response.output_data.unwrap_or(OutputData {
+        total_count: None,
+        records: None,
+    }))
+}
+
 pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
-    let client = reqwest::Client::new();
+    let client = crate::commands::http::client()?;
     let limit = query.limit.unwrap_or(10).min(100);
     let offset = query.offset.unwrap_or(0);
     let page = (offset / limit) + 1;
@@ -52,15 +137,12 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
         page
     );
 
-    if let Some(year) = &query.year {
-        if year.contains('-') {
-            let parts: Vec<&str> = year.split('-').collect();
-            if parts.len() == 2 {
-                url.push_str(&format!("&startPubYear={}&endPubYear={}", parts[0], parts[1]));
-            }
-        } else {
-            url.push_str(&format!("&startPubYear={}&endPubYear={}", year, year));
-        }
+    let years = super::parse_year_filter(query.year.as_deref().unwrap_or_default())?;
+    if let Some(start) = years.start {
+        url.push_str(&format!("&startPubYear={}", start));
+    }
+    if let Some(end) = years.end {
+        url.push_str(&format!("&endPubYear={}", end));
     }
 
     let response = client
@@ -70,6 +152,8 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
+    crate::commands::http::check_rate_limit(&response, "KCI")?;
+
     if !response.status().is_success() {
         return Err(AppError::Network("KCI search failed".to_string()));
     }
@@ -79,13 +163,7 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
         .await
         .map_err(|e| AppError::Parse(e.to_string()))?;
 
-    let kci_response: Response = xml_from_str(&xml_text)
-        .map_err(|e| AppError::Parse(format!("Failed to parse KCI response: {}", e)))?;
-
-    let output = kci_response.output_data.unwrap_or(OutputData {
-        total_count: None,
-        records: None,
-    });
+    let output = parse_response(&xml_text)?;
 
     let total = output.total_count
         .and_then(|c| c.parse::<i32>().ok())
@@ -102,9 +180,10 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
             let authors: Vec<Author> = record.author
                 .map(|a| {
                     a.split(';')
+                        .filter(|name| !name.trim().is_empty())
                         .map(|name| Author {
                             author_id: None,
-                            name: name.trim().to_string(),
+                            name: super::normalize_author_name(name),
                         })
                         .collect()
                 })
@@ -131,9 +210,55 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
                     pubmed: None,
                     pubmed_central: None,
                 }),
+                tldr: None,
             })
         })
         .collect();
 
     Ok(SearchResponse { total, results })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_error_response() {
+        let invalid_key = r#"<?xml version="1.0" encoding="UTF-8"?>
+            <MetaData>
+                <outputData>
+                    <result>
+                        <resultCode>E0001</resultCode>
+                        <resultMsg>인증키가 유효하지 않습니다.</resultMsg>
+                    </result>
+                </outputData>
+            </MetaData>"#;
+        match parse_response(invalid_key) {
+            Err(AppError::Network(message)) => {
+                assert!(message.starts_with("KCI API key invalid or missing"))
+            }
+            other => panic!("expected a key error, got {:?}", other.map(|_| ())),
+        }
+
+        assert!(matches!(
+            parse_response("<MetaData><error>Invalid API key</error></MetaData>"),
+            Err(AppError::Network(message)) if message.starts_with("KCI API key invalid or missing")
+        ));
+
+        let server_error =
+            "<MetaData><error><code>E9999</code><message>Service unavailable</message></error></MetaData>";
+        match parse_response(server_error) {
+            Err(AppError::Network(message)) => {
+                assert_eq!(message, "KCI error E9999: Service unavailable")
+            }
+            other => panic!("expected a KCI error, got {:?}", other.map(|_| ())),
+        }
+
+        let ok = r#"<MetaData><outputData><result><resultCode>00</resultCode></result>
+            <totalCount>1</totalCount><record><articleId>ART1</articleId><title>T</title></record>
+            </outputData></MetaData>"#;
+        let output = parse_response(ok).unwrap();
+        assert_eq!(output.total_count.as_deref(), Some("1"));
+        assert_eq!(output.records.unwrap().len(), 1);
+    }
+}
@@ -5,22 +5,303 @@ mod kci;
 mod pubmed;
 mod semantic_scholar;
 
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::db::DbConnection;
 use crate::error::AppError;
-use crate::models::paper_search::{SearchQuery, SearchResponse, SearchResult, SearchSource};
+use crate::models::paper_search::{
+    CitationEdge, CitationGraphNode, MultiSearchResponse, SearchHistoryEntry, SearchQuery,
+    SearchResponse, SearchResult, SearchSource, SearchSourceInfo, SourceStat,
+};
+use crate::models::{Paper, UpdatePaperInput};
 
 /// Search papers using the specified source (defaults to Semantic Scholar)
 #[tauri::command]
-pub async fn search_papers(query: SearchQuery) -> Result<SearchResponse, AppError> {
+pub async fn search_papers(
+    query: SearchQuery,
+    db: State<'_, DbConnection>,
+) -> Result<SearchResponse, AppError> {
     let source = query.source.unwrap_or(SearchSource::SemanticScholar);
+    validate_query(source, &query)?;
+
+    let serpapi_key = match source {
+        SearchSource::GoogleScholar => serpapi_key(&db)?,
+        _ => None,
+    };
+    let text = query.query.clone();
+    let response = search_source(source, query, serpapi_key).await?;
+
+    // History is a convenience; failing to log it mustn't fail the search
+    let logged = db
+        .get()
+        .and_then(|conn| crate::db::search_history::record(&conn, &text, source, response.total));
+    if let Err(e) = logged {
+        log::warn!("Failed to record search history: {}", e);
+    }
+    Ok(response)
+}
+
+/// Most recent searches made with `search_papers`, newest first
+#[tauri::command]
+pub fn get_search_history(
+    db: State<'_, DbConnection>,
+    limit: Option<i64>,
+) -> Result<Vec<SearchHistoryEntry>, AppError> {
+    let conn = db.get()?;
+    crate::db::search_history::list(&conn, limit.unwrap_or(20).clamp(1, 200))
+}
+
+#[tauri::command]
+pub fn clear_search_history(db: State<'_, DbConnection>) -> Result<(), AppError> {
+    let conn = db.get()?;
+    crate::db::search_history::clear(&conn)
+}
+
+fn serpapi_key(db: &DbConnection) -> Result<Option<String>, AppError> {
+    let conn = db.get()?;
+    crate::db::settings::get_setting_string(&conn, google_scholar::SERPAPI_KEY_SETTING)
+}
+
+/// Reject a query with nothing to search for, or a malformed year filter,
+/// before any request is made. Crossref can search by its field queries alone.
+fn validate_query(source: SearchSource, query: &SearchQuery) -> Result<(), AppError> {
+    let has_field_query = source == SearchSource::Crossref
+        && [&query.author, &query.title_query, &query.bibliographic]
+            .iter()
+            .any(|field| field.as_deref().is_some_and(|q| !q.trim().is_empty()));
+    if query.query.trim().is_empty() && !has_field_query {
+        return Err(AppError::Validation("query is empty".to_string()));
+    }
+    parse_year_filter(query.year.as_deref().unwrap_or_default())?;
+    Ok(())
+}
 
+async fn search_source(
+    source: SearchSource,
+    query: SearchQuery,
+    serpapi_key: Option<String>,
+) -> Result<SearchResponse, AppError> {
+    validate_query(source, &query)?;
     match source {
-        SearchSource::SemanticScholar => semantic_scholar::search(query).await,
-        SearchSource::PubMed => pubmed::search(query).await,
-        SearchSource::Crossref => crossref::search(query).await,
+        SearchSource::SemanticScholar => search_paginated(query, semantic_scholar::search).await,
+        SearchSource::PubMed => search_paginated(query, pubmed::search).await,
+        SearchSource::Crossref => search_paginated(query, crossref::search).await,
         SearchSource::Arxiv => arxiv::search(query).await,
         SearchSource::Kci => kci::search(query).await,
-        SearchSource::GoogleScholar => google_scholar::search(query).await,
+        // Google Scholar goes through SerpAPI when the user has supplied a key
+        SearchSource::GoogleScholar => match serpapi_key {
+            Some(key) => google_scholar::search_serpapi(query, &key).await,
+            None => google_scholar::search(query).await,
+        },
+    }
+}
+
+/// Search several sources concurrently (every source by default) and merge the
+/// results, dropping duplicates by DOI or title. A failing source is reported
+/// in its `SourceStat` instead of failing the whole search.
+#[tauri::command]
+pub async fn search_multiple_sources(
+    query: SearchQuery,
+    sources: Option<Vec<SearchSource>>,
+    db: State<'_, DbConnection>,
+) -> Result<MultiSearchResponse, AppError> {
+    let mut unique: Vec<SearchSource> = Vec::new();
+    for source in sources
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| SearchSource::ALL.to_vec())
+    {
+        if !unique.contains(&source) {
+            unique.push(source);
+        }
+    }
+    let sources = unique;
+
+    // Fail outright when no source can run the query; otherwise the sources
+    // that can't report it in their stats
+    let validations: Vec<_> = sources.iter().map(|&s| validate_query(s, &query)).collect();
+    if !validations.iter().any(Result::is_ok) {
+        if let Some(Err(e)) = validations.into_iter().next() {
+            return Err(e);
+        }
+    }
+
+    let serpapi_key = serpapi_key(&db)?;
+    let mut tasks = tokio::task::JoinSet::new();
+    for (index, source) in sources.iter().copied().enumerate() {
+        let query = SearchQuery {
+            source: Some(source),
+            ..query.clone()
+        };
+        let serpapi_key = serpapi_key.clone();
+        tasks.spawn(async move {
+            let started = Instant::now();
+            let result = search_source(source, query, serpapi_key).await;
+            (index, started.elapsed(), result)
+        });
+    }
+
+    let mut outcomes = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(e) => log::warn!("Search task failed: {}", e),
+        }
+    }
+    // Merge in the order the sources were asked for, not the order they finished
+    outcomes.sort_by_key(|(index, _, _)| *index);
+
+    let mut seen = HashSet::new();
+    let mut results = Vec::new();
+    let mut stats = Vec::new();
+    for (index, elapsed, result) in outcomes {
+        let (count, error) = match result {
+            Ok(response) => {
+                let count = response.results.len();
+                results.extend(
+                    response
+                        .results
+                        .into_iter()
+                        .filter(|r| seen.insert(merge_key(r))),
+                );
+                (count, None)
+            }
+            Err(e) => (0, Some(e.to_string())),
+        };
+        stats.push(SourceStat {
+            source: sources[index],
+            count,
+            elapsed_ms: elapsed.as_millis() as u64,
+            error,
+        });
+    }
+
+    Ok(MultiSearchResponse {
+        total: results.len() as i32,
+        results,
+        sources: stats,
+    })
+}
+
+/// Identity of a search result across sources: its DOI, or else its title and year
+fn merge_key(result: &SearchResult) -> String {
+    if let Some(doi) = result
+        .external_ids
+        .as_ref()
+        .and_then(|ids| ids.doi.as_deref())
+        .filter(|doi| !doi.trim().is_empty())
+    {
+        return format!("doi:{}", normalize_doi(doi));
+    }
+    let title: String = result
+        .title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    format!("title:{}:{}", title, result.year.unwrap_or_default())
+}
+
+/// List every search source with the filters it supports and its API key status
+#[tauri::command]
+pub fn get_search_sources(db: State<'_, DbConnection>) -> Result<Vec<SearchSourceInfo>, AppError> {
+    let serpapi_configured = serpapi_key(&db)?.is_some();
+
+    let sources = SearchSource::ALL
+        .into_iter()
+        .map(|source| {
+            let (display_name, supports_fields_of_study, supports_server_sort) = match source {
+                SearchSource::SemanticScholar => ("Semantic Scholar", true, false),
+                SearchSource::PubMed => ("PubMed", false, true),
+                SearchSource::Crossref => ("Crossref", false, true),
+                SearchSource::Arxiv => ("arXiv", false, true),
+                SearchSource::Kci => ("KCI", false, false),
+                SearchSource::GoogleScholar => ("Google Scholar", false, false),
+            };
+            // Both keys are optional: Semantic Scholar is rate-limited without one,
+            // and Google Scholar falls back to scraping without SerpAPI
+            let (api_key_configured, api_key_setting) = match source {
+                SearchSource::SemanticScholar => (semantic_scholar::get_api_key().is_some(), None),
+                SearchSource::GoogleScholar => (
+                    serpapi_configured,
+                    Some(google_scholar::SERPAPI_KEY_SETTING.to_string()),
+                ),
+                _ => (false, None),
+            };
+
+            SearchSourceInfo {
+                source,
+                display_name: display_name.to_string(),
+                supports_year_filter: true,
+                supports_fields_of_study,
+                supports_server_sort,
+                requires_api_key: false,
+                api_key_configured,
+                api_key_setting,
+            }
+        })
+        .collect();
+    Ok(sources)
+}
+
+/// Most results gathered across pages for a single search
+const MAX_FETCH_RESULTS: i32 = 1000;
+
+/// Results per request for sources that cap a single page at 100
+const PAGE_SIZE: i32 = 100;
+
+/// Run a search page by page when more results are wanted than one request returns.
+/// A failure after the first page ends the search with the results gathered so far.
+async fn search_paginated<F, Fut>(query: SearchQuery, search: F) -> Result<SearchResponse, AppError>
+where
+    F: Fn(SearchQuery) -> Fut,
+    Fut: Future<Output = Result<SearchResponse, AppError>>,
+{
+    let wanted = if query.fetch_all.unwrap_or(false) {
+        MAX_FETCH_RESULTS
+    } else {
+        query.limit.unwrap_or(10).min(MAX_FETCH_RESULTS)
+    };
+    if wanted <= PAGE_SIZE {
+        return search(query).await;
+    }
+
+    let start = query.offset.unwrap_or(0);
+    let mut results: Vec<SearchResult> = Vec::new();
+    let mut total = 0;
+
+    while (results.len() as i32) < wanted {
+        let page_size = PAGE_SIZE.min(wanted - results.len() as i32);
+        let page_query = SearchQuery {
+            limit: Some(page_size),
+            offset: Some(start + results.len() as i32),
+            ..query.clone()
+        };
+
+        let page = match search(page_query).await {
+            Ok(page) => page,
+            Err(e) if !results.is_empty() => {
+                log::warn!("Stopping paginated search after {} results: {}", results.len(), e);
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+
+        total = page.total;
+        let exhausted = (page.results.len() as i32) < page_size
+            || start + results.len() as i32 + page.results.len() as i32 >= page.total;
+        results.extend(page.results);
+        if exhausted {
+            break;
+        }
     }
+
+    Ok(SearchResponse {
+        total: total.max(results.len() as i32),
+        results,
+    })
 }
 
 /// Get paper details by ID
@@ -49,3 +330,323 @@ pub async fn get_paper_recommendations(
 ) -> Result<Vec<SearchResult>, AppError> {
     semantic_scholar::get_recommendations(paper_id, limit).await
 }
+
+/// Get the papers a paper cites, for backward snowballing. `paper_id` is a
+/// Semantic Scholar ID or a prefixed ID such as `DOI:10.1000/xyz`.
+#[tauri::command]
+pub async fn get_paper_references(
+    paper_id: String,
+    limit: Option<i32>,
+    offset: Option<i32>,
+) -> Result<Vec<SearchResult>, AppError> {
+    semantic_scholar::get_linked_papers(&paper_id, "references", limit, offset).await
+}
+
+/// Get the papers that cite a paper, for forward snowballing
+#[tauri::command]
+pub async fn get_paper_citations(
+    paper_id: String,
+    limit: Option<i32>,
+    offset: Option<i32>,
+) -> Result<Vec<SearchResult>, AppError> {
+    semantic_scholar::get_linked_papers(&paper_id, "citations", limit, offset).await
+}
+
+/// Fill a library paper's title, author, year, publisher and abstract from its
+/// Crossref record. Fields Crossref leaves blank keep their current values.
+#[tauri::command]
+pub async fn enrich_paper_from_doi(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_id: String,
+    doi: String,
+) -> Result<Paper, AppError> {
+    let doi = normalize_doi(&doi);
+    if doi.is_empty() {
+        return Err(AppError::Validation("DOI is required".to_string()));
+    }
+
+    let record = crossref::get_by_doi(&doi).await?;
+
+    let authors = record
+        .authors
+        .iter()
+        .map(|a| a.name.trim())
+        .filter(|name| !name.is_empty())
+        .collect::<Vec<_>>()
+        .join("; ");
+    let non_empty = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+
+    let input = UpdatePaperInput {
+        title: non_empty(Some(record.title)).filter(|t| t != "Unknown"),
+        author: non_empty(Some(authors)),
+        year: record.year,
+        publisher: non_empty(record.venue),
+        abstract_text: non_empty(record.abstract_text),
+        doi: Some(doi),
+        ..Default::default()
+    };
+
+    let conn = db.get()?;
+    let paper = crate::db::papers::update_paper(&conn, &paper_id, input)?;
+    let _ = app.emit("papers-changed", &paper.folder_id);
+
+    Ok(paper)
+}
+
+/// Publication years to search within; either end may be open
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct YearFilter {
+    pub start: Option<i32>,
+    pub end: Option<i32>,
+}
+
+impl YearFilter {
+    pub fn contains(&self, year: i32) -> bool {
+        self.start.map_or(true, |start| year >= start) && self.end.map_or(true, |end| year <= end)
+    }
+}
+
+/// Parse a year filter: `2020`, `2018-2022`, `2020-` (2020 onward) or `-2015`
+/// (up to 2015). Months and days are ignored, so `2020/05` and `2020-05-01`
+/// mean 2020. Empty input is an open filter.
+pub(crate) fn parse_year_filter(input: &str) -> Result<YearFilter, AppError> {
+    let invalid = || {
+        AppError::Validation(format!(
+            "Invalid year filter \"{}\": use 2020, 2018-2022, 2020- or -2015",
+            input
+        ))
+    };
+
+    let normalized = input.trim().replace(['–', '—', ':'], "-").replace("..", "-");
+    if normalized.is_empty() {
+        return Ok(YearFilter::default());
+    }
+
+    let parts: Vec<&str> = normalized.split('-').map(str::trim).collect();
+    let open_start = parts.first().is_some_and(|p| p.is_empty());
+    let open_end = parts.len() > 1 && parts.last().is_some_and(|p| p.is_empty());
+
+    let mut years = Vec::new();
+    for part in parts.iter().filter(|p| !p.is_empty()) {
+        let digits = part.chars().take_while(char::is_ascii_digit).count();
+        let rest = &part[digits..];
+        match digits {
+            // A year, possibly followed by `/05` or `.05`
+            4 if rest.is_empty() || rest.starts_with(['/', '.']) => {
+                years.push(part[..4].parse().map_err(|_| invalid())?)
+            }
+            // A month or day belonging to the previous year
+            1 | 2 if rest.is_empty() && !years.is_empty() => {}
+            _ => return Err(invalid()),
+        }
+    }
+
+    let filter = match (years.as_slice(), open_start, open_end) {
+        ([year], false, false) => YearFilter { start: Some(*year), end: Some(*year) },
+        ([year], false, true) => YearFilter { start: Some(*year), end: None },
+        ([year], true, false) => YearFilter { start: None, end: Some(*year) },
+        ([start, end], false, false) if start <= end => YearFilter {
+            start: Some(*start),
+            end: Some(*end),
+        },
+        _ => return Err(invalid()),
+    };
+    Ok(filter)
+}
+
+/// Lowercase surname prefixes that belong to the family name ("van Dijk", "de la Cruz")
+const NAME_PARTICLES: &[&str] = &[
+    "van", "von", "de", "der", "den", "del", "della", "da", "das", "do", "dos", "di", "du", "la",
+    "le", "ter", "ten", "bin", "al", "el",
+];
+
+const NAME_SUFFIXES: &[&str] = &["jr", "jr.", "sr", "sr.", "ii", "iii", "iv"];
+
+/// Put an author name in the canonical "Family, Given" form used for stored authors.
+/// Handles "Given Family", PubMed's "Family GI" and already-inverted names;
+/// names in CJK scripts, which have no given/family separator, are kept as written.
+pub(crate) fn normalize_author_name(name: &str) -> String {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    let name = name.trim_matches(|c: char| c == ',' || c == ';').trim();
+
+    if let Some((family, given)) = name.split_once(',') {
+        let (family, given) = (family.trim(), given.trim());
+        return if given.is_empty() {
+            family.to_string()
+        } else {
+            format!("{}, {}", family, given)
+        };
+    }
+
+    let is_cjk = name.chars().any(|c| {
+        matches!(c as u32, 0x1100..=0x11FF | 0x3040..=0x30FF | 0x3400..=0x9FFF | 0xAC00..=0xD7AF)
+    });
+    let mut parts: Vec<&str> = name.split(' ').collect();
+    if is_cjk || parts.len() < 2 {
+        return name.to_string();
+    }
+
+    let suffix = parts
+        .last()
+        .filter(|p| NAME_SUFFIXES.contains(&p.to_lowercase().as_str()))
+        .copied();
+    if suffix.is_some() {
+        parts.pop();
+    }
+    if parts.len() < 2 {
+        return name.to_string();
+    }
+
+    // PubMed style: "Smith JA" - surname followed by run-together initials
+    let last = parts[parts.len() - 1];
+    let (family, given) = if last.len() <= 3 && last.chars().all(|c| c.is_ascii_uppercase()) {
+        let initials = last.chars().map(|c| format!("{}.", c)).collect::<Vec<_>>().join(" ");
+        (parts[..parts.len() - 1].join(" "), initials)
+    } else {
+        let family_start = parts[..parts.len() - 1]
+            .iter()
+            .position(|p| NAME_PARTICLES.contains(p))
+            .filter(|&i| i > 0)
+            .unwrap_or(parts.len() - 1);
+        (parts[family_start..].join(" "), parts[..family_start].join(" "))
+    };
+
+    match suffix {
+        Some(suffix) => format!("{} {}, {}", family, suffix, given),
+        None => format!("{}, {}", family, given),
+    }
+}
+
+/// Normalize a DOI for comparison (lowercase, without resolver prefix)
+pub(crate) fn normalize_doi(doi: &str) -> String {
+    let doi = doi.trim().to_lowercase();
+    doi.trim_start_matches("https://doi.org/")
+        .trim_start_matches("http://doi.org/")
+        .trim_start_matches("doi:")
+        .to_string()
+}
+
+/// Build the citation graph among a set of library papers.
+/// Each paper's references and citations are fetched from Semantic Scholar
+/// (and cached), and only edges with both endpoints in the given set are returned.
+#[tauri::command]
+pub async fn get_citation_graph(
+    papers: Vec<CitationGraphNode>,
+    db: State<'_, DbConnection>,
+) -> Result<Vec<CitationEdge>, AppError> {
+    let by_doi: HashMap<String, String> = papers
+        .iter()
+        .filter(|p| !p.doi.trim().is_empty())
+        .map(|p| (normalize_doi(&p.doi), p.paper_id.clone()))
+        .collect();
+
+    let mut edges = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (doi, paper_id) in &by_doi {
+        let cached = {
+            let conn = db.get()?;
+            crate::db::citation_cache::get_links(&conn, doi)?
+        };
+
+        let (references, citations) = match cached {
+            Some(links) => links,
+            None => {
+                let fetched = async {
+                    let references = semantic_scholar::get_linked_dois(doi, "references").await?;
+                    let citations = semantic_scholar::get_linked_dois(doi, "citations").await?;
+                    Ok::<_, AppError>((references, citations))
+                }
+                .await;
+
+                match fetched {
+                    Ok((references, citations)) => {
+                        let conn = db.get()?;
+                        crate::db::citation_cache::store_links(&conn, doi, &references, &citations)?;
+                        (references, citations)
+                    }
+                    Err(e) => {
+                        log::warn!("Skipping citation links for DOI {}: {}", doi, e);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        let outgoing = references
+            .iter()
+            .filter_map(|r| by_doi.get(r))
+            .map(|to| (paper_id.clone(), to.clone()));
+        let incoming = citations
+            .iter()
+            .filter_map(|c| by_doi.get(c))
+            .map(|from| (from.clone(), paper_id.clone()));
+
+        for (from_paper_id, to_paper_id) in outgoing.chain(incoming) {
+            if from_paper_id == to_paper_id {
+                continue;
+            }
+            let edge = CitationEdge {
+                from_paper_id,
+                to_paper_id,
+            };
+            if seen.insert(edge.clone()) {
+                edges.push(edge);
+            }
+        }
+    }
+
+    Ok(edges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_author_name() {
+        assert_eq!(normalize_author_name("John Smith"), "Smith, John");
+        assert_eq!(normalize_author_name("Mary Jane  Watson"), "Watson, Mary Jane");
+        assert_eq!(normalize_author_name("Smith JA"), "Smith, J. A.");
+        assert_eq!(normalize_author_name("Smith,John"), "Smith, John");
+        assert_eq!(normalize_author_name("Ludwig van Beethoven"), "van Beethoven, Ludwig");
+        assert_eq!(normalize_author_name("Martin Luther King Jr."), "King Jr., Martin Luther");
+        assert_eq!(normalize_author_name("홍길동"), "홍길동");
+        assert_eq!(normalize_author_name("Aristotle"), "Aristotle");
+    }
+
+    #[tokio::test]
+    async fn test_empty_query_rejected_for_every_source() {
+        for source in SearchSource::ALL {
+            let query: SearchQuery = serde_json::from_value(serde_json::json!({ "query": " \t" }))
+                .unwrap();
+            let result = search_source(source, query, None).await;
+            assert!(
+                matches!(&result, Err(AppError::Validation(m)) if m == "query is empty"),
+                "{:?} accepted an empty query",
+                source
+            );
+        }
+
+        let by_author: SearchQuery =
+            serde_json::from_value(serde_json::json!({ "query": "", "author": "Smith" })).unwrap();
+        assert!(validate_query(SearchSource::Crossref, &by_author).is_ok());
+        assert!(validate_query(SearchSource::Arxiv, &by_author).is_err());
+    }
+
+    #[test]
+    fn test_parse_year_filter() {
+        let range = |start, end| YearFilter { start, end };
+        assert_eq!(parse_year_filter("2020").unwrap(), range(Some(2020), Some(2020)));
+        assert_eq!(parse_year_filter(" 2018-2022 ").unwrap(), range(Some(2018), Some(2022)));
+        assert_eq!(parse_year_filter("2020-").unwrap(), range(Some(2020), None));
+        assert_eq!(parse_year_filter("-2015").unwrap(), range(None, Some(2015)));
+        assert_eq!(parse_year_filter("2020/05").unwrap(), range(Some(2020), Some(2020)));
+        assert_eq!(parse_year_filter("2020-05-01").unwrap(), range(Some(2020), Some(2020)));
+        assert_eq!(parse_year_filter("").unwrap(), YearFilter::default());
+        assert!(parse_year_filter("2022-2018").is_err());
+        assert!(parse_year_filter("20").is_err());
+        assert!(parse_year_filter("recent").is_err());
+    }
+}
@@ -16,21 +16,20 @@ struct ESearchResult {
 }
 
 pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
-    let client = reqwest::Client::new();
+    let client = crate::commands::http::client()?;
     let limit = query.limit.unwrap_or(10).min(100);
     let offset = query.offset.unwrap_or(0);
 
     let mut search_term = query.query.clone();
 
-    if let Some(year) = &query.year {
-        if year.contains('-') {
-            let parts: Vec<&str> = year.split('-').collect();
-            if parts.len() == 2 {
-                search_term.push_str(&format!(" AND {}:{}[dp]", parts[0], parts[1]));
-            }
-        } else {
-            search_term.push_str(&format!(" AND {}[dp]", year));
-        }
+    // PubMed ranges need both ends; 3000 is its conventional "present"
+    let years = super::parse_year_filter(query.year.as_deref().unwrap_or_default())?;
+    if years.start.is_some() || years.end.is_some() {
+        search_term.push_str(&format!(
+            " AND {}:{}[dp]",
+            years.start.unwrap_or(1000),
+            years.end.unwrap_or(3000)
+        ));
     }
 
     let search_url = format!(
@@ -43,11 +42,13 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
 
     let search_response = client
         .get(&search_url)
-        .header("User-Agent", "PaperManager/1.0")
+        .header("User-Agent", crate::commands::http::user_agent())
         .send()
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
+    crate::commands::http::check_rate_limit(&search_response, "PubMed")?;
+
     if !search_response.status().is_success() {
         return Err(AppError::Network("PubMed search failed".to_string()));
     }
@@ -74,11 +75,13 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
 
     let summary_response = client
         .get(&summary_url)
-        .header("User-Agent", "PaperManager/1.0")
+        .header("User-Agent", crate::commands::http::user_agent())
         .send()
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
+    crate::commands::http::check_rate_limit(&summary_response, "PubMed")?;
+
     if !summary_response.status().is_success() {
         return Err(AppError::Network("PubMed summary fetch failed".to_string()));
     }
@@ -108,7 +111,7 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
                             .filter_map(|a| {
                                 a.get("name").and_then(|n| n.as_str()).map(|name| Author {
                                     author_id: None,
-                                    name: name.to_string(),
+                                    name: super::normalize_author_name(name),
                                 })
                             })
                             .collect()
@@ -146,6 +149,7 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
                         pubmed: Some(pmid.clone()),
                         pubmed_central: None,
                     }),
+                    tldr: None,
                 });
             }
         }
@@ -5,7 +5,16 @@ use std::env;
 
 const API_URL: &str = "https://api.semanticscholar.org/graph/v1";
 
-fn get_api_key() -> Option<String> {
+/// Fields requested for every paper record
+const PAPER_FIELDS: &str =
+    "paperId,title,authors,year,abstract,venue,publicationVenue,citationCount,url,openAccessPdf,externalIds";
+
+/// `PAPER_FIELDS` plus `tldr`, which the recommendation and citation
+/// endpoints don't serve
+const DETAIL_FIELDS: &str =
+    "paperId,title,authors,year,abstract,venue,publicationVenue,citationCount,url,openAccessPdf,externalIds,tldr";
+
+pub(super) fn get_api_key() -> Option<String> {
     env::var("SEMANTIC_SCHOLAR_API_KEY").ok()
 }
 
@@ -20,8 +29,9 @@ struct Response {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Paper {
-    pub paper_id: String,
-    pub title: String,
+    // Both occasionally come back null; defaults are filled in by `convert_paper`
+    pub paper_id: Option<String>,
+    pub title: Option<String>,
     pub authors: Option<Vec<PaperAuthor>>,
     pub year: Option<i32>,
     #[serde(rename = "abstract")]
@@ -31,6 +41,18 @@ pub(crate) struct Paper {
     pub url: Option<String>,
     pub open_access_pdf: Option<OpenAccessPdf>,
     pub external_ids: Option<ExternalIds>,
+    pub publication_venue: Option<PublicationVenue>,
+    pub tldr: Option<Tldr>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct PublicationVenue {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct Tldr {
+    pub text: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,31 +64,39 @@ pub(crate) struct PaperAuthor {
 
 pub(crate) fn convert_paper(paper: Paper) -> SearchResult {
     SearchResult {
-        paper_id: paper.paper_id,
-        title: paper.title,
+        paper_id: paper.paper_id.unwrap_or_default(),
+        title: paper
+            .title
+            .filter(|t| !t.trim().is_empty())
+            .unwrap_or_else(|| "Unknown".to_string()),
         authors: paper
             .authors
             .unwrap_or_default()
             .into_iter()
             .map(|a| Author {
                 author_id: a.author_id,
-                name: a.name.unwrap_or_default(),
+                name: super::normalize_author_name(&a.name.unwrap_or_default()),
             })
             .collect(),
         year: paper.year,
         abstract_text: paper.abstract_text,
-        venue: paper.venue,
+        // `venue` is often blank when the structured venue is known
+        venue: paper
+            .venue
+            .filter(|v| !v.trim().is_empty())
+            .or_else(|| paper.publication_venue.and_then(|v| v.name)),
         citation_count: paper.citation_count,
         url: paper.url,
         open_access_pdf: paper.open_access_pdf,
         external_ids: paper.external_ids,
+        tldr: paper.tldr.and_then(|t| t.text).filter(|t| !t.trim().is_empty()),
     }
 }
 
 pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
-    let client = reqwest::Client::new();
+    let client = crate::commands::http::client()?;
 
-    let fields = "paperId,title,authors,year,abstract,venue,citationCount,url,openAccessPdf,externalIds";
+    let fields = DETAIL_FIELDS;
     let limit = query.limit.unwrap_or(10).min(100);
     let offset = query.offset.unwrap_or(0);
 
@@ -79,8 +109,15 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
         offset
     );
 
-    if let Some(year) = &query.year {
-        url.push_str(&format!("&year={}", year));
+    let years = super::parse_year_filter(query.year.as_deref().unwrap_or_default())?;
+    match (years.start, years.end) {
+        (Some(start), Some(end)) if start == end => url.push_str(&format!("&year={}", start)),
+        (None, None) => {}
+        (start, end) => url.push_str(&format!(
+            "&year={}-{}",
+            start.map(|y| y.to_string()).unwrap_or_default(),
+            end.map(|y| y.to_string()).unwrap_or_default()
+        )),
     }
 
     if let Some(fields_of_study) = &query.fields_of_study {
@@ -91,7 +128,7 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
 
     let mut request = client
         .get(&url)
-        .header("User-Agent", "PaperManager/1.0");
+        .header("User-Agent", crate::commands::http::user_agent());
 
     if let Some(api_key) = get_api_key() {
         request = request.header("x-api-key", api_key);
@@ -102,6 +139,8 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
+    crate::commands::http::check_rate_limit(&response, "Semantic Scholar")?;
+
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
@@ -122,14 +161,14 @@ pub async fn search(query: SearchQuery) -> Result<SearchResponse, AppError> {
 }
 
 pub async fn get_details(paper_id: String) -> Result<SearchResult, AppError> {
-    let client = reqwest::Client::new();
+    let client = crate::commands::http::client()?;
 
-    let fields = "paperId,title,authors,year,abstract,venue,citationCount,url,openAccessPdf,externalIds";
+    let fields = DETAIL_FIELDS;
     let url = format!("{}/paper/{}?fields={}", API_URL, paper_id, fields);
 
     let mut request = client
         .get(&url)
-        .header("User-Agent", "PaperManager/1.0");
+        .header("User-Agent", crate::commands::http::user_agent());
 
     if let Some(api_key) = get_api_key() {
         request = request.header("x-api-key", api_key);
@@ -140,6 +179,8 @@ pub async fn get_details(paper_id: String) -> Result<SearchResult, AppError> {
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
+    crate::commands::http::check_rate_limit(&response, "Semantic Scholar")?;
+
     if !response.status().is_success() {
         let status = response.status();
         let error_text = response.text().await.unwrap_or_default();
@@ -155,9 +196,9 @@ pub async fn get_details(paper_id: String) -> Result<SearchResult, AppError> {
 }
 
 pub async fn get_recommendations(paper_id: String, limit: Option<i32>) -> Result<Vec<SearchResult>, AppError> {
-    let client = reqwest::Client::new();
+    let client = crate::commands::http::client()?;
 
-    let fields = "paperId,title,authors,year,abstract,venue,citationCount,url,openAccessPdf,externalIds";
+    let fields = PAPER_FIELDS;
     let limit = limit.unwrap_or(5).min(20);
     let url = format!(
         "https://api.semanticscholar.org/recommendations/v1/papers/forpaper/{}?fields={}&limit={}",
@@ -166,7 +207,7 @@ pub async fn get_recommendations(paper_id: String, limit: Option<i32>) -> Result
 
     let mut request = client
         .get(&url)
-        .header("User-Agent", "PaperManager/1.0");
+        .header("User-Agent", crate::commands::http::user_agent());
 
     if let Some(api_key) = get_api_key() {
         request = request.header("x-api-key", api_key);
@@ -177,6 +218,8 @@ pub async fn get_recommendations(paper_id: String, limit: Option<i32>) -> Result
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
 
+    crate::commands::http::check_rate_limit(&response, "Semantic Scholar")?;
+
     if !response.status().is_success() {
         return Ok(vec![]);
     }
@@ -194,3 +237,160 @@ pub async fn get_recommendations(paper_id: String, limit: Option<i32>) -> Result
 
     Ok(api_response.recommended_papers.into_iter().map(convert_paper).collect())
 }
+
+/// Get full records of the papers a paper cites (`direction` "references") or
+/// that cite it ("citations"), one page at a time. Entries Semantic Scholar
+/// can't identify (no paper ID) are skipped.
+pub async fn get_linked_papers(
+    paper_id: &str,
+    direction: &str,
+    limit: Option<i32>,
+    offset: Option<i32>,
+) -> Result<Vec<SearchResult>, AppError> {
+    let client = crate::commands::http::client()?;
+
+    let fields = PAPER_FIELDS;
+    let limit = limit.unwrap_or(100).clamp(1, 1000);
+    let offset = offset.unwrap_or(0).max(0);
+    let url = format!(
+        "{}/paper/{}/{}?fields={}&limit={}&offset={}",
+        API_URL, paper_id, direction, fields, limit, offset
+    );
+
+    let mut request = client
+        .get(&url)
+        .header("User-Agent", crate::commands::http::user_agent());
+
+    if let Some(api_key) = get_api_key() {
+        request = request.header("x-api-key", api_key);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::Network(e.to_string()))?;
+
+    crate::commands::http::check_rate_limit(&response, "Semantic Scholar")?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text().await.unwrap_or_default();
+        return Err(AppError::Network(format!(
+            "Failed to fetch {} ({}): {}",
+            direction, status, error_text
+        )));
+    }
+
+    #[derive(Deserialize)]
+    struct LinkedResponse {
+        data: Option<Vec<serde_json::Value>>,
+    }
+
+    let api_response: LinkedResponse = response
+        .json()
+        .await
+        .map_err(|e| AppError::Parse(e.to_string()))?;
+
+    let key = if direction == "references" { "citedPaper" } else { "citingPaper" };
+    Ok(api_response
+        .data
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|mut entry| serde_json::from_value::<Paper>(entry.get_mut(key)?.take()).ok())
+        .filter(|paper| paper.paper_id.is_some())
+        .map(convert_paper)
+        .collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct LinkedPapersResponse {
+    data: Option<Vec<LinkedPaperEntry>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LinkedPaperEntry {
+    cited_paper: Option<LinkedPaper>,
+    citing_paper: Option<LinkedPaper>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LinkedPaper {
+    external_ids: Option<LinkedExternalIds>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LinkedExternalIds {
+    #[serde(rename = "DOI")]
+    doi: Option<String>,
+}
+
+/// Get the DOIs of papers linked to a DOI.
+/// `direction` is either "references" (papers it cites) or "citations" (papers citing it).
+pub async fn get_linked_dois(doi: &str, direction: &str) -> Result<Vec<String>, AppError> {
+    let client = crate::commands::http::client()?;
+
+    let url = format!(
+        "{}/paper/DOI:{}/{}?fields=externalIds&limit=1000",
+        API_URL, doi, direction
+    );
+
+    let mut request = client
+        .get(&url)
+        .header("User-Agent", crate::commands::http::user_agent());
+
+    if let Some(api_key) = get_api_key() {
+        request = request.header("x-api-key", api_key);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::Network(e.to_string()))?;
+
+    crate::commands::http::check_rate_limit(&response, "Semantic Scholar")?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        return Err(AppError::Network(format!(
+            "Failed to fetch {} for DOI {} ({})",
+            direction, doi, status
+        )));
+    }
+
+    let api_response: LinkedPapersResponse = response
+        .json()
+        .await
+        .map_err(|e| AppError::Parse(e.to_string()))?;
+
+    Ok(api_response
+        .data
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|entry| entry.cited_paper.or(entry.citing_paper))
+        .filter_map(|paper| paper.external_ids.and_then(|ids| ids.doi))
+        .map(|doi| doi.to_lowercase())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_null_title_entry() {
+        let json = r#"{"total": 2, "offset": 0, "data": [
+            {"paperId": "abc", "title": null, "authors": [], "year": 2020},
+            {"paperId": "def", "title": "A Study on Machine Learning", "authors": [{"authorId": "1", "name": "John Smith"}], "year": 2023}
+        ]}"#;
+        let response: Response = serde_json::from_str(json).unwrap();
+        let results: Vec<SearchResult> = response.data.into_iter().map(convert_paper).collect();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "Unknown");
+        assert_eq!(results[1].paper_id, "def");
+        assert_eq!(results[1].title, "A Study on Machine Learning");
+        assert_eq!(results[1].authors[0].name, "Smith, John");
+    }
+}
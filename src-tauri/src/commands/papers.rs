@@ -1,17 +1,50 @@
+use std::collections::{HashMap, HashSet};
 use tauri::{AppHandle, Emitter, State};
 
+use crate::commands::undo::{record_operation, UndoableOperation};
 use crate::db::DbConnection;
 use crate::error::AppError;
-use crate::models::{CreatePaperInput, Paper, UpdatePaperInput};
+use crate::models::{CreatePaperInput, Paper, PaperFilter, UpdatePaperInput};
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn get_papers(
     db: State<'_, DbConnection>,
     folder_id: Option<String>,
     sort_by: Option<String>,
+    is_read: Option<bool>,
+    min_importance: Option<i32>,
+    tag: Option<String>,
+    limit: Option<i32>,
+    offset: Option<i32>,
 ) -> Result<Vec<Paper>, AppError> {
     let conn = db.get()?;
-    crate::db::papers::get_papers(&conn, folder_id, sort_by)
+    let filter = PaperFilter {
+        folder_id,
+        is_read,
+        min_importance,
+        tag,
+    };
+    crate::db::papers::get_papers(&conn, &filter, sort_by, limit, offset)
+}
+
+/// Count papers matching the same filters as `get_papers`, for pagination
+#[tauri::command]
+pub fn count_papers(
+    db: State<'_, DbConnection>,
+    folder_id: Option<String>,
+    is_read: Option<bool>,
+    min_importance: Option<i32>,
+    tag: Option<String>,
+) -> Result<i32, AppError> {
+    let conn = db.get()?;
+    let filter = PaperFilter {
+        folder_id,
+        is_read,
+        min_importance,
+        tag,
+    };
+    crate::db::papers::count_papers(&conn, &filter)
 }
 
 #[tauri::command]
@@ -27,7 +60,8 @@ pub fn create_paper(
     input: CreatePaperInput,
 ) -> Result<Paper, AppError> {
     let conn = db.get()?;
-    let paper = crate::db::papers::create_paper(&conn, input)?;
+    let mut paper = crate::db::papers::create_paper(&conn, input)?;
+    crate::commands::citations::assign_initial_citation_key(&conn, &mut paper)?;
     let _ = app.emit("papers-changed", &paper.folder_id);
     Ok(paper)
 }
@@ -45,19 +79,86 @@ pub fn update_paper(
     Ok(paper)
 }
 
+/// Move a paper to the trash, or remove it for good when `permanent` is set
 #[tauri::command]
 pub fn delete_paper(
     app: AppHandle,
     db: State<'_, DbConnection>,
     paper_id: String,
+    permanent: Option<bool>,
 ) -> Result<(), AppError> {
     let conn = db.get()?;
     let paper = crate::db::papers::get_paper(&conn, &paper_id)?;
-    crate::db::papers::delete_paper(&conn, &paper_id)?;
+    if permanent.unwrap_or(false) {
+        crate::db::papers::delete_paper(&conn, &paper_id)?;
+        crate::commands::pdf::release_pdf(&app, &conn, &paper.pdf_path)?;
+    } else {
+        crate::db::papers::trash_paper(&conn, &paper_id)?;
+    }
     let _ = app.emit("papers-changed", &paper.folder_id);
     Ok(())
 }
 
+#[tauri::command]
+pub fn restore_paper(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_id: String,
+) -> Result<Paper, AppError> {
+    let conn = db.get()?;
+    let paper = crate::db::papers::restore_paper(&conn, &paper_id)?;
+    let _ = app.emit("papers-changed", &paper.folder_id);
+    Ok(paper)
+}
+
+#[tauri::command]
+pub fn get_trashed_papers(db: State<'_, DbConnection>) -> Result<Vec<Paper>, AppError> {
+    let conn = db.get()?;
+    crate::db::papers::get_trashed_papers(&conn)
+}
+
+/// Permanently delete every trashed paper and any PDFs no longer referenced.
+/// Returns the number of papers removed.
+#[tauri::command]
+pub fn empty_trash(app: AppHandle, db: State<'_, DbConnection>) -> Result<usize, AppError> {
+    let conn = db.get()?;
+    let trashed = crate::db::papers::get_trashed_papers(&conn)?;
+
+    let mut affected_folders = std::collections::HashSet::new();
+
+    for paper in &trashed {
+        crate::db::papers::delete_paper(&conn, &paper.id)?;
+        crate::commands::pdf::release_pdf(&app, &conn, &paper.pdf_path)?;
+        affected_folders.insert(paper.folder_id.clone());
+    }
+
+    for folder_id in affected_folders {
+        let _ = app.emit("papers-changed", &folder_id);
+    }
+    Ok(trashed.len())
+}
+
+/// Record that a paper was opened in the reader
+#[tauri::command]
+pub fn touch_paper_opened(
+    db: State<'_, DbConnection>,
+    paper_id: String,
+) -> Result<Paper, AppError> {
+    let conn = db.get()?;
+    crate::db::papers::update_paper_last_opened(&conn, &paper_id)?;
+    crate::db::papers::get_paper(&conn, &paper_id)
+}
+
+/// Get the most recently opened papers
+#[tauri::command]
+pub fn get_recently_opened(
+    db: State<'_, DbConnection>,
+    limit: Option<i32>,
+) -> Result<Vec<Paper>, AppError> {
+    let conn = db.get()?;
+    crate::db::papers::get_recently_opened(&conn, limit.unwrap_or(20))
+}
+
 #[tauri::command]
 pub fn check_duplicate(db: State<'_, DbConnection>, title: String) -> Result<bool, AppError> {
     let conn = db.get()?;
@@ -90,7 +191,7 @@ pub fn batch_update_papers(
     Ok(updated_papers)
 }
 
-/// Batch delete multiple papers
+/// Move multiple papers to the trash
 #[tauri::command]
 pub fn batch_delete_papers(
     app: AppHandle,
@@ -103,8 +204,9 @@ pub fn batch_delete_papers(
     for paper_id in &paper_ids {
         let paper = crate::db::papers::get_paper(&conn, paper_id)?;
         affected_folders.insert(paper.folder_id.clone());
-        crate::db::papers::delete_paper(&conn, paper_id)?;
+        crate::db::papers::trash_paper(&conn, paper_id)?;
     }
+    record_operation(&conn, &UndoableOperation::BatchDelete { paper_ids })?;
 
     // Emit change events for all affected folders
     for folder_id in affected_folders {
@@ -113,3 +215,304 @@ pub fn batch_delete_papers(
 
     Ok(())
 }
+
+/// Mark several papers read or unread at once. Returns the number updated.
+#[tauri::command]
+pub fn batch_set_read(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_ids: Vec<String>,
+    is_read: bool,
+) -> Result<usize, AppError> {
+    let conn = db.get()?;
+    let affected_folders = crate::db::papers::set_read_status(&conn, &paper_ids, is_read)?;
+    for folder_id in affected_folders {
+        let _ = app.emit("papers-changed", &folder_id);
+    }
+    Ok(paper_ids.len())
+}
+
+/// Set the importance of several papers at once. Returns the number updated.
+#[tauri::command]
+pub fn batch_set_importance(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_ids: Vec<String>,
+    importance: i32,
+) -> Result<usize, AppError> {
+    let conn = db.get()?;
+    let affected_folders = crate::db::papers::set_importance(&conn, &paper_ids, importance)?;
+    for folder_id in affected_folders {
+        let _ = app.emit("papers-changed", &folder_id);
+    }
+    Ok(paper_ids.len())
+}
+
+/// Lowercase a name part and drop the periods and extra spaces that vary between formats
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .replace('.', " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether an author field names the queried person. The last name matches by
+/// substring; given names match by prefix, so `J. Smith` finds `Smith, John`.
+fn author_matches(author_field: &str, last: &str, first: &str) -> bool {
+    let last = normalize_name(last);
+    let first = normalize_name(first);
+    let first_parts: Vec<&str> = first.split_whitespace().collect();
+
+    let named = crate::commands::citations::parse_authors(author_field)
+        .into_iter()
+        .any(|(author_last, author_first)| {
+            let author_first = normalize_name(&author_first);
+            let author_parts: Vec<&str> = author_first.split_whitespace().collect();
+            normalize_name(&author_last).contains(&last)
+                && first_parts.len() <= author_parts.len()
+                && first_parts
+                    .iter()
+                    .zip(&author_parts)
+                    .all(|(q, a)| a.starts_with(q))
+        });
+
+    // Fall back to a plain match for single names and formats the parser splits oddly
+    let full_name = format!("{} {}", first, last);
+    named || normalize_name(author_field).contains(full_name.trim())
+}
+
+/// Find library papers by an author, in any of the stored name formats
+/// ("Smith, John", "John Smith", "J. Smith"), ordered by year
+#[tauri::command]
+pub fn get_papers_by_author(
+    db: State<'_, DbConnection>,
+    name: String,
+) -> Result<Vec<Paper>, AppError> {
+    let parsed = crate::commands::citations::parse_authors(&name)
+        .into_iter()
+        .next();
+    let Some((last, first)) = parsed else {
+        return Err(AppError::Validation("Author name is required".to_string()));
+    };
+
+    let conn = db.get()?;
+    let term = last.trim_end_matches('.');
+    let candidates = crate::db::papers::get_papers_by_author_text(&conn, term)?;
+    Ok(candidates
+        .into_iter()
+        .filter(|paper| author_matches(&paper.author, &last, &first))
+        .collect())
+}
+
+/// Paper with its relatedness score, used while ranking
+struct ScoredPaper {
+    paper: Paper,
+    score: f64,
+}
+
+fn keyword_set(keywords: &str) -> HashSet<String> {
+    keywords
+        .split([',', ';'])
+        .map(|k| k.trim().to_lowercase())
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+fn author_last_names(author: &str) -> HashSet<String> {
+    crate::commands::citations::parse_authors(author)
+        .into_iter()
+        .map(|(last, _)| last.to_lowercase())
+        .filter(|last| !last.is_empty())
+        .collect()
+}
+
+/// Find papers in the local library related to the given paper.
+/// Scores by shared keywords, overlapping tags, shared authors and
+/// full-text similarity of indexed PDF content to the paper's subject.
+#[tauri::command]
+pub fn find_related_local(
+    db: State<'_, DbConnection>,
+    paper_id: String,
+    limit: Option<i32>,
+) -> Result<Vec<Paper>, AppError> {
+    const KEYWORD_WEIGHT: f64 = 2.0;
+    const TAG_WEIGHT: f64 = 1.5;
+    const AUTHOR_WEIGHT: f64 = 3.0;
+    const TEXT_WEIGHT: f64 = 2.0;
+
+    let conn = db.get()?;
+    let target = crate::db::papers::get_paper(&conn, &paper_id)?;
+    let limit = limit.unwrap_or(10).max(0) as usize;
+
+    let target_keywords = keyword_set(&target.keywords);
+    let target_tags: HashSet<String> = target.tags.iter().map(|t| t.to_lowercase()).collect();
+    let target_authors = author_last_names(&target.author);
+
+    // Search the full text for the subject's most frequent words, longer words
+    // first among equals, so the same paper always gets the same terms
+    let mut word_counts: HashMap<String, usize> = HashMap::new();
+    for word in target.subject.split(|c: char| !c.is_alphanumeric()) {
+        if word.chars().count() > 3 {
+            *word_counts.entry(word.to_lowercase()).or_default() += 1;
+        }
+    }
+    let mut ranked: Vec<(String, usize)> = word_counts.into_iter().collect();
+    ranked.sort_by(|(a, a_count), (b, b_count)| {
+        b_count
+            .cmp(a_count)
+            .then_with(|| b.chars().count().cmp(&a.chars().count()))
+            .then_with(|| a.cmp(b))
+    });
+    let terms: Vec<String> = ranked.into_iter().take(16).map(|(word, _)| word).collect();
+    let text_ranks = crate::db::pdf_content::match_papers_by_terms(&conn, &terms)?;
+
+    // bm25 ranks are negative; the most negative is the best match
+    let best_rank = text_ranks
+        .iter()
+        .filter(|(id, _)| **id != target.id)
+        .map(|(_, rank)| *rank)
+        .fold(0.0_f64, f64::min);
+
+    let candidates = crate::db::papers::get_papers(&conn, &Default::default(), None, None, None)?;
+
+    let mut scored: Vec<ScoredPaper> = candidates
+        .into_iter()
+        .filter(|p| p.id != target.id)
+        .map(|paper| {
+            let shared_keywords = keyword_set(&paper.keywords)
+                .intersection(&target_keywords)
+                .count();
+            let shared_tags = paper
+                .tags
+                .iter()
+                .filter(|t| target_tags.contains(&t.to_lowercase()))
+                .count();
+            let shared_authors = author_last_names(&paper.author)
+                .intersection(&target_authors)
+                .count();
+            let text_score = match text_ranks.get(&paper.id) {
+                Some(rank) if best_rank < 0.0 => rank / best_rank,
+                _ => 0.0,
+            };
+
+            let score = shared_keywords as f64 * KEYWORD_WEIGHT
+                + shared_tags as f64 * TAG_WEIGHT
+                + if shared_authors > 0 {
+                    AUTHOR_WEIGHT
+                } else {
+                    0.0
+                }
+                + text_score * TEXT_WEIGHT;
+
+            ScoredPaper { paper, score }
+        })
+        .filter(|s| s.score > 0.0)
+        .collect();
+
+    scored.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(scored.into_iter().take(limit).map(|s| s.paper).collect())
+}
+
+/// What spellings of one tag have in common: "Machine-Learning" and
+/// "machine learning" share a key
+fn tag_key(tag: &str) -> String {
+    tag.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Suggest tags for a paper, best first: tags used on papers in the same
+/// folder or sharing its keywords, and the paper's own keywords. Suggestions
+/// use the library's most common spelling of a tag, and tags the paper
+/// already has are left out.
+#[tauri::command]
+pub fn suggest_tags(
+    db: State<'_, DbConnection>,
+    paper_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<String>, AppError> {
+    const FOLDER_WEIGHT: f64 = 1.0;
+    const SHARED_KEYWORD_WEIGHT: f64 = 2.0;
+    const OWN_KEYWORD_WEIGHT: f64 = 3.0;
+    // A keyword that is already a tag elsewhere keeps the library consistent
+    const EXISTING_TAG_BONUS: f64 = 2.0;
+
+    let conn = db.get()?;
+    let target = crate::db::papers::get_paper(&conn, &paper_id)?;
+    let limit = limit.unwrap_or(10);
+
+    let target_keywords = keyword_set(&target.keywords);
+    let own_tags: HashSet<String> = target.tags.iter().map(|t| tag_key(t)).collect();
+    let papers = crate::db::papers::get_papers(&conn, &Default::default(), None, None, None)?;
+
+    let mut spellings: HashMap<String, HashMap<&str, usize>> = HashMap::new();
+    let mut scores: HashMap<String, f64> = HashMap::new();
+    for paper in papers.iter().filter(|p| p.id != target.id) {
+        let shared_keywords = keyword_set(&paper.keywords)
+            .intersection(&target_keywords)
+            .count();
+        let weight = shared_keywords as f64 * SHARED_KEYWORD_WEIGHT
+            + if paper.folder_id == target.folder_id {
+                FOLDER_WEIGHT
+            } else {
+                0.0
+            };
+
+        for tag in &paper.tags {
+            let key = tag_key(tag);
+            if key.is_empty() {
+                continue;
+            }
+            *spellings
+                .entry(key.clone())
+                .or_default()
+                .entry(tag.trim())
+                .or_default() += 1;
+            if weight > 0.0 {
+                *scores.entry(key).or_default() += weight;
+            }
+        }
+    }
+
+    let mut keyword_spellings: HashMap<String, &str> = HashMap::new();
+    for keyword in target.keywords.split([',', ';']).map(str::trim) {
+        let key = tag_key(keyword);
+        if key.is_empty() || keyword_spellings.contains_key(&key) {
+            continue;
+        }
+        let bonus = if spellings.contains_key(&key) {
+            EXISTING_TAG_BONUS
+        } else {
+            0.0
+        };
+        *scores.entry(key.clone()).or_default() += OWN_KEYWORD_WEIGHT + bonus;
+        keyword_spellings.insert(key, keyword);
+    }
+
+    let mut ranked: Vec<(String, f64)> = scores
+        .into_iter()
+        .filter(|(key, _)| !own_tags.contains(key))
+        .filter_map(|(key, score)| {
+            // Most used spelling, alphabetically first on a tie
+            let library_spelling = spellings.get(&key).and_then(|counts| {
+                counts
+                    .iter()
+                    .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                    .map(|(spelling, _)| spelling.to_string())
+            });
+            let tag =
+                library_spelling.or_else(|| keyword_spellings.get(&key).map(|k| k.to_string()))?;
+            Some((tag, score))
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    Ok(ranked.into_iter().take(limit).map(|(tag, _)| tag).collect())
+}
@@ -1,15 +1,68 @@
 use base64::{engine::general_purpose::STANDARD, Engine as _};
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
 
+use crate::db::DbConnection;
 use crate::error::AppError;
+use crate::models::{
+    ImportDirectoryProgress, ImportDirectorySummary, MissingPdf, PdfInfo, RelinkResult,
+};
 
-fn get_pdf_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
-    let app_data = app
-        .path()
+/// PDFs larger than this should be streamed with `get_pdf_byte_range`
+/// instead of loaded in one piece through `get_pdf_as_base64`
+pub const STREAMING_THRESHOLD_BYTES: u64 = 10 * 1024 * 1024;
+
+// Upper bound for a single streamed chunk
+const MAX_CHUNK_BYTES: u64 = 8 * 1024 * 1024;
+
+const THUMBNAIL_WIDTH: i32 = 300;
+const THUMBNAIL_MAX_HEIGHT: i32 = 420;
+
+fn get_app_data_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    app.path()
         .app_data_dir()
-        .map_err(|e| AppError::Io(e.to_string()))?;
-    let pdf_dir = app_data.join("pdfs");
+        .map_err(|e| AppError::Io(e.to_string()))
+}
+
+/// Resolve a stored `pdf_path` to an absolute path.
+/// PDFs inside the app data dir are stored relative to it so backups stay portable.
+pub(crate) fn resolve_pdf_path(app: &AppHandle, stored: &str) -> Result<PathBuf, AppError> {
+    let path = PathBuf::from(stored);
+    if path.is_absolute() {
+        return Ok(path);
+    }
+    Ok(get_app_data_dir(app)?.join(path))
+}
+
+fn stored_pdf_path(app_data: &Path, path: &Path) -> String {
+    match path.strip_prefix(app_data) {
+        Ok(relative) => relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => path.to_string_lossy().to_string(),
+    }
+}
+
+/// Convert an absolute PDF path to the form stored in the database
+pub(crate) fn to_stored_pdf_path(app: &AppHandle, path: &Path) -> Result<String, AppError> {
+    Ok(stored_pdf_path(&get_app_data_dir(app)?, path))
+}
+
+/// Rewrite absolute PDF paths inside the app data dir to relative ones
+pub fn relativize_pdf_paths(conn: &rusqlite::Connection, app_data: &Path) -> Result<(), AppError> {
+    for (paper_id, _title, _folder_id, pdf_path) in crate::db::papers::get_papers_with_pdf(conn)? {
+        let path = Path::new(&pdf_path);
+        if path.is_absolute() && path.starts_with(app_data) {
+            crate::db::papers::update_pdf_location(conn, &paper_id, &stored_pdf_path(app_data, path))?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn get_pdf_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let pdf_dir = get_app_data_dir(app)?.join("pdfs");
 
     if !pdf_dir.exists() {
         std::fs::create_dir_all(&pdf_dir)?;
@@ -18,9 +71,377 @@ fn get_pdf_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
     Ok(pdf_dir)
 }
 
+fn get_thumbnail_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let thumbnail_dir = get_app_data_dir(app)?.join("thumbnails");
+
+    if !thumbnail_dir.exists() {
+        std::fs::create_dir_all(&thumbnail_dir)?;
+    }
+
+    Ok(thumbnail_dir)
+}
+
+/// Copy a PDF into the pdfs dir and return its stored path.
+/// If a file with identical content was imported before, that file is reused instead.
+pub(crate) fn store_pdf(
+    app: &AppHandle,
+    conn: &rusqlite::Connection,
+    source: &Path,
+    dest_filename: &str,
+) -> Result<String, AppError> {
+    store_pdf_bytes(app, conn, &std::fs::read(source)?, dest_filename)
+}
+
+/// Write PDF content into the pdfs dir and return its stored path, reusing an
+/// existing file with identical content as `store_pdf` does
+pub(crate) fn store_pdf_bytes(
+    app: &AppHandle,
+    conn: &rusqlite::Connection,
+    bytes: &[u8],
+    dest_filename: &str,
+) -> Result<String, AppError> {
+    let hash = pdf_hash(bytes);
+
+    if let Some(existing) = find_existing_copy(app, conn, &hash)? {
+        return Ok(existing);
+    }
+
+    let dest_path = get_pdf_dir(app)?.join(dest_filename);
+    std::fs::write(&dest_path, bytes)?;
+
+    let stored = to_stored_pdf_path(app, &dest_path)?;
+    crate::db::pdf_files::register(conn, &hash, &stored)?;
+    Ok(stored)
+}
+
+fn pdf_hash(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Stored path of a previously imported file with content hash `hash`, if it is still on disk
+fn find_existing_copy(
+    app: &AppHandle,
+    conn: &rusqlite::Connection,
+    hash: &str,
+) -> Result<Option<String>, AppError> {
+    match crate::db::pdf_files::find_by_hash(conn, hash)? {
+        Some(existing) if resolve_pdf_path(app, &existing)?.exists() => Ok(Some(existing)),
+        _ => Ok(None),
+    }
+}
+
+/// Stored path of a previously imported PDF with the same content as `source`
+pub(crate) fn find_stored_pdf(
+    conn: &rusqlite::Connection,
+    source: &Path,
+) -> Result<Option<String>, AppError> {
+    let hash = pdf_hash(&std::fs::read(source)?);
+    crate::db::pdf_files::find_by_hash(conn, &hash)
+}
+
+/// Whether a paper in the library already uses a PDF with content hash `hash`
+fn is_pdf_in_library(conn: &rusqlite::Connection, hash: &str) -> Result<bool, AppError> {
+    match crate::db::pdf_files::find_by_hash(conn, hash)? {
+        Some(existing) => Ok(crate::db::pdf_files::count_references(conn, &existing)? > 0),
+        None => Ok(false),
+    }
+}
+
+/// A PDF read from disk for import. Reading and hashing need no database, so
+/// bulk imports do them without holding the connection.
+struct PdfImport {
+    source_path: PathBuf,
+    file_name: String,
+    bytes: Vec<u8>,
+    hash: String,
+}
+
+impl PdfImport {
+    fn read(source_path: &Path) -> Result<Self, AppError> {
+        let file_name = source_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "paper.pdf".to_string());
+        let bytes = std::fs::read(source_path)?;
+        let hash = pdf_hash(&bytes);
+
+        Ok(Self {
+            source_path: source_path.to_path_buf(),
+            file_name,
+            bytes,
+            hash,
+        })
+    }
+}
+
+/// Create a paper in `folder_id` for a PDF on disk: copy the file into storage
+/// and pre-fill bibliographic fields from a sidecar metadata file or the PDF's
+/// own metadata
+pub(crate) fn import_pdf_file(
+    app: &AppHandle,
+    conn: &rusqlite::Connection,
+    folder_id: &str,
+    source_path: &Path,
+) -> Result<crate::models::Paper, AppError> {
+    let pdf = PdfImport::read(source_path)?;
+    import_pdf_contents(app, folder_id, &pdf, || Ok(conn))
+}
+
+/// Store `pdf` and create its paper. `connect` is only called around the
+/// database steps, so copying the file and reading its metadata happen without
+/// the connection. If creating the paper fails, a newly written copy is removed.
+fn import_pdf_contents<C>(
+    app: &AppHandle,
+    folder_id: &str,
+    pdf: &PdfImport,
+    connect: impl Fn() -> Result<C, AppError>,
+) -> Result<crate::models::Paper, AppError>
+where
+    C: std::ops::Deref<Target = rusqlite::Connection>,
+{
+    // Reuse an identical, previously imported copy
+    let existing = find_existing_copy(app, &*connect()?, &pdf.hash)?;
+    let (stored_path, written) = match existing {
+        Some(stored) => (stored, None),
+        None => {
+            let dest_path = get_pdf_dir(app)?
+                .join(format!("{}_{}", uuid::Uuid::new_v4(), pdf.file_name));
+            std::fs::write(&dest_path, &pdf.bytes)?;
+            (to_stored_pdf_path(app, &dest_path)?, Some(dest_path))
+        }
+    };
+
+    let metadata = match crate::commands::pdf_metadata::extract_pdf_metadata(&pdf.source_path) {
+        Ok(metadata) => Some(metadata),
+        Err(e) => {
+            log::warn!("Could not read metadata from {}: {}", pdf.source_path.display(), e);
+            None
+        }
+    };
+
+    let created = connect()
+        .and_then(|conn| create_imported_paper(&conn, folder_id, pdf, &stored_path, metadata));
+    if created.is_err() {
+        if let Some(dest_path) = written {
+            let _ = std::fs::remove_file(dest_path);
+        }
+    }
+    created
+}
+
+/// Create the paper for a stored PDF in one transaction, so a failure leaves
+/// no paper behind
+fn create_imported_paper(
+    conn: &rusqlite::Connection,
+    folder_id: &str,
+    pdf: &PdfImport,
+    stored_path: &str,
+    metadata: Option<crate::models::PdfMetadata>,
+) -> Result<crate::models::Paper, AppError> {
+    let tx = conn.unchecked_transaction()?;
+
+    // Extract title from filename (remove .pdf extension)
+    let title = pdf
+        .source_path
+        .file_stem()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "Untitled".to_string());
+
+    let input = crate::models::CreatePaperInput {
+        folder_id: folder_id.to_string(),
+        title,
+        author: None,
+        year: None,
+        pdf_path: Some(stored_path.to_string()),
+        pdf_filename: Some(pdf.file_name.clone()),
+    };
+    let paper = crate::db::papers::create_paper(&tx, input)?;
+    crate::db::pdf_files::register(&tx, &pdf.hash, stored_path)?;
+
+    // Apply the folder's import defaults
+    let folder = crate::db::folders::get_folder(&tx, folder_id)?;
+    let update_input = crate::models::UpdatePaperInput {
+        importance: folder.default_importance,
+        tags: (!folder.default_tags.is_empty()).then_some(folder.default_tags),
+        ..Default::default()
+    };
+    let mut paper = crate::db::papers::update_paper(&tx, &paper.id, update_input)?;
+
+    // Prefer a .bib/.ris/.json file downloaded alongside the PDF, otherwise
+    // pre-fill bibliographic fields from the PDF itself, leaving the rest to AI analysis
+    match crate::commands::citations::apply_sidecar_metadata(&tx, &paper.id, &pdf.source_path) {
+        Ok(Some(updated)) => paper = updated,
+        Ok(None) => {
+            let applied = metadata.map(|metadata| {
+                crate::commands::pdf_metadata::apply_pdf_metadata(&tx, &paper.id, metadata)
+            });
+            match applied {
+                Some(Ok(updated)) => paper = updated,
+                Some(Err(e)) => log::warn!(
+                    "Could not apply metadata from {}: {}",
+                    pdf.source_path.display(),
+                    e
+                ),
+                None => {}
+            }
+        }
+        Err(e) => log::warn!(
+            "Could not apply metadata file for {}: {}",
+            pdf.source_path.display(),
+            e
+        ),
+    }
+
+    crate::commands::citations::assign_initial_citation_key(&tx, &mut paper)?;
+    tx.commit()?;
+    Ok(paper)
+}
+
+/// Collect the PDFs in a directory (and its subdirectories when `recursive`), in path order
+fn find_pdfs(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>, AppError> {
+    let mut pdfs = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)?.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                if recursive {
+                    pending.push(path);
+                }
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext.to_string_lossy().eq_ignore_ascii_case("pdf"))
+            {
+                pdfs.push(path);
+            }
+        }
+    }
+
+    pdfs.sort();
+    Ok(pdfs)
+}
+
+/// Import every PDF in a directory into a folder. PDFs whose content is already
+/// in the library are skipped. Emits `import-directory-progress` per file and
+/// `import-directory-done` with the counts at the end. Runs off the main thread
+/// so `cancel_operation` can stop it.
+#[tauri::command(async)]
+#[allow(clippy::too_many_arguments)]
+pub fn import_directory(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    cancellation: State<'_, crate::commands::cancellation::CancellationState>,
+    dir: String,
+    folder_id: String,
+    recursive: bool,
+    index: Option<bool>,
+    operation_id: Option<String>,
+) -> Result<Vec<crate::models::Paper>, AppError> {
+    let dir = PathBuf::from(&dir);
+    if !dir.is_dir() {
+        return Err(AppError::Validation(format!("Not a directory: {}", dir.display())));
+    }
+
+    crate::db::folders::get_folder(&*db.get()?, &folder_id)?;
+    let pdfs = find_pdfs(&dir, recursive)?;
+
+    let token = cancellation.register(operation_id);
+    let mut summary = ImportDirectorySummary::default();
+    let mut papers = Vec::new();
+
+    for (i, pdf) in pdfs.iter().enumerate() {
+        if token.is_cancelled() {
+            break;
+        }
+
+        // The connection is only taken for the database steps, so other
+        // commands aren't blocked while files are read and copied
+        let outcome = PdfImport::read(pdf).and_then(|import| {
+            if is_pdf_in_library(&*db.get()?, &import.hash)? {
+                return Ok(None);
+            }
+            import_pdf_contents(&app, &folder_id, &import, || db.get()).map(Some)
+        });
+
+        let (status, error) = match outcome {
+            Ok(Some(paper)) => {
+                summary.imported += 1;
+                papers.push(paper);
+                ("imported", None)
+            }
+            Ok(None) => {
+                summary.skipped += 1;
+                ("skipped", None)
+            }
+            Err(e) => {
+                log::warn!("Failed to import {}: {}", pdf.display(), e);
+                summary.failed += 1;
+                ("failed", Some(e.to_string()))
+            }
+        };
+
+        let _ = app.emit(
+            "import-directory-progress",
+            ImportDirectoryProgress {
+                current: i + 1,
+                total: pdfs.len(),
+                file_path: pdf.to_string_lossy().to_string(),
+                status: status.to_string(),
+                error,
+            },
+        );
+    }
+    cancellation.finish(&token);
+
+    if !papers.is_empty() {
+        let _ = app.emit("papers-changed", &folder_id);
+    }
+
+    if index.unwrap_or(false) {
+        for paper in &papers {
+            let indexed = crate::commands::pdf_indexing::index_paper(
+                app.clone(),
+                db.clone(),
+                paper.id.clone(),
+                None,
+            );
+            if let Err(e) = indexed {
+                log::warn!("Failed to index {}: {}", paper.id, e);
+            }
+        }
+    }
+
+    let _ = app.emit("import-directory-done", &summary);
+    Ok(papers)
+}
+
+/// Delete a stored PDF file once no paper references it.
+/// Only files inside the app's pdfs dir are removed, never user files linked from elsewhere.
+pub(crate) fn release_pdf(
+    app: &AppHandle,
+    conn: &rusqlite::Connection,
+    pdf_path: &str,
+) -> Result<(), AppError> {
+    if pdf_path.is_empty() || crate::db::pdf_files::count_references(conn, pdf_path)? > 0 {
+        return Ok(());
+    }
+
+    let path = resolve_pdf_path(app, pdf_path)?;
+    if path.starts_with(get_pdf_dir(app)?) && path.exists() {
+        std::fs::remove_file(&path)?;
+    }
+    crate::db::pdf_files::remove_path(conn, pdf_path)
+}
+
 #[tauri::command]
-pub fn import_pdf(app: AppHandle, source_path: String, paper_id: String) -> Result<String, AppError> {
-    let pdf_dir = get_pdf_dir(&app)?;
+pub fn import_pdf(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    source_path: String,
+    paper_id: String,
+) -> Result<String, AppError> {
     let source = PathBuf::from(&source_path);
 
     let filename = source
@@ -29,26 +450,105 @@ pub fn import_pdf(app: AppHandle, source_path: String, paper_id: String) -> Resu
         .unwrap_or("paper.pdf");
 
     let dest_filename = format!("{}_{}", paper_id, filename);
-    let dest_path = pdf_dir.join(&dest_filename);
 
-    std::fs::copy(&source, &dest_path)?;
+    let conn = db.get()?;
+    let stored_path = store_pdf(&app, &conn, &source, &dest_filename)?;
+
+    // Pre-fill bibliographic fields from the PDF itself; a failure here never blocks the import
+    match crate::commands::pdf_metadata::extract_pdf_metadata(&source)
+        .and_then(|metadata| crate::commands::pdf_metadata::apply_pdf_metadata(&conn, &paper_id, metadata))
+    {
+        Ok(paper) => {
+            let _ = app.emit("papers-changed", &paper.folder_id);
+        }
+        Err(e) => log::warn!("Could not read metadata from {}: {}", source_path, e),
+    }
 
-    Ok(dest_path.to_string_lossy().to_string())
+    Ok(stored_path)
 }
 
+/// Read a whole PDF as base64. Intended for files up to `STREAMING_THRESHOLD_BYTES`;
+/// larger files should be range-requested with `get_pdf_byte_range`.
 #[tauri::command]
-pub fn get_pdf_as_base64(pdf_path: String) -> Result<String, AppError> {
-    let bytes = std::fs::read(&pdf_path)?;
+pub fn get_pdf_as_base64(app: AppHandle, pdf_path: String) -> Result<String, AppError> {
+    let bytes = std::fs::read(resolve_pdf_path(&app, &pdf_path)?)?;
+    if bytes.len() as u64 > STREAMING_THRESHOLD_BYTES {
+        log::warn!(
+            "Loading {} byte PDF as base64; use get_pdf_byte_range for large files",
+            bytes.len()
+        );
+    }
     Ok(STANDARD.encode(&bytes))
 }
 
+/// Get the size of a PDF in bytes, so the viewer can choose between base64 and streaming
 #[tauri::command]
-pub fn delete_pdf(pdf_path: String) -> Result<(), AppError> {
-    let path = PathBuf::from(&pdf_path);
-    if path.exists() {
-        std::fs::remove_file(&path)?;
+pub fn get_pdf_size(app: AppHandle, pdf_path: String) -> Result<u64, AppError> {
+    Ok(std::fs::metadata(resolve_pdf_path(&app, &pdf_path)?)?.len())
+}
+
+/// Get a paper's PDF page count, encryption, text layer and file size, e.g. to
+/// warn before indexing that a scanned PDF needs OCR
+#[tauri::command]
+pub fn get_pdf_info(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_id: String,
+) -> Result<PdfInfo, AppError> {
+    let pdf_path = {
+        let conn = db.get()?;
+        crate::db::papers::get_paper(&conn, &paper_id)?.pdf_path
+    };
+    if pdf_path.is_empty() {
+        return Err(AppError::NotFound("No PDF file attached".to_string()));
     }
-    Ok(())
+
+    let path = resolve_pdf_path(&app, &pdf_path)?;
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("PDF file not found: {}", pdf_path)));
+    }
+    crate::commands::pdf_metadata::read_pdf_info(&path)
+}
+
+/// Read bytes `[start, end)` of a PDF as raw binary (an `ArrayBuffer` on the frontend).
+/// The range is clamped to the file size and to `MAX_CHUNK_BYTES`.
+#[tauri::command]
+pub fn get_pdf_byte_range(
+    app: AppHandle,
+    pdf_path: String,
+    start: u64,
+    end: u64,
+) -> Result<tauri::ipc::Response, AppError> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    if end < start {
+        return Err(AppError::Validation(format!(
+            "Invalid byte range {}-{}",
+            start, end
+        )));
+    }
+
+    let mut file = std::fs::File::open(resolve_pdf_path(&app, &pdf_path)?)?;
+    let size = file.metadata()?.len();
+    let start = start.min(size);
+    let end = end.min(size).min(start + MAX_CHUNK_BYTES);
+
+    let mut buffer = vec![0u8; (end - start) as usize];
+    file.seek(SeekFrom::Start(start))?;
+    file.read_exact(&mut buffer)?;
+
+    Ok(tauri::ipc::Response::new(buffer))
+}
+
+/// Delete a stored PDF unless a paper still references it (see `release_pdf`)
+#[tauri::command]
+pub fn delete_pdf(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    pdf_path: String,
+) -> Result<(), AppError> {
+    let conn = db.get()?;
+    release_pdf(&app, &conn, &pdf_path)
 }
 
 #[tauri::command]
@@ -56,3 +556,193 @@ pub fn get_pdf_storage_path(app: AppHandle) -> Result<String, AppError> {
     let pdf_dir = get_pdf_dir(&app)?;
     Ok(pdf_dir.to_string_lossy().to_string())
 }
+
+/// Resolve a stored PDF path to an absolute file path (e.g. for the asset protocol)
+#[tauri::command]
+pub fn resolve_pdf_file(app: AppHandle, pdf_path: String) -> Result<String, AppError> {
+    Ok(resolve_pdf_path(&app, &pdf_path)?.to_string_lossy().to_string())
+}
+
+/// List papers whose PDF file no longer exists at the stored path
+#[tauri::command]
+pub fn verify_pdf_paths(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+) -> Result<Vec<MissingPdf>, AppError> {
+    let conn = db.get()?;
+    let papers = crate::db::papers::get_papers_with_pdf(&conn)?;
+
+    let mut missing = Vec::new();
+    for (paper_id, title, _folder_id, pdf_path) in papers {
+        if !resolve_pdf_path(&app, &pdf_path)?.exists() {
+            missing.push(MissingPdf {
+                paper_id,
+                title,
+                pdf_path,
+            });
+        }
+    }
+
+    Ok(missing)
+}
+
+// Index every file under `dir` by file name (first match wins)
+fn index_files_by_name(dir: &Path, index: &mut std::collections::HashMap<String, PathBuf>) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            index_files_by_name(&path, index);
+        } else if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            index.entry(name.to_string()).or_insert(path);
+        }
+    }
+}
+
+/// Relink papers with missing PDFs to files of the same name under `new_base_dir`
+#[tauri::command]
+pub fn relink_pdfs(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    new_base_dir: String,
+) -> Result<RelinkResult, AppError> {
+    let base_dir = PathBuf::from(&new_base_dir);
+    if !base_dir.is_dir() {
+        return Err(AppError::NotFound(format!(
+            "Directory not found: {}",
+            new_base_dir
+        )));
+    }
+
+    let mut files = std::collections::HashMap::new();
+    index_files_by_name(&base_dir, &mut files);
+
+    let conn = db.get()?;
+    let papers = crate::db::papers::get_papers_with_pdf(&conn)?;
+
+    let mut relinked = 0;
+    let mut still_missing = Vec::new();
+    let mut changed_folders = std::collections::HashSet::new();
+
+    for (paper_id, title, folder_id, pdf_path) in papers {
+        if resolve_pdf_path(&app, &pdf_path)?.exists() {
+            continue;
+        }
+
+        // Stored paths may come from another OS, so split on both separators
+        let file_name = pdf_path.rsplit(['/', '\\']).next().unwrap_or_default();
+
+        match files.get(file_name) {
+            Some(found) => {
+                let stored = to_stored_pdf_path(&app, found)?;
+                crate::db::papers::update_pdf_location(&conn, &paper_id, &stored)?;
+                changed_folders.insert(folder_id);
+                relinked += 1;
+            }
+            None => still_missing.push(MissingPdf {
+                paper_id,
+                title,
+                pdf_path,
+            }),
+        }
+    }
+
+    for folder_id in changed_folders {
+        let _ = app.emit("papers-changed", &folder_id);
+    }
+
+    Ok(RelinkResult {
+        relinked,
+        still_missing,
+    })
+}
+
+/// Render the first page of a paper's PDF to a PNG thumbnail and return its path.
+/// Thumbnails are cached in `thumbnails/<paper_id>.png` and regenerated when the PDF is newer.
+#[tauri::command]
+pub fn generate_pdf_thumbnail(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_id: String,
+) -> Result<String, AppError> {
+    let pdf_path: Option<String> = {
+        let conn = db.get()?;
+        conn.query_row(
+            "SELECT pdf_path FROM papers WHERE id = ?",
+            [&paper_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| AppError::NotFound("Paper not found".to_string()))?
+    };
+
+    let pdf_path = pdf_path
+        .filter(|p| !p.is_empty())
+        .ok_or_else(|| AppError::NotFound("No PDF file attached".to_string()))?;
+    let pdf_path = resolve_pdf_path(&app, &pdf_path)?;
+
+    if !pdf_path.exists() {
+        return Err(AppError::NotFound(format!(
+            "PDF file not found: {}",
+            pdf_path.display()
+        )));
+    }
+
+    let thumbnail_path = get_thumbnail_dir(&app)?.join(format!("{}.png", paper_id));
+
+    if !is_thumbnail_fresh(&thumbnail_path, &pdf_path) {
+        render_first_page(&app, &pdf_path, &thumbnail_path)?;
+    }
+
+    Ok(thumbnail_path.to_string_lossy().to_string())
+}
+
+// A cached thumbnail is reusable if it was written after the PDF was last modified
+fn is_thumbnail_fresh(thumbnail: &Path, pdf: &Path) -> bool {
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+
+    match (modified(thumbnail), modified(pdf)) {
+        (Some(thumbnail_time), Some(pdf_time)) => thumbnail_time >= pdf_time,
+        _ => false,
+    }
+}
+
+fn render_first_page(app: &AppHandle, pdf: &Path, output: &Path) -> Result<(), AppError> {
+    use pdfium_render::prelude::*;
+
+    // Prefer a PDFium library bundled with the app, then fall back to a system install
+    let bindings = app
+        .path()
+        .resource_dir()
+        .map_err(|e| AppError::Io(e.to_string()))
+        .and_then(|dir| {
+            Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(&dir))
+                .map_err(|e| AppError::Io(e.to_string()))
+        })
+        .or_else(|_| Pdfium::bind_to_system_library())
+        .map_err(|e| AppError::Io(format!("PDFium library not available: {}", e)))?;
+    let pdfium = Pdfium::new(bindings);
+
+    let document = pdfium
+        .load_pdf_from_file(pdf, None)
+        .map_err(|e| AppError::Parse(format!("Failed to open PDF: {}", e)))?;
+    let page = document
+        .pages()
+        .get(0)
+        .map_err(|e| AppError::Parse(format!("Failed to read first page: {}", e)))?;
+
+    let config = PdfRenderConfig::new()
+        .set_target_width(THUMBNAIL_WIDTH)
+        .set_maximum_height(THUMBNAIL_MAX_HEIGHT);
+
+    page.render_with_config(&config)
+        .map_err(|e| AppError::Parse(format!("Failed to render page: {}", e)))?
+        .as_image()
+        .save_with_format(output, image::ImageFormat::Png)
+        .map_err(|e| AppError::Io(format!("Failed to save thumbnail: {}", e)))?;
+
+    Ok(())
+}
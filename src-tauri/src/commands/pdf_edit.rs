@@ -0,0 +1,322 @@
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::db::DbConnection;
+use crate::error::AppError;
+use crate::models::{CreatePaperInput, Paper, UpdatePaperInput};
+
+/// Page attributes a page may inherit from its ancestors in the page tree
+const INHERITED_PAGE_KEYS: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+/// Longest file name stem derived from a paper title, in characters
+const MAX_FILENAME_CHARS: usize = 100;
+
+fn parse_error(e: lopdf::Error) -> AppError {
+    AppError::Parse(format!("Failed to process PDF: {}", e))
+}
+
+/// Open a paper's PDF, decrypting it when it only has an owner password
+fn load_paper_pdf(
+    app: &AppHandle,
+    conn: &rusqlite::Connection,
+    paper_id: &str,
+) -> Result<Document, AppError> {
+    let paper = crate::db::papers::get_paper(conn, paper_id)?;
+    if paper.pdf_path.is_empty() {
+        return Err(AppError::Validation(format!(
+            "Paper has no PDF attached: {}",
+            paper.title
+        )));
+    }
+
+    let path = crate::commands::pdf::resolve_pdf_path(app, &paper.pdf_path)?;
+    if !path.exists() {
+        return Err(AppError::NotFound(format!(
+            "PDF file not found: {}",
+            paper.pdf_path
+        )));
+    }
+
+    let mut doc = Document::load(&path)
+        .map_err(|e| AppError::Parse(format!("Failed to read PDF: {}", e)))?;
+    crate::commands::pdf_indexing::decrypt_pdf(&mut doc, None)?;
+    Ok(doc)
+}
+
+/// A page's dictionary with its inherited attributes copied in, so it renders
+/// the same under a different page tree
+fn standalone_page(doc: &Document, page_id: ObjectId) -> Result<Dictionary, AppError> {
+    let mut page = doc.get_dictionary(page_id).map_err(parse_error)?.clone();
+
+    let mut parent = page.get(b"Parent").and_then(Object::as_reference).ok();
+    while let Some(node) = parent.and_then(|id| doc.get_dictionary(id).ok()) {
+        for key in INHERITED_PAGE_KEYS {
+            if !page.has(key) {
+                if let Ok(value) = node.get(key) {
+                    page.set(key, value.clone());
+                }
+            }
+        }
+        parent = node.get(b"Parent").and_then(Object::as_reference).ok();
+    }
+    Ok(page)
+}
+
+/// Concatenate the pages of `docs`, in order, under a single page tree.
+/// Outlines and other document-level structures of the sources are dropped.
+pub(crate) fn merge_documents(docs: Vec<Document>) -> Result<Document, AppError> {
+    let version = docs.first().map(|doc| doc.version.clone()).unwrap_or_default();
+    let mut merged = Document::with_version(version);
+    let pages_id = merged.new_object_id();
+    let mut kids: Vec<Object> = Vec::new();
+
+    for mut doc in docs {
+        doc.renumber_objects_with(merged.max_id + 1);
+        let pages = doc
+            .get_pages()
+            .into_values()
+            .map(|page_id| Ok((page_id, standalone_page(&doc, page_id)?)))
+            .collect::<Result<Vec<_>, AppError>>()?;
+
+        merged.max_id = doc.max_id;
+        merged.objects.extend(doc.objects);
+        for (page_id, mut page) in pages {
+            page.set("Parent", pages_id);
+            merged.objects.insert(page_id, Object::Dictionary(page));
+            kids.push(page_id.into());
+        }
+    }
+
+    let count = kids.len() as i64;
+    merged.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => count,
+        }),
+    );
+    let catalog_id = merged.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    merged.trailer.set("Root", catalog_id);
+
+    // The source catalogs and page trees are no longer referenced
+    merged.prune_objects();
+    merged.renumber_objects();
+    merged.compress();
+    Ok(merged)
+}
+
+/// Keep only pages `start..=end` (1-based) of `doc`
+pub(crate) fn keep_page_range(
+    mut doc: Document,
+    start: u32,
+    end: u32,
+) -> Result<Document, AppError> {
+    let page_count = doc.get_pages().len() as u32;
+    if start == 0 || start > end || end > page_count {
+        return Err(AppError::Validation(format!(
+            "Invalid page range {}-{}: the PDF has {} pages",
+            start, end, page_count
+        )));
+    }
+
+    let outside: Vec<u32> = (1..=page_count).filter(|n| !(start..=end).contains(n)).collect();
+    doc.delete_pages(&outside);
+
+    // Outline entries may point at deleted pages
+    if let Ok(catalog) = doc.catalog_mut() {
+        catalog.remove(b"Outlines");
+    }
+    doc.prune_objects();
+    doc.renumber_objects();
+    doc.compress();
+    Ok(doc)
+}
+
+/// Turn a paper title into a PDF file name
+fn title_filename(title: &str) -> String {
+    let stem: String = title
+        .chars()
+        .filter(|c| !matches!(c, '\\' | '/' | ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+        .filter(|c| !c.is_control())
+        .take(MAX_FILENAME_CHARS)
+        .collect();
+    let stem = stem.split_whitespace().collect::<Vec<_>>().join("_");
+    if stem.is_empty() {
+        "paper.pdf".to_string()
+    } else {
+        format!("{}.pdf", stem)
+    }
+}
+
+/// Store a generated PDF and create a paper for it in `folder_id`
+fn create_paper_with_pdf(
+    app: &AppHandle,
+    conn: &rusqlite::Connection,
+    folder_id: &str,
+    title: &str,
+    mut doc: Document,
+) -> Result<Paper, AppError> {
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+
+    let file_name = title_filename(title);
+    let input = CreatePaperInput {
+        folder_id: folder_id.to_string(),
+        title: title.to_string(),
+        author: None,
+        year: None,
+        pdf_path: None,
+        pdf_filename: Some(file_name.clone()),
+    };
+    let paper = crate::db::papers::create_paper(conn, input)?;
+
+    let dest_filename = format!("{}_{}", paper.id, file_name);
+    let stored_path = crate::commands::pdf::store_pdf_bytes(app, conn, &bytes, &dest_filename)?;
+    let update = UpdatePaperInput {
+        pdf_path: Some(stored_path),
+        ..Default::default()
+    };
+    crate::db::papers::update_paper(conn, &paper.id, update)
+}
+
+fn validate_title(title: &str) -> Result<&str, AppError> {
+    let title = title.trim();
+    if title.is_empty() {
+        return Err(AppError::Validation("Title is required".to_string()));
+    }
+    Ok(title)
+}
+
+/// Concatenate the PDFs of `paper_ids`, in order, into a new paper in
+/// `folder_id`. The source papers and their files are left untouched.
+#[tauri::command]
+pub fn merge_pdfs(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_ids: Vec<String>,
+    output_title: String,
+    folder_id: String,
+) -> Result<Paper, AppError> {
+    if paper_ids.len() < 2 {
+        return Err(AppError::Validation(
+            "Select at least two papers to merge".to_string(),
+        ));
+    }
+    let title = validate_title(&output_title)?;
+
+    let conn = db.get()?;
+    crate::db::folders::get_folder(&conn, &folder_id)?;
+
+    let docs = paper_ids
+        .iter()
+        .map(|paper_id| load_paper_pdf(&app, &conn, paper_id))
+        .collect::<Result<Vec<_>, _>>()?;
+    let merged = merge_documents(docs)?;
+
+    let paper = create_paper_with_pdf(&app, &conn, &folder_id, title, merged)?;
+    let _ = app.emit("papers-changed", &paper.folder_id);
+    Ok(paper)
+}
+
+/// Write pages `start_page..=end_page` (1-based) of a paper's PDF to a new
+/// paper in `folder_id` and index it. The source paper is left untouched.
+#[tauri::command]
+pub fn extract_pdf_pages(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_id: String,
+    start_page: u32,
+    end_page: u32,
+    new_title: String,
+    folder_id: String,
+) -> Result<Paper, AppError> {
+    let title = validate_title(&new_title)?;
+
+    let paper = {
+        let conn = db.get()?;
+        crate::db::folders::get_folder(&conn, &folder_id)?;
+
+        let doc = load_paper_pdf(&app, &conn, &paper_id)?;
+        let extracted = keep_page_range(doc, start_page, end_page)?;
+        create_paper_with_pdf(&app, &conn, &folder_id, title, extracted)?
+    };
+
+    // Indexing takes its own connection
+    let indexed = crate::commands::pdf_indexing::index_paper(
+        app.clone(),
+        db.clone(),
+        paper.id.clone(),
+        None,
+    );
+    if let Err(e) = indexed {
+        log::warn!("Failed to index {}: {}", paper.id, e);
+    }
+
+    let _ = app.emit("papers-changed", &paper.folder_id);
+    let conn = db.get()?;
+    crate::db::papers::get_paper(&conn, &paper.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A document with `pages` empty pages that inherit their MediaBox
+    fn test_document(pages: usize) -> Document {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let kids: Vec<Object> = (0..pages)
+            .map(|_| {
+                doc.add_object(dictionary! {
+                    "Type" => "Page",
+                    "Parent" => pages_id,
+                })
+                .into()
+            })
+            .collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => pages as i64,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+        doc
+    }
+
+    #[test]
+    fn test_merge_documents() {
+        let mut merged = merge_documents(vec![test_document(1), test_document(2)]).unwrap();
+        let pages = merged.get_pages();
+        assert_eq!(pages.len(), 3);
+        for page_id in pages.values() {
+            assert!(merged.get_dictionary(*page_id).unwrap().has(b"MediaBox"));
+        }
+
+        // Survives a round trip through the writer
+        let mut bytes = Vec::new();
+        merged.save_to(&mut bytes).unwrap();
+        assert_eq!(Document::load_mem(&bytes).unwrap().get_pages().len(), 3);
+    }
+
+    #[test]
+    fn test_keep_page_range() {
+        let doc = keep_page_range(test_document(5), 2, 3).unwrap();
+        assert_eq!(doc.get_pages().len(), 2);
+
+        assert!(keep_page_range(test_document(5), 0, 2).is_err());
+        assert!(keep_page_range(test_document(5), 4, 3).is_err());
+        assert!(keep_page_range(test_document(5), 4, 6).is_err());
+    }
+}
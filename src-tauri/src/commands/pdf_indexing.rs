@@ -1,100 +1,254 @@
+use std::panic::AssertUnwindSafe;
 use std::path::Path;
+use std::sync::{mpsc, Mutex};
+use pdf_extract::encryption::DecryptionError;
 use tauri::{AppHandle, Emitter, State};
+use crate::commands::cancellation::CancellationState;
+use crate::db::pdf_content::FtsTokenizer;
 use crate::db::DbConnection;
 use crate::error::AppError;
-use crate::models::{FullTextSearchQuery, FullTextSearchResponse, IndexingStatus};
+use crate::models::{FtsRebuildReport, FullTextSearchQuery, FullTextSearchResponse, IndexingProgress, IndexingStatus};
 
-/// Extract text from a PDF file using pdf-extract
-fn extract_pdf_text(pdf_path: &str) -> Result<String, AppError> {
-    let path = Path::new(pdf_path);
-    if !path.exists() {
-        return Err(AppError::NotFound(format!("PDF not found: {}", pdf_path)));
+/// Decrypt a PDF in place with `password`, or with the empty user password
+/// most publisher PDFs use. The password is only held for the call.
+pub(crate) fn decrypt_pdf(
+    doc: &mut pdf_extract::Document,
+    password: Option<&str>,
+) -> Result<(), AppError> {
+    if !doc.is_encrypted() {
+        return Ok(());
+    }
+    match doc.decrypt(password.unwrap_or("")) {
+        Ok(()) => Ok(()),
+        Err(pdf_extract::Error::Decryption(DecryptionError::IncorrectPassword)) => {
+            Err(AppError::PdfEncrypted(match password {
+                Some(_) => "the password is incorrect".to_string(),
+                None => "a password is required to open it".to_string(),
+            }))
+        }
+        Err(e) => Err(AppError::Parse(format!("Failed to decrypt PDF: {}", e))),
+    }
+}
+
+fn open_pdf(pdf_path: &Path, password: Option<&str>) -> Result<pdf_extract::Document, AppError> {
+    if !pdf_path.exists() {
+        return Err(AppError::NotFound(format!("PDF not found: {}", pdf_path.display())));
     }
 
-    pdf_extract::extract_text(path)
-        .map_err(|e| AppError::Parse(format!("Failed to extract PDF text: {}", e)))
+    let mut doc = pdf_extract::Document::load(pdf_path)
+        .map_err(|e| AppError::Parse(format!("Failed to read PDF: {}", e)))?;
+    decrypt_pdf(&mut doc, password)?;
+    Ok(doc)
 }
 
-/// Index a single paper's PDF content
-#[tauri::command]
-pub fn index_paper(
-    app: AppHandle,
-    db: State<'_, DbConnection>,
-    paper_id: String,
-) -> Result<IndexingStatus, AppError> {
-    let conn = db.get()?;
+/// Extract text from a PDF file using pdf-extract
+pub(crate) fn extract_pdf_text(pdf_path: &str, password: Option<&str>) -> Result<String, AppError> {
+    let doc = open_pdf(Path::new(pdf_path), password)?;
 
-    // Get paper's PDF path
-    let pdf_path: String = conn.query_row(
-        "SELECT pdf_path FROM papers WHERE id = ?",
-        [&paper_id],
-        |row| row.get(0),
-    ).map_err(|_| AppError::NotFound("Paper not found".to_string()))?;
+    let mut text = String::new();
+    let mut output = pdf_extract::PlainTextOutput::new(&mut text);
+    pdf_extract::output_doc(&doc, &mut output)
+        .map_err(|e| AppError::Parse(format!("Failed to extract PDF text: {}", e)))?;
+    Ok(text)
+}
 
-    if pdf_path.is_empty() {
+/// Default number of PDFs extracted at once by `index_all_papers`; large PDFs
+/// take a lot of memory, so this stays modest even on many-core machines
+const DEFAULT_INDEXING_WORKERS: usize = 4;
+
+fn failed_status(paper_id: String, error: String) -> IndexingStatus {
+    IndexingStatus {
+        paper_id,
+        total_pages: 0,
+        indexed_pages: 0,
+        is_complete: false,
+        error: Some(error),
+        failed_pages: Vec::new(),
+    }
+}
+
+/// Replace a paper's indexed pages with freshly extracted ones. The paper is
+/// marked indexed in the same transaction as its pages, so an interrupted run
+/// leaves it unindexed and `index_all_papers` picks it up again. A paper with
+/// no readable pages at all stays unindexed.
+fn store_extracted_pages(
+    app: &AppHandle,
+    conn: &rusqlite::Connection,
+    paper_id: String,
+    extracted: ExtractedPages,
+) -> Result<IndexingStatus, AppError> {
+    let total_pages = (extracted.pages.len() + extracted.failed_pages.len()) as i32;
+    if extracted.pages.is_empty() {
         return Ok(IndexingStatus {
-            paper_id: paper_id.clone(),
-            total_pages: 0,
-            indexed_pages: 0,
-            is_complete: false,
-            error: Some("No PDF file attached".to_string()),
+            total_pages,
+            failed_pages: extracted.failed_pages,
+            ..failed_status(paper_id, "No text could be extracted from the PDF".to_string())
         });
     }
 
-    // Extract text from PDF
-    let text = match extract_pdf_text(&pdf_path) {
-        Ok(t) => t,
-        Err(e) => {
-            return Ok(IndexingStatus {
-                paper_id: paper_id.clone(),
-                total_pages: 0,
-                indexed_pages: 0,
-                is_complete: false,
-                error: Some(e.to_string()),
-            });
-        }
-    };
+    let tx = conn.unchecked_transaction()?;
 
     // Clear existing pages for this paper
-    crate::db::pdf_content::delete_pdf_pages(&conn, &paper_id)?;
-
-    // For now, treat entire PDF as one page (pdf-extract doesn't provide page-by-page)
-    // This can be enhanced later with per-page extraction
-    let total_pages = 1;
-    crate::db::pdf_content::insert_pdf_page(&conn, &paper_id, 1, &text)?;
+    crate::db::pdf_content::delete_pdf_pages(&tx, &paper_id)?;
+    for (page_number, text) in &extracted.pages {
+        crate::db::pdf_content::insert_pdf_page(&tx, &paper_id, *page_number, text)?;
+    }
 
     // Mark as indexed
-    crate::db::pdf_content::mark_paper_indexed(&conn, &paper_id)?;
+    crate::db::pdf_content::mark_paper_indexed(&tx, &paper_id)?;
+    tx.commit()?;
 
     // Emit event to notify frontend
     let _ = app.emit("paper-indexed", &paper_id);
 
+    let error = (!extracted.failed_pages.is_empty())
+        .then(|| format!("{} of {} pages could not be read", extracted.failed_pages.len(), total_pages));
     Ok(IndexingStatus {
         paper_id,
         total_pages,
-        indexed_pages: total_pages,
+        indexed_pages: extracted.pages.len() as i32,
         is_complete: true,
-        error: None,
+        error,
+        failed_pages: extracted.failed_pages,
     })
 }
 
-/// Index all unindexed papers
+fn extract_stored_pdf_pages(
+    app: &AppHandle,
+    stored_path: &str,
+    password: Option<&str>,
+) -> Result<ExtractedPages, AppError> {
+    let pdf_path = crate::commands::pdf::resolve_pdf_path(app, stored_path)?;
+    extract_pdf_pages(&pdf_path, password)
+}
+
+/// Text of each page of a PDF. Pages that fail to extract (e.g. corrupt
+/// content streams) are listed in `failed_pages` instead of failing the file.
+struct ExtractedPages {
+    pages: Vec<(i32, String)>,
+    failed_pages: Vec<i32>,
+}
+
+fn extract_pdf_pages(pdf_path: &Path, password: Option<&str>) -> Result<ExtractedPages, AppError> {
+    let doc = open_pdf(pdf_path, password)?;
+
+    let mut extracted = ExtractedPages { pages: Vec::new(), failed_pages: Vec::new() };
+    for page_number in doc.get_pages().into_keys() {
+        // pdf-extract panics on some malformed pages rather than returning an error
+        let page = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut text = String::new();
+            let mut output = pdf_extract::PlainTextOutput::new(&mut text);
+            pdf_extract::output_doc_page(&doc, &mut output, page_number).map(|_| text)
+        }));
+        match page {
+            Ok(Ok(text)) => extracted.pages.push((page_number as i32, text)),
+            _ => {
+                log::warn!("Failed to extract page {} of {}", page_number, pdf_path.display());
+                extracted.failed_pages.push(page_number as i32);
+            }
+        }
+    }
+    Ok(extracted)
+}
+
+/// Index a single paper's PDF content. `password` opens an encrypted PDF and is
+/// not stored; without it, an encrypted PDF fails with `pdf_encrypted`.
 #[tauri::command]
+pub fn index_paper(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    paper_id: String,
+    password: Option<String>,
+) -> Result<IndexingStatus, AppError> {
+    // Get paper's PDF path
+    let pdf_path: String = db.get()?.query_row(
+        "SELECT pdf_path FROM papers WHERE id = ?",
+        [&paper_id],
+        |row| row.get(0),
+    ).map_err(|_| AppError::NotFound("Paper not found".to_string()))?;
+
+    if pdf_path.is_empty() {
+        return Ok(failed_status(paper_id, "No PDF file attached".to_string()));
+    }
+
+    // Extract text without holding the database lock
+    match extract_stored_pdf_pages(&app, &pdf_path, password.as_deref()) {
+        Ok(extracted) => {
+            let conn = db.get()?;
+            store_extracted_pages(&app, &conn, paper_id, extracted)
+        }
+        // Surfaced as an error so the UI can ask for the password and retry
+        Err(e @ AppError::PdfEncrypted(_)) => Err(e),
+        Err(e) => Ok(failed_status(paper_id, e.to_string())),
+    }
+}
+
+/// Indexed vs. total papers with a PDF, e.g. to offer resuming `index_all_papers`
+#[tauri::command]
+pub fn get_indexing_progress(db: State<'_, DbConnection>) -> Result<IndexingProgress, AppError> {
+    let conn = db.get()?;
+    crate::db::pdf_content::get_indexing_progress(&conn)
+}
+
+/// Index all unindexed papers, so an interrupted run resumes where it left off.
+/// Text is extracted on a pool of worker threads (sized by the
+/// `indexing_concurrency` setting) while pages are written to the database from
+/// this thread as each PDF finishes. Runs off the main thread so
+/// `cancel_operation` can stop it.
+#[tauri::command(async)]
 pub fn index_all_papers(
     app: AppHandle,
     db: State<'_, DbConnection>,
+    cancellation: State<'_, CancellationState>,
+    operation_id: Option<String>,
 ) -> Result<Vec<IndexingStatus>, AppError> {
     let conn = db.get()?;
     let papers = crate::db::pdf_content::get_unindexed_papers(&conn)?;
-    drop(conn); // Release connection before looping
+    let workers = crate::db::settings::get_setting_i32(&conn, "indexing_concurrency")?
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_INDEXING_WORKERS)
+        .min(papers.len().max(1));
+    drop(conn); // Release connection while extracting
 
-    let mut results = Vec::new();
-    for (paper_id, _pdf_path) in papers {
-        let status = index_paper(app.clone(), db.clone(), paper_id)?;
-        results.push(status);
-    }
+    let token = cancellation.register(operation_id);
+    let queue = Mutex::new(papers.into_iter());
+    let (tx, rx) = mpsc::channel();
+
+    let result = std::thread::scope(|scope| {
+        for _ in 0..workers {
+            let (tx, queue, token, app) = (tx.clone(), &queue, &token, &app);
+            scope.spawn(move || {
+                while !token.is_cancelled() {
+                    let next = queue.lock().ok().and_then(|mut papers| papers.next());
+                    let Some((paper_id, pdf_path)) = next else {
+                        break;
+                    };
+                    let extracted = extract_stored_pdf_pages(app, &pdf_path, None);
+                    // The receiver is gone if storing failed; stop extracting
+                    if tx.send((paper_id, extracted)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        let mut results = Vec::new();
+        for (paper_id, extracted) in rx {
+            let status = match extracted {
+                Ok(extracted) => {
+                    let conn = db.get()?;
+                    store_extracted_pages(&app, &conn, paper_id, extracted)?
+                }
+                Err(e) => failed_status(paper_id, e.to_string()),
+            };
+            results.push(status);
+        }
+        Ok(results)
+    });
+    cancellation.finish(&token);
 
-    Ok(results)
+    result
 }
 
 /// Full-text search across all PDFs
@@ -107,6 +261,26 @@ pub fn search_full_text(
     crate::db::pdf_content::search_pdf_content(&conn, &query)
 }
 
+/// Rebuild the full-text index with the tokenizer chosen in the `fts_tokenizer`
+/// setting. Returns the tokenizer now in use.
+#[tauri::command]
+pub fn rebuild_search_index(db: State<'_, DbConnection>) -> Result<String, AppError> {
+    let conn = db.get()?;
+    let setting = crate::db::settings::get_setting_string(&conn, "fts_tokenizer")?;
+    let tokenizer = FtsTokenizer::from_setting(setting.as_deref());
+    crate::db::pdf_content::recreate_fts_index(&conn, tokenizer)?;
+
+    Ok(tokenizer.name().to_string())
+}
+
+/// Rebuild the full-text index from the stored page text, e.g. after restoring
+/// a backup, and report whether it matches `pdf_pages` afterwards
+#[tauri::command]
+pub fn rebuild_fts_index(db: State<'_, DbConnection>) -> Result<FtsRebuildReport, AppError> {
+    let conn = db.get()?;
+    crate::db::pdf_content::rebuild_fts_index(&conn)
+}
+
 /// Check if a paper has been indexed
 #[tauri::command]
 pub fn get_paper_index_status(
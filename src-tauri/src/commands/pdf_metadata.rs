@@ -0,0 +1,343 @@
+use std::path::Path;
+use std::sync::LazyLock;
+
+use chrono::Datelike;
+use lopdf::{Dictionary, Document, Object};
+use regex::Regex;
+use rusqlite::Connection;
+
+use crate::error::AppError;
+use crate::models::{Paper, PdfInfo, PdfMetadata, UpdatePaperInput};
+
+/// Matches a DOI such as `10.1000/xyz123`, stopping at whitespace and markup
+static DOI_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?i)\b(10\.\d{4,9}/[^\s"'<>]+)"#).unwrap());
+
+/// A plausible publication year
+static YEAR_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(1[89]\d{2}|20\d{2})\b").unwrap());
+
+static RDF_LI_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<rdf:li\b[^>]*>(.*?)</rdf:li>").unwrap());
+
+/// XMP properties that carry the publication date, in order of preference
+const XMP_DATE_TAGS: [&str; 4] = [
+    "prism:publicationDate",
+    "prism:coverDate",
+    "prism:coverDisplayDate",
+    "dc:date",
+];
+
+/// Read title, author, keywords, year and DOI from a PDF's document info
+/// dictionary and XMP metadata, falling back to a DOI scan of the first page.
+/// Missing or implausible values are left as `None`.
+pub(crate) fn extract_pdf_metadata(pdf_path: &Path) -> Result<PdfMetadata, AppError> {
+    let mut doc = Document::load(pdf_path)
+        .map_err(|e| AppError::Parse(format!("Failed to read PDF: {}", e)))?;
+    // Info strings and the XMP stream are encrypted along with the content
+    crate::commands::pdf_indexing::decrypt_pdf(&mut doc, None)?;
+
+    let info = doc
+        .trailer
+        .get_deref(b"Info", &doc)
+        .and_then(Object::as_dict)
+        .ok();
+    let info_string = |key: &[u8]| info.and_then(|dict| dict_string(dict, key, &doc));
+    let xmp = read_xmp(&doc).unwrap_or_default();
+
+    let title = xmp_text(&xmp, "dc:title")
+        .and_then(|t| clean_title(&t))
+        .or_else(|| info_string(b"Title").and_then(|t| clean_title(&t)));
+
+    let creators = xmp_list(&xmp, "dc:creator");
+    let author = if creators.is_empty() {
+        info_string(b"Author")
+    } else {
+        Some(creators.join("; "))
+    }
+    .and_then(|a| clean_author(&a));
+
+    let keywords = xmp_text(&xmp, "pdf:Keywords")
+        .or_else(|| {
+            let subjects = xmp_list(&xmp, "dc:subject");
+            (!subjects.is_empty()).then(|| subjects.join(", "))
+        })
+        .or_else(|| info_string(b"Keywords"))
+        .map(|k| collapse_whitespace(&k))
+        .filter(|k| !k.is_empty());
+
+    let year = XMP_DATE_TAGS
+        .iter()
+        .filter_map(|tag| xmp_text(&xmp, tag))
+        .find_map(|date| parse_year(&date));
+
+    let doi = [
+        xmp_text(&xmp, "prism:doi"),
+        xmp_text(&xmp, "dc:identifier"),
+        info_string(b"doi"),
+        info_string(b"Subject"),
+    ]
+    .into_iter()
+    .flatten()
+    .find_map(|text| find_doi(&text))
+    .or_else(|| {
+        doc.extract_text(&[1])
+            .ok()
+            .and_then(|text| find_doi(&text))
+    });
+
+    Ok(PdfMetadata {
+        title,
+        author,
+        keywords,
+        year,
+        doi,
+    })
+}
+
+/// Fill a freshly imported paper from its PDF metadata. The title replaces the
+/// filename-derived one; other fields are only set when still empty.
+pub(crate) fn apply_pdf_metadata(
+    conn: &Connection,
+    paper_id: &str,
+    metadata: PdfMetadata,
+) -> Result<Paper, AppError> {
+    let paper = crate::db::papers::get_paper(conn, paper_id)?;
+
+    let input = UpdatePaperInput {
+        title: metadata.title,
+        author: metadata.author.filter(|_| paper.author.is_empty()),
+        keywords: metadata.keywords.filter(|_| paper.keywords.is_empty()),
+        year: metadata.year.filter(|_| paper.year == 0),
+        doi: metadata.doi.filter(|_| paper.doi.is_empty()),
+        ..Default::default()
+    };
+
+    crate::db::papers::update_paper(conn, paper_id, input)
+}
+
+/// Read a PDF's page count, encryption and whether its first page has a text
+/// layer. Encrypted files are opened with the empty user password when possible.
+pub(crate) fn read_pdf_info(pdf_path: &Path) -> Result<PdfInfo, AppError> {
+    let file_size = std::fs::metadata(pdf_path)?.len();
+    let mut doc = Document::load(pdf_path)
+        .map_err(|e| AppError::Parse(format!("Failed to read PDF: {}", e)))?;
+
+    let is_encrypted = doc.is_encrypted();
+    let readable = !is_encrypted || doc.decrypt("").is_ok();
+
+    let pages = doc.get_pages();
+    let has_text_layer = readable
+        && pages
+            .keys()
+            .next()
+            .and_then(|&first| doc.extract_text(&[first]).ok())
+            .is_some_and(|text| text.chars().any(char::is_alphanumeric));
+
+    Ok(PdfInfo {
+        page_count: pages.len() as u32,
+        is_encrypted,
+        has_text_layer,
+        file_size,
+    })
+}
+
+/// Find the first DOI in a block of text, without trailing punctuation
+pub(crate) fn find_doi(text: &str) -> Option<String> {
+    DOI_RE.captures(text).map(|caps| {
+        caps[1]
+            .trim_end_matches(['.', ',', ';', ':', ')', ']', '}'])
+            .to_string()
+    })
+}
+
+fn read_xmp(doc: &Document) -> Option<String> {
+    let stream = doc
+        .catalog()
+        .ok()?
+        .get_deref(b"Metadata", doc)
+        .ok()?
+        .as_stream()
+        .ok()?;
+    let content = stream.get_plain_content().ok()?;
+    Some(String::from_utf8_lossy(&content).into_owned())
+}
+
+/// Read a text string from a dictionary, handling both UTF-16BE and PDFDocEncoding
+fn dict_string(dict: &Dictionary, key: &[u8], doc: &Document) -> Option<String> {
+    let bytes = dict.get_deref(key, doc).ok()?.as_str().ok()?;
+    let text = decode_pdf_string(bytes);
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+fn decode_pdf_string(bytes: &[u8]) -> String {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        let units: Vec<u16> = utf16
+            .chunks_exact(2)
+            .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+            .collect();
+        String::from_utf16_lossy(&units)
+    } else if let Some(utf8) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        String::from_utf8_lossy(utf8).into_owned()
+    } else {
+        // PDFDocEncoding agrees with Latin-1 for the printable characters that matter here
+        bytes.iter().map(|&b| b as char).collect()
+    }
+}
+
+/// Get a single-valued XMP property, either as an element (`rdf:Alt`/`rdf:li`
+/// or plain text) or as an attribute on `rdf:Description`
+fn xmp_text(xmp: &str, tag: &str) -> Option<String> {
+    if xmp.is_empty() {
+        return None;
+    }
+
+    let tag = regex::escape(tag);
+    let element = Regex::new(&format!(r"(?s)<{0}\b[^>]*>(.*?)</{0}>", tag)).unwrap();
+    let value = match element.captures(xmp) {
+        Some(caps) => xmp_list_items(&caps[1])
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| caps[1].to_string()),
+        None => {
+            let attribute = Regex::new(&format!(r#"\b{}="([^"]*)""#, tag)).unwrap();
+            attribute.captures(xmp)?[1].to_string()
+        }
+    };
+
+    let value = unescape_xml(&value);
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Get every `rdf:li` item of a multi-valued XMP property
+fn xmp_list(xmp: &str, tag: &str) -> Vec<String> {
+    let element = Regex::new(&format!(r"(?s)<{0}\b[^>]*>(.*?)</{0}>", regex::escape(tag))).unwrap();
+    element
+        .captures(xmp)
+        .map(|caps| {
+            xmp_list_items(&caps[1])
+                .iter()
+                .map(|item| unescape_xml(item).trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn xmp_list_items(body: &str) -> Vec<String> {
+    RDF_LI_RE
+        .captures_iter(body)
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+fn unescape_xml(text: &str) -> String {
+    quick_xml::escape::unescape(text)
+        .map(|s| s.into_owned())
+        .unwrap_or_else(|_| text.to_string())
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Reject titles that are really filenames or authoring-tool placeholders
+fn clean_title(raw: &str) -> Option<String> {
+    let title = collapse_whitespace(raw);
+    let title = title
+        .strip_prefix("Microsoft Word - ")
+        .unwrap_or(&title)
+        .to_string();
+    let lower = title.to_lowercase();
+
+    let looks_like_file = [".pdf", ".doc", ".docx", ".tex", ".dvi", ".indd"]
+        .iter()
+        .any(|ext| lower.ends_with(ext));
+    if title.chars().count() < 4 || looks_like_file || lower.starts_with("untitled") {
+        return None;
+    }
+
+    Some(title)
+}
+
+fn clean_author(raw: &str) -> Option<String> {
+    let author = collapse_whitespace(raw);
+    let lower = author.to_lowercase();
+    if author.is_empty() || ["unknown", "administrator", "admin", "user"].contains(&lower.as_str()) {
+        return None;
+    }
+    Some(author)
+}
+
+fn parse_year(date: &str) -> Option<i32> {
+    let year: i32 = YEAR_RE.captures(date)?[1].parse().ok()?;
+    (year <= chrono::Utc::now().year() + 1).then_some(year)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xmp_text() {
+        let element = r#"<rdf:Description>
+            <dc:title><rdf:Alt>
+                <rdf:li xml:lang="x-default">Deep &amp; Wide</rdf:li>
+            </rdf:Alt></dc:title>
+            <prism:doi>10.1000/xyz123</prism:doi>
+        </rdf:Description>"#;
+        assert_eq!(xmp_text(element, "dc:title").as_deref(), Some("Deep & Wide"));
+        assert_eq!(xmp_text(element, "prism:doi").as_deref(), Some("10.1000/xyz123"));
+        assert_eq!(xmp_text(element, "pdf:Keywords"), None);
+
+        let attribute =
+            r#"<rdf:Description pdf:Keywords="graphs, networks" prism:coverDate="2019-03-01"/>"#;
+        assert_eq!(xmp_text(attribute, "pdf:Keywords").as_deref(), Some("graphs, networks"));
+        let year = xmp_text(attribute, "prism:coverDate").and_then(|d| parse_year(&d));
+        assert_eq!(year, Some(2019));
+    }
+
+    #[test]
+    fn test_xmp_list() {
+        let xmp = r#"<dc:creator><rdf:Seq>
+            <rdf:li>Ada Lovelace</rdf:li>
+            <rdf:li> </rdf:li>
+            <rdf:li>Alan Turing</rdf:li>
+        </rdf:Seq></dc:creator>"#;
+        assert_eq!(xmp_list(xmp, "dc:creator"), vec!["Ada Lovelace", "Alan Turing"]);
+        assert!(xmp_list(xmp, "dc:subject").is_empty());
+    }
+
+    #[test]
+    fn test_decode_pdf_string() {
+        let utf16: Vec<u8> = [0xFE, 0xFF]
+            .into_iter()
+            .chain("Café 論文".encode_utf16().flat_map(u16::to_be_bytes))
+            .collect();
+        assert_eq!(decode_pdf_string(&utf16), "Café 論文");
+        assert_eq!(decode_pdf_string(b"Plain title"), "Plain title");
+    }
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(clean_title("Microsoft Word - thesis_final.docx"), None);
+        assert_eq!(
+            clean_title("Microsoft Word - Attention Is All You Need").as_deref(),
+            Some("Attention Is All You Need")
+        );
+        assert_eq!(clean_title("Untitled-1"), None);
+        assert_eq!(clean_title("paper.pdf"), None);
+    }
+
+    #[test]
+    fn test_find_doi() {
+        assert_eq!(
+            find_doi("See https://doi.org/10.1145/3292500.3330701.").as_deref(),
+            Some("10.1145/3292500.3330701")
+        );
+        assert_eq!(find_doi("(doi:10.1000/xyz123);").as_deref(), Some("10.1000/xyz123"));
+        assert_eq!(find_doi("no identifier here"), None);
+    }
+}
@@ -1,18 +1,38 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tauri::State;
-use crate::db::{settings, DbConnection};
+use crate::db::{secrets, settings, DbConnection};
 use crate::error::AppError;
 
+/// Known app settings with their types; numbers and flags are parsed and
+/// validated here so the frontend never has to.
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AppSettings {
     pub gemini_api_key: Option<String>,
     pub openai_api_key: Option<String>,
     pub default_font_family: Option<String>,
-    pub default_font_size: Option<String>,
+    pub default_font_size: Option<i32>,
     pub storage_path: Option<String>,
     pub google_account_email: Option<String>,
+    pub http_timeout_secs: Option<i32>,
+    pub http_proxy: Option<String>,
+    pub contact_email: Option<String>,
+    pub auto_backup_interval_hours: Option<i32>,
+    pub auto_analyze_delay_secs: Option<i32>,
+    pub serpapi_key: Option<String>,
+    pub rename_pattern: Option<String>,
+    pub rename_max_title_length: Option<i32>,
+    pub rename_space_replacement: Option<String>,
+    pub rename_lowercase: Option<bool>,
+    pub auto_rename_after_analysis: Option<bool>,
+    pub fts_tokenizer: Option<String>,
+    pub indexing_concurrency: Option<i32>,
+    pub bibtex_custom_fields: Option<String>,
+    pub analysis_temperature: Option<f32>,
+    pub text_temperature: Option<f32>,
+    pub analysis_max_output_tokens: Option<i32>,
+    pub default_citation_style: Option<String>,
 }
 
 impl Default for AppSettings {
@@ -21,28 +41,101 @@ impl Default for AppSettings {
             gemini_api_key: None,
             openai_api_key: None,
             default_font_family: Some("sans-serif".to_string()),
-            default_font_size: Some("12".to_string()),
+            default_font_size: Some(12),
             storage_path: None,
             google_account_email: None,
+            http_timeout_secs: None,
+            http_proxy: None,
+            contact_email: None,
+            auto_backup_interval_hours: None,
+            auto_analyze_delay_secs: None,
+            serpapi_key: None,
+            rename_pattern: None,
+            rename_max_title_length: None,
+            rename_space_replacement: None,
+            rename_lowercase: None,
+            auto_rename_after_analysis: None,
+            fts_tokenizer: None,
+            indexing_concurrency: None,
+            bibtex_custom_fields: None,
+            analysis_temperature: None,
+            text_temperature: None,
+            analysis_max_output_tokens: None,
+            default_citation_style: None,
         }
     }
 }
 
-/// Get all app settings
+const MASK: &str = "••••";
+
+/// Mask a secret down to its last 4 characters, e.g. `••••1234`
+fn mask_secret(value: &str) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= 4 {
+        return MASK.to_string();
+    }
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}{}", MASK, tail)
+}
+
+// A masked value echoed back from the UI must not overwrite the real secret
+fn is_masked_echo(key: &str, value: &str) -> bool {
+    secrets::is_sensitive(key) && value.starts_with(MASK)
+}
+
+/// Read a typed setting, treating a malformed or out-of-range stored value as
+/// unset. Values written by older versions were never validated, and one of
+/// them must not stop the whole settings dialog from loading.
+fn lenient<T>(key: &str, value: Result<Option<T>, AppError>) -> Result<Option<T>, AppError> {
+    match value {
+        Err(AppError::Validation(message)) => {
+            log::warn!("Ignoring invalid stored setting {}: {}", key, message);
+            Ok(None)
+        }
+        value => value,
+    }
+}
+
+/// Get all app settings (secrets are masked; use `get_setting` for the full value).
+/// A malformed stored value for a typed setting is logged and read as unset.
 #[tauri::command]
 pub fn get_settings(db: State<'_, DbConnection>) -> Result<AppSettings, AppError> {
     let conn = db.get()?;
-    let all_settings = settings::get_all_settings(&conn)?;
+    let defaults = AppSettings::default();
 
-    let map: HashMap<String, String> = all_settings.into_iter().collect();
+    let text = |key: &str| settings::get_setting_string(&conn, key);
+    let secret = |key: &str| -> Result<Option<String>, AppError> {
+        Ok(settings::get_setting_string(&conn, key)?.map(|value| mask_secret(&value)))
+    };
+    let number = |key: &str| lenient(key, settings::get_setting_i32(&conn, key));
+    let flag = |key: &str| lenient(key, settings::get_setting_bool(&conn, key));
+    let decimal = |key: &str| lenient(key, settings::get_setting_f32(&conn, key));
 
     Ok(AppSettings {
-        gemini_api_key: map.get("gemini_api_key").cloned(),
-        openai_api_key: map.get("openai_api_key").cloned(),
-        default_font_family: map.get("default_font_family").cloned().or(Some("sans-serif".to_string())),
-        default_font_size: map.get("default_font_size").cloned().or(Some("12".to_string())),
-        storage_path: map.get("storage_path").cloned(),
-        google_account_email: map.get("google_account_email").cloned(),
+        gemini_api_key: secret("gemini_api_key")?,
+        openai_api_key: secret("openai_api_key")?,
+        default_font_family: text("default_font_family")?.or(defaults.default_font_family),
+        default_font_size: number("default_font_size")?.or(defaults.default_font_size),
+        storage_path: text("storage_path")?,
+        google_account_email: text("google_account_email")?,
+        http_timeout_secs: number("http_timeout_secs")?,
+        http_proxy: text("http_proxy")?,
+        contact_email: text("contact_email")?,
+        auto_backup_interval_hours: number("auto_backup_interval_hours")?,
+        auto_analyze_delay_secs: number("auto_analyze_delay_secs")?,
+        serpapi_key: secret("serpapi_key")?,
+        rename_pattern: text("rename_pattern")?,
+        rename_max_title_length: number("rename_max_title_length")?,
+        rename_space_replacement: settings::get_setting(&conn, "rename_space_replacement")?,
+        rename_lowercase: flag("rename_lowercase")?,
+        auto_rename_after_analysis: flag("auto_rename_after_analysis")?,
+        fts_tokenizer: text("fts_tokenizer")?,
+        indexing_concurrency: number("indexing_concurrency")?,
+        bibtex_custom_fields: text("bibtex_custom_fields")?,
+        analysis_temperature: decimal("analysis_temperature")?,
+        text_temperature: decimal("text_temperature")?,
+        analysis_max_output_tokens: number("analysis_max_output_tokens")?,
+        default_citation_style: text("default_citation_style")?,
     })
 }
 
@@ -56,23 +149,81 @@ pub fn get_setting(db: State<'_, DbConnection>, key: String) -> Result<Option<St
 /// Set a single setting
 #[tauri::command]
 pub fn set_setting(db: State<'_, DbConnection>, key: String, value: String) -> Result<(), AppError> {
+    if is_masked_echo(&key, &value) {
+        return Ok(());
+    }
     let conn = db.get()?;
-    settings::set_setting(&conn, &key, &value)
+    settings::set_setting(&conn, &key, &value)?;
+    crate::commands::http::load_settings(&conn)
 }
 
 /// Update multiple settings at once
 #[tauri::command]
 pub fn update_settings(db: State<'_, DbConnection>, settings_map: HashMap<String, String>) -> Result<(), AppError> {
+    // Validate everything up front so a bad value doesn't leave a partial update
+    for (key, value) in &settings_map {
+        settings::validate_setting(key, value)?;
+    }
+
     let conn = db.get()?;
     for (key, value) in settings_map {
+        if is_masked_echo(&key, &value) {
+            continue;
+        }
         settings::set_setting(&conn, &key, &value)?;
     }
-    Ok(())
+    crate::commands::http::load_settings(&conn)
 }
 
 /// Delete a setting
 #[tauri::command]
 pub fn delete_setting(db: State<'_, DbConnection>, key: String) -> Result<(), AppError> {
     let conn = db.get()?;
-    settings::delete_setting(&conn, &key)
+    settings::delete_setting(&conn, &key)?;
+    crate::commands::http::load_settings(&conn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_secret() {
+        assert_eq!(mask_secret("AIzaSyExampleKey1234"), "••••1234");
+        assert_eq!(mask_secret("abc"), "••••");
+        assert!(is_masked_echo("gemini_api_key", "••••1234"));
+        assert!(!is_masked_echo("gemini_api_key", "AIzaSyNewKey"));
+        assert!(!is_masked_echo("google_account_email", "••••@example.com"));
+    }
+
+    #[test]
+    fn test_validate_setting() {
+        assert!(settings::validate_setting("rename_max_title_length", "80").is_ok());
+        assert!(settings::validate_setting("rename_max_title_length", "").is_ok());
+        assert!(matches!(
+            settings::validate_setting("rename_max_title_length", "eighty"),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            settings::validate_setting("default_font_size", "200"),
+            Err(AppError::Validation(_))
+        ));
+        assert!(settings::validate_setting("rename_lowercase", "true").is_ok());
+        assert!(settings::validate_setting("rename_lowercase", "maybe").is_err());
+        assert!(settings::validate_setting("fts_tokenizer", "trigram").is_ok());
+        assert!(settings::validate_setting("fts_tokenizer", "porter").is_err());
+        assert!(settings::validate_setting("text_temperature", "0.7").is_ok());
+        assert!(settings::validate_setting("analysis_temperature", "3").is_err());
+        assert!(settings::validate_setting("analysis_temperature", "NaN").is_err());
+        assert!(settings::validate_setting("http_proxy", "anything").is_ok());
+    }
+
+    #[test]
+    fn test_lenient() {
+        let invalid = Err(AppError::Validation("must be a whole number".to_string()));
+        assert!(matches!(lenient::<i32>("default_font_size", invalid), Ok(None)));
+        assert!(matches!(lenient("default_font_size", Ok(Some(14))), Ok(Some(14))));
+        let locked = Err(AppError::Database("database is locked".to_string()));
+        assert!(lenient::<i32>("default_font_size", locked).is_err());
+    }
 }
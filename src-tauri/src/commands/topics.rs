@@ -2,7 +2,7 @@ use tauri::{AppHandle, Emitter, State};
 
 use crate::db::DbConnection;
 use crate::error::AppError;
-use crate::models::{CreateTopicInput, Topic, UpdateTopicInput};
+use crate::models::{CreateTopicInput, Topic, TopicWithCount, UpdateTopicInput};
 
 #[tauri::command]
 pub fn get_topics(db: State<'_, DbConnection>) -> Result<Vec<Topic>, AppError> {
@@ -10,6 +10,13 @@ pub fn get_topics(db: State<'_, DbConnection>) -> Result<Vec<Topic>, AppError> {
     crate::db::topics::get_topics(&conn)
 }
 
+/// Get all topics with the total number of papers in each
+#[tauri::command]
+pub fn get_topics_with_counts(db: State<'_, DbConnection>) -> Result<Vec<TopicWithCount>, AppError> {
+    let conn = db.get()?;
+    crate::db::topics::get_topics_with_counts(&conn)
+}
+
 #[tauri::command]
 pub fn get_topic(db: State<'_, DbConnection>, topic_id: String) -> Result<Topic, AppError> {
     let conn = db.get()?;
@@ -41,6 +48,19 @@ pub fn update_topic(
     Ok(topic)
 }
 
+/// Set topic sort order to match the given ID order
+#[tauri::command]
+pub fn reorder_topics(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    ordered_ids: Vec<String>,
+) -> Result<Vec<Topic>, AppError> {
+    let conn = db.get()?;
+    let topics = crate::db::topics::reorder_topics(&conn, &ordered_ids)?;
+    let _ = app.emit("topics-changed", ());
+    Ok(topics)
+}
+
 #[tauri::command]
 pub fn delete_topic(
     app: AppHandle,
@@ -0,0 +1,131 @@
+use std::collections::HashSet;
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::db::DbConnection;
+use crate::error::AppError;
+use crate::models::UpdatePaperInput;
+
+/// A PDF moved by a batch rename, with stored paths on both sides
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PdfRename {
+    pub paper_id: String,
+    pub old_path: String,
+    pub new_path: String,
+    pub old_filename: String,
+}
+
+/// The state needed to reverse a batch operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoableOperation {
+    BatchRename { renames: Vec<PdfRename> },
+    BatchDelete { paper_ids: Vec<String> },
+}
+
+/// Outcome of `undo_last_operation`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoResult {
+    /// `batch_rename` or `batch_delete`
+    pub kind: String,
+    pub reverted: usize,
+    /// One message per paper that couldn't be reverted
+    pub errors: Vec<String>,
+}
+
+/// Remember a batch operation so it can be undone. Only the latest one is kept.
+pub(crate) fn record_operation(
+    conn: &Connection,
+    operation: &UndoableOperation,
+) -> Result<(), AppError> {
+    crate::db::operation_log::record(conn, &serde_json::to_string(operation)?)
+}
+
+/// Move a renamed PDF back and repoint the papers using it
+fn revert_rename(
+    app: &AppHandle,
+    conn: &Connection,
+    rename: &PdfRename,
+) -> Result<String, AppError> {
+    let current = crate::commands::pdf::resolve_pdf_path(app, &rename.new_path)?;
+    let original = crate::commands::pdf::resolve_pdf_path(app, &rename.old_path)?;
+
+    if !current.exists() {
+        return Err(AppError::NotFound(format!(
+            "PDF file not found: {}",
+            rename.new_path
+        )));
+    }
+    if original.exists() {
+        return Err(AppError::Validation(format!(
+            "Target file already exists: {}",
+            original.display()
+        )));
+    }
+
+    std::fs::rename(&current, &original)?;
+    crate::db::pdf_files::rename_path(conn, &rename.new_path, &rename.old_path)?;
+
+    let input = UpdatePaperInput {
+        pdf_filename: Some(rename.old_filename.clone()),
+        ..Default::default()
+    };
+    let paper = crate::db::papers::update_paper(conn, &rename.paper_id, input)?;
+    Ok(paper.folder_id)
+}
+
+/// Reverse the most recent batch rename or batch delete. Returns `None` when
+/// there is nothing to undo.
+#[tauri::command]
+pub fn undo_last_operation(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+) -> Result<Option<UndoResult>, AppError> {
+    let conn = db.get()?;
+    let Some(recorded) = crate::db::operation_log::take(&conn)? else {
+        return Ok(None);
+    };
+    let operation: UndoableOperation = serde_json::from_str(&recorded)?;
+
+    let mut affected_folders = HashSet::new();
+    let mut errors = Vec::new();
+
+    let (kind, total) = match &operation {
+        UndoableOperation::BatchRename { renames } => {
+            // Newest first, in case one rename freed the name another took
+            for rename in renames.iter().rev() {
+                match revert_rename(&app, &conn, rename) {
+                    Ok(folder_id) => {
+                        affected_folders.insert(folder_id);
+                    }
+                    Err(e) => errors.push(format!("{}: {}", rename.paper_id, e)),
+                }
+            }
+            ("batch_rename", renames.len())
+        }
+        UndoableOperation::BatchDelete { paper_ids } => {
+            for paper_id in paper_ids {
+                match crate::db::papers::restore_paper(&conn, paper_id) {
+                    Ok(paper) => {
+                        affected_folders.insert(paper.folder_id);
+                    }
+                    Err(e) => errors.push(format!("{}: {}", paper_id, e)),
+                }
+            }
+            ("batch_delete", paper_ids.len())
+        }
+    };
+
+    for folder_id in affected_folders {
+        let _ = app.emit("papers-changed", &folder_id);
+    }
+
+    Ok(Some(UndoResult {
+        kind: kind.to_string(),
+        reverted: total - errors.len(),
+        errors,
+    }))
+}
@@ -1,7 +1,11 @@
+use std::collections::HashMap;
+
 use tauri::{AppHandle, Emitter, State};
 
+use crate::commands::citations::{expand_citations, format_references_section, CitationStyle};
 use crate::db::DbConnection;
 use crate::error::AppError;
+use crate::models::paper::{Paper, PaperFilter};
 use crate::models::{
     CreateWritingDocumentInput, CreateWritingProjectInput, MoveWritingDocumentInput,
     UpdateWritingDocumentInput, UpdateWritingProjectInput, WritingDocument, WritingProject,
@@ -147,15 +151,62 @@ pub fn move_writing_document(
     Ok(document)
 }
 
+/// Quote a highlight (with its note and source paper) at the end of a document
+#[tauri::command]
+pub fn insert_highlight_into_document(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    document_id: String,
+    highlight_id: String,
+) -> Result<WritingDocument, AppError> {
+    let conn = db.get()?;
+    let highlight = crate::db::highlights::get_highlight(&conn, &highlight_id)?;
+    let paper = crate::db::papers::get_paper(&conn, &highlight.paper_id)?;
+    let document = crate::db::writing::append_highlight(&conn, &document_id, &highlight, &paper.title)?;
+    let _ = app.emit("writing-documents-changed", &document.project_id);
+    Ok(document)
+}
+
+/// Writing documents a highlight has been quoted in
+#[tauri::command]
+pub fn get_highlight_documents(
+    db: State<'_, DbConnection>,
+    highlight_id: String,
+) -> Result<Vec<WritingDocument>, AppError> {
+    let conn = db.get()?;
+    crate::db::writing::get_highlight_documents(&conn, &highlight_id)
+}
+
 // ============================================================================
 // Export Commands
 // ============================================================================
 
+/// Export a project as Markdown. `[@citekey]` citations of library papers are
+/// expanded in `citation_style` (the default citation style unless given) and
+/// followed by a References section.
 #[tauri::command]
 pub fn export_project_markdown(
     db: State<'_, DbConnection>,
     project_id: String,
+    citation_style: Option<CitationStyle>,
 ) -> Result<String, AppError> {
     let conn = db.get()?;
-    crate::db::writing::export_project_markdown(&conn, &project_id)
+    let markdown = crate::db::writing::export_project_markdown(&conn, &project_id)?;
+
+    let papers = crate::db::papers::get_papers(&conn, &PaperFilter::default(), None, None, None)?;
+    let papers_by_key: HashMap<String, &Paper> = papers
+        .iter()
+        .filter(|p| !p.citation_key.is_empty())
+        .map(|p| (p.citation_key.clone(), p))
+        .collect();
+
+    let style = match citation_style {
+        Some(style) => style,
+        None => crate::commands::citations::default_citation_style(&conn)?,
+    };
+    let (mut markdown, cited) = expand_citations(&markdown, &papers_by_key, &style);
+    if !cited.is_empty() {
+        markdown.push_str(&format_references_section(&cited, &style));
+    }
+    Ok(markdown)
 }
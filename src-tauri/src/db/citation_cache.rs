@@ -0,0 +1,53 @@
+use rusqlite::{params, Connection};
+
+use crate::error::AppError;
+
+/// Reference DOIs and citation DOIs linked to a paper
+pub type CitationLinks = (Vec<String>, Vec<String>);
+
+fn parse_json_array(json: &str) -> Vec<String> {
+    serde_json::from_str(json).unwrap_or_default()
+}
+
+fn to_json_array(vec: &[String]) -> String {
+    serde_json::to_string(vec).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Get cached (reference DOIs, citation DOIs) for a paper's DOI
+pub fn get_links(conn: &Connection, doi: &str) -> Result<Option<CitationLinks>, AppError> {
+    let result = conn.query_row(
+        "SELECT reference_dois, citation_dois FROM citation_links_cache WHERE doi = ?",
+        [doi],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+    );
+
+    match result {
+        Ok((references, citations)) => Ok(Some((
+            parse_json_array(&references),
+            parse_json_array(&citations),
+        ))),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+/// Store reference and citation DOIs fetched for a paper's DOI
+pub fn store_links(
+    conn: &Connection,
+    doi: &str,
+    references: &[String],
+    citations: &[String],
+) -> Result<(), AppError> {
+    conn.execute(
+        r#"
+        INSERT INTO citation_links_cache (doi, reference_dois, citation_dois, fetched_at)
+        VALUES (?, ?, ?, datetime('now'))
+        ON CONFLICT(doi) DO UPDATE SET
+            reference_dois = excluded.reference_dois,
+            citation_dois = excluded.citation_dois,
+            fetched_at = excluded.fetched_at
+        "#,
+        params![doi, to_json_array(references), to_json_array(citations)],
+    )?;
+    Ok(())
+}
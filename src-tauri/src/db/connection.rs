@@ -1,25 +1,53 @@
 use rusqlite::Connection;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 use crate::error::AppError;
 
 pub struct DbConnection {
     conn: Mutex<Connection>,
+    path: PathBuf,
 }
 
 impl DbConnection {
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, AppError> {
-        let conn = Connection::open(path)?;
-        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        let path = path.as_ref().to_path_buf();
         Ok(Self {
-            conn: Mutex::new(conn),
+            conn: Mutex::new(Self::open(&path)?),
+            path,
         })
     }
 
+    fn open(path: &Path) -> Result<Connection, AppError> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        Ok(conn)
+    }
+
     pub fn get(&self) -> Result<std::sync::MutexGuard<'_, Connection>, AppError> {
         self.conn
             .lock()
             .map_err(|e| AppError::Database(format!("Failed to acquire lock: {}", e)))
     }
+
+    /// Move `replacement` over the database file and reopen it, migrating it to
+    /// the current schema. The lock is held throughout, so no query runs
+    /// against the old file once this returns.
+    pub fn replace_file(&self, replacement: &Path) -> Result<(), AppError> {
+        let mut conn = self.get()?;
+
+        // Close the old file before moving the new one over it
+        let old = std::mem::replace(&mut *conn, Connection::open_in_memory()?);
+        if let Err((old, e)) = old.close() {
+            *conn = old;
+            return Err(e.into());
+        }
+
+        // Reopen even if the move failed, so the app keeps its old data
+        let moved = std::fs::rename(replacement, &self.path);
+        *conn = Self::open(&self.path)?;
+        moved?;
+
+        crate::db::migrations::run(&conn)
+    }
 }
@@ -2,23 +2,67 @@ use rusqlite::{params, Connection};
 use uuid::Uuid;
 
 use crate::error::AppError;
-use crate::models::{CreateFolderInput, Folder, UpdateFolderInput};
+use crate::models::{CreateFolderInput, Folder, FolderWithCount, UpdateFolderInput};
+
+const SELECT_COLUMNS: &str =
+    "id, topic_id, name, sort_order, default_importance, default_tags, created_at, updated_at";
+
+fn row_to_folder(row: &rusqlite::Row) -> rusqlite::Result<Folder> {
+    Ok(Folder {
+        id: row.get(0)?,
+        topic_id: row.get(1)?,
+        name: row.get(2)?,
+        sort_order: row.get(3)?,
+        default_importance: row.get(4)?,
+        default_tags: serde_json::from_str(&row.get::<_, String>(5)?).unwrap_or_default(),
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+/// Importance defaults follow paper importance, 1 to 5
+fn validate_default_importance(importance: Option<i32>) -> Result<(), AppError> {
+    match importance {
+        Some(i) if !(1..=5).contains(&i) => Err(AppError::Validation(format!(
+            "Default importance must be between 1 and 5, got {}",
+            i
+        ))),
+        _ => Ok(()),
+    }
+}
 
 pub fn get_folders(conn: &Connection, topic_id: &str) -> Result<Vec<Folder>, AppError> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM folders WHERE topic_id = ? ORDER BY sort_order ASC",
+        SELECT_COLUMNS
+    ))?;
+
+    let folders = stmt
+        .query_map([topic_id], row_to_folder)?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(folders)
+}
+
+pub fn get_folders_with_counts(
+    conn: &Connection,
+    topic_id: &str,
+) -> Result<Vec<FolderWithCount>, AppError> {
     let mut stmt = conn.prepare(
-        "SELECT id, topic_id, name, sort_order, created_at, updated_at 
-         FROM folders WHERE topic_id = ? ORDER BY sort_order ASC",
+        "SELECT f.id, f.topic_id, f.name, f.sort_order, f.default_importance, f.default_tags,
+                f.created_at, f.updated_at, COUNT(p.id)
+         FROM folders f
+         LEFT JOIN papers p ON p.folder_id = f.id AND p.deleted_at IS NULL
+         WHERE f.topic_id = ?
+         GROUP BY f.id
+         ORDER BY f.sort_order ASC",
     )?;
 
     let folders = stmt
         .query_map([topic_id], |row| {
-            Ok(Folder {
-                id: row.get(0)?,
-                topic_id: row.get(1)?,
-                name: row.get(2)?,
-                sort_order: row.get(3)?,
-                created_at: row.get(4)?,
-                updated_at: row.get(5)?,
+            Ok(FolderWithCount {
+                folder: row_to_folder(row)?,
+                paper_count: row.get(8)?,
             })
         })?
         .collect::<Result<Vec<_>, _>>()?;
@@ -27,47 +71,27 @@ pub fn get_folders(conn: &Connection, topic_id: &str) -> Result<Vec<Folder>, App
 }
 
 pub fn get_all_folders(conn: &Connection) -> Result<Vec<Folder>, AppError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, topic_id, name, sort_order, created_at, updated_at 
-         FROM folders ORDER BY sort_order ASC",
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM folders ORDER BY sort_order ASC",
+        SELECT_COLUMNS
+    ))?;
 
     let folders = stmt
-        .query_map([], |row| {
-            Ok(Folder {
-                id: row.get(0)?,
-                topic_id: row.get(1)?,
-                name: row.get(2)?,
-                sort_order: row.get(3)?,
-                created_at: row.get(4)?,
-                updated_at: row.get(5)?,
-            })
-        })?
+        .query_map([], row_to_folder)?
         .collect::<Result<Vec<_>, _>>()?;
 
     Ok(folders)
 }
 
 pub fn get_folder(conn: &Connection, folder_id: &str) -> Result<Folder, AppError> {
-    let mut stmt = conn.prepare(
-        "SELECT id, topic_id, name, sort_order, created_at, updated_at 
-         FROM folders WHERE id = ?",
-    )?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM folders WHERE id = ?", SELECT_COLUMNS))?;
 
-    stmt.query_row([folder_id], |row| {
-        Ok(Folder {
-            id: row.get(0)?,
-            topic_id: row.get(1)?,
-            name: row.get(2)?,
-            sort_order: row.get(3)?,
-            created_at: row.get(4)?,
-            updated_at: row.get(5)?,
-        })
-    })
-    .map_err(|_| AppError::NotFound(format!("Folder not found: {}", folder_id)))
+    stmt.query_row([folder_id], row_to_folder)
+        .map_err(|_| AppError::NotFound(format!("Folder not found: {}", folder_id)))
 }
 
 pub fn create_folder(conn: &Connection, input: CreateFolderInput) -> Result<Folder, AppError> {
+    validate_default_importance(input.default_importance)?;
     let id = Uuid::new_v4().to_string();
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
@@ -80,10 +104,21 @@ pub fn create_folder(conn: &Connection, input: CreateFolderInput) -> Result<Fold
         )
         .unwrap_or(-1);
 
+    let default_tags = serde_json::to_string(&input.default_tags.unwrap_or_default())?;
+
     conn.execute(
-        "INSERT INTO folders (id, topic_id, name, sort_order, created_at, updated_at) 
-         VALUES (?, ?, ?, ?, ?, ?)",
-        params![id, input.topic_id, input.name, max_order + 1, now, now],
+        "INSERT INTO folders (id, topic_id, name, sort_order, default_importance, default_tags, created_at, updated_at)
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            id,
+            input.topic_id,
+            input.name,
+            max_order + 1,
+            input.default_importance,
+            default_tags,
+            now,
+            now
+        ],
     )?;
 
     get_folder(conn, &id)
@@ -99,15 +134,76 @@ pub fn update_folder(
 
     let name = input.name.unwrap_or(folder.name);
     let sort_order = input.sort_order.unwrap_or(folder.sort_order);
+    let default_importance = match input.default_importance {
+        Some(0) => None,
+        Some(importance) => Some(importance),
+        None => folder.default_importance,
+    };
+    validate_default_importance(default_importance)?;
+    let default_tags = serde_json::to_string(&input.default_tags.unwrap_or(folder.default_tags))?;
 
     conn.execute(
-        "UPDATE folders SET name = ?, sort_order = ?, updated_at = ? WHERE id = ?",
-        params![name, sort_order, now, folder_id],
+        "UPDATE folders SET name = ?, sort_order = ?, default_importance = ?, default_tags = ?, updated_at = ?
+         WHERE id = ?",
+        params![name, sort_order, default_importance, default_tags, now, folder_id],
     )?;
 
     get_folder(conn, folder_id)
 }
 
+/// Move a folder (and with it, all its papers) to another topic.
+/// The folder is placed after the target topic's existing folders.
+pub fn move_folder(
+    conn: &Connection,
+    folder_id: &str,
+    target_topic_id: &str,
+) -> Result<Folder, AppError> {
+    get_folder(conn, folder_id)?;
+    crate::db::topics::get_topic(conn, target_topic_id)?;
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    let max_order: i32 = conn
+        .query_row(
+            "SELECT COALESCE(MAX(sort_order), -1) FROM folders WHERE topic_id = ? AND id != ?",
+            params![target_topic_id, folder_id],
+            |row| row.get(0),
+        )
+        .unwrap_or(-1);
+
+    conn.execute(
+        "UPDATE folders SET topic_id = ?, sort_order = ?, updated_at = ? WHERE id = ?",
+        params![target_topic_id, max_order + 1, now, folder_id],
+    )?;
+
+    get_folder(conn, folder_id)
+}
+
+pub fn reorder_folders(
+    conn: &Connection,
+    topic_id: &str,
+    ordered_ids: &[String],
+) -> Result<Vec<Folder>, AppError> {
+    let tx = conn.unchecked_transaction()?;
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    for (index, folder_id) in ordered_ids.iter().enumerate() {
+        let affected = tx.execute(
+            "UPDATE folders SET sort_order = ?, updated_at = ? WHERE id = ? AND topic_id = ?",
+            params![index as i32, now, folder_id, topic_id],
+        )?;
+
+        if affected == 0 {
+            return Err(AppError::NotFound(format!(
+                "Folder not found in topic {}: {}",
+                topic_id, folder_id
+            )));
+        }
+    }
+
+    tx.commit()?;
+    get_folders(conn, topic_id)
+}
+
 pub fn delete_folder(conn: &Connection, folder_id: &str) -> Result<(), AppError> {
     // Check if folder exists
     get_folder(conn, folder_id)?;
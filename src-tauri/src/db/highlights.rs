@@ -1,4 +1,6 @@
-use rusqlite::{params, Connection};
+use std::collections::HashMap;
+
+use rusqlite::{params, params_from_iter, Connection};
 use uuid::Uuid;
 
 use crate::error::AppError;
@@ -56,6 +58,32 @@ pub fn get_highlights(
     }
 }
 
+/// Number of highlights on each of `paper_ids`, including papers with none
+pub fn count_highlights(
+    conn: &Connection,
+    paper_ids: &[String],
+) -> Result<HashMap<String, i32>, AppError> {
+    let mut counts: HashMap<String, i32> = paper_ids.iter().map(|id| (id.clone(), 0)).collect();
+    if paper_ids.is_empty() {
+        return Ok(counts);
+    }
+
+    let placeholders = vec!["?"; paper_ids.len()].join(", ");
+    let query = format!(
+        "SELECT paper_id, COUNT(*) FROM highlights WHERE paper_id IN ({}) GROUP BY paper_id",
+        placeholders
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let rows = stmt.query_map(params_from_iter(paper_ids.iter()), |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i32>(1)?))
+    })?;
+    for row in rows {
+        let (paper_id, count) = row?;
+        counts.insert(paper_id, count);
+    }
+    Ok(counts)
+}
+
 pub fn get_highlight(conn: &Connection, highlight_id: &str) -> Result<Highlight, AppError> {
     let query = format!("SELECT {} FROM highlights WHERE id = ?", SELECT_COLUMNS);
     let mut stmt = conn.prepare(&query)?;
@@ -120,3 +148,28 @@ pub fn delete_highlight(conn: &Connection, highlight_id: &str) -> Result<(), App
 
     Ok(())
 }
+
+/// Recreate a set of exported highlights on a paper, assigning new IDs
+pub fn import_highlights(
+    conn: &Connection,
+    paper_id: &str,
+    highlights: Vec<Highlight>,
+) -> Result<Vec<Highlight>, AppError> {
+    let tx = conn.unchecked_transaction()?;
+    let mut imported = Vec::new();
+
+    for highlight in highlights {
+        let input = CreateHighlightInput {
+            paper_id: paper_id.to_string(),
+            page_number: highlight.page_number,
+            rects: highlight.rects,
+            selected_text: highlight.selected_text,
+            color: Some(highlight.color),
+            note: Some(highlight.note),
+        };
+        imported.push(create_highlight(&tx, input)?);
+    }
+
+    tx.commit()?;
+    Ok(imported)
+}
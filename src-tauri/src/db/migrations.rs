@@ -1,6 +1,9 @@
 use rusqlite::Connection;
 use crate::error::AppError;
 
+/// Recorded in `PRAGMA user_version` once migrations finish; bump when adding one
+pub const SCHEMA_VERSION: i32 = 17;
+
 pub fn run(conn: &Connection) -> Result<(), AppError> {
     // Main schema
     conn.execute_batch(
@@ -177,6 +180,24 @@ pub fn run(conn: &Connection) -> Result<(), AppError> {
             created_at TEXT NOT NULL DEFAULT (datetime('now'))
         );
 
+        -- History of files imported from watch folders, also used to skip re-imports
+        CREATE TABLE IF NOT EXISTS watch_folder_imports (
+            id TEXT PRIMARY KEY,
+            watch_folder_id TEXT NOT NULL REFERENCES watch_folders(id) ON DELETE CASCADE,
+            file_name TEXT NOT NULL,
+            source_path TEXT NOT NULL,
+            paper_id TEXT REFERENCES papers(id) ON DELETE SET NULL,
+            status TEXT NOT NULL,
+            error TEXT,
+            imported_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_watch_folder_imports_folder
+            ON watch_folder_imports(watch_folder_id, imported_at DESC);
+        DROP INDEX IF EXISTS idx_watch_folder_imports_file;
+        CREATE INDEX IF NOT EXISTS idx_watch_folder_imports_source
+            ON watch_folder_imports(watch_folder_id, source_path);
+
         CREATE INDEX IF NOT EXISTS idx_watch_folders_path ON watch_folders(path);
         CREATE INDEX IF NOT EXISTS idx_watch_folders_active ON watch_folders(is_active);
 
@@ -223,6 +244,50 @@ pub fn run(conn: &Connection) -> Result<(), AppError> {
         CREATE INDEX IF NOT EXISTS idx_writing_docs_project ON writing_documents(project_id);
         CREATE INDEX IF NOT EXISTS idx_writing_docs_parent ON writing_documents(parent_id);
         CREATE INDEX IF NOT EXISTS idx_writing_docs_order ON writing_documents(project_id, sort_order);
+
+        -- Highlights quoted into writing documents
+        CREATE TABLE IF NOT EXISTS writing_document_highlights (
+            document_id TEXT NOT NULL REFERENCES writing_documents(id) ON DELETE CASCADE,
+            highlight_id TEXT NOT NULL REFERENCES highlights(id) ON DELETE CASCADE,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (document_id, highlight_id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_writing_doc_highlights_highlight ON writing_document_highlights(highlight_id);
+
+        -- Cached Semantic Scholar reference/citation DOI lists, keyed by DOI
+        CREATE TABLE IF NOT EXISTS citation_links_cache (
+            doi TEXT PRIMARY KEY,
+            reference_dois TEXT NOT NULL DEFAULT '[]',
+            citation_dois TEXT NOT NULL DEFAULT '[]',
+            fetched_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Stored PDF files keyed by SHA-256 of their content, for import dedup
+        CREATE TABLE IF NOT EXISTS pdf_files (
+            hash TEXT PRIMARY KEY,
+            pdf_path TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_pdf_files_path ON pdf_files(pdf_path);
+        CREATE INDEX IF NOT EXISTS idx_papers_pdf_path ON papers(pdf_path);
+
+        -- The most recent undoable batch operation, as JSON
+        CREATE TABLE IF NOT EXISTS operation_log (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            operation TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        -- Recent external searches, newest last
+        CREATE TABLE IF NOT EXISTS search_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            query TEXT NOT NULL,
+            source TEXT NOT NULL,
+            result_count INTEGER NOT NULL DEFAULT 0,
+            searched_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
         "#,
     )?;
 
@@ -245,5 +310,229 @@ pub fn run(conn: &Connection) -> Result<(), AppError> {
         )?;
     }
 
+    // Add reading history column to papers table if it doesn't exist
+    let has_last_opened_at: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('papers') WHERE name='last_opened_at'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_last_opened_at {
+        conn.execute_batch(
+            r#"
+            ALTER TABLE papers ADD COLUMN last_opened_at TEXT;
+            CREATE INDEX IF NOT EXISTS idx_papers_last_opened ON papers(last_opened_at DESC);
+            "#,
+        )?;
+    }
+
+    // Add DOI column to papers table if it doesn't exist
+    let has_doi: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('papers') WHERE name='doi'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_doi {
+        conn.execute("ALTER TABLE papers ADD COLUMN doi TEXT NOT NULL DEFAULT ''", [])?;
+    }
+
+    // Add abstract column to papers table if it doesn't exist
+    let has_abstract: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('papers') WHERE name='abstract_text'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_abstract {
+        conn.execute("ALTER TABLE papers ADD COLUMN abstract_text TEXT NOT NULL DEFAULT ''", [])?;
+    }
+
+    // Add link column to papers table if it doesn't exist
+    let has_url: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('papers') WHERE name='url'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_url {
+        conn.execute("ALTER TABLE papers ADD COLUMN url TEXT NOT NULL DEFAULT ''", [])?;
+    }
+
+    // Add trash timestamp to papers table if it doesn't exist
+    let has_deleted_at: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('papers') WHERE name='deleted_at'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_deleted_at {
+        conn.execute("ALTER TABLE papers ADD COLUMN deleted_at TEXT", [])?;
+    }
+
+    // Add BibTeX entry type to papers table if it doesn't exist
+    let has_bibtex_type: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('papers') WHERE name='bibtex_type'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_bibtex_type {
+        conn.execute("ALTER TABLE papers ADD COLUMN bibtex_type TEXT NOT NULL DEFAULT ''", [])?;
+    }
+
+    // Add persisted citation key to papers table if it doesn't exist
+    let has_citation_key: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('papers') WHERE name='citation_key'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_citation_key {
+        conn.execute_batch(
+            r#"
+            ALTER TABLE papers ADD COLUMN citation_key TEXT NOT NULL DEFAULT '';
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_papers_citation_key
+                ON papers(citation_key) WHERE citation_key != '';
+            "#,
+        )?;
+    }
+
+    // Add translated subject/abstract to papers table if it doesn't exist
+    let has_subject_translated: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('papers') WHERE name='subject_translated'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_subject_translated {
+        conn.execute(
+            "ALTER TABLE papers ADD COLUMN subject_translated TEXT NOT NULL DEFAULT ''",
+            [],
+        )?;
+    }
+
+    // Add import defaults to folders table if they don't exist
+    let has_folder_defaults: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('folders') WHERE name='default_tags'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_folder_defaults {
+        conn.execute_batch(
+            r#"
+            ALTER TABLE folders ADD COLUMN default_importance INTEGER;
+            ALTER TABLE folders ADD COLUMN default_tags TEXT NOT NULL DEFAULT '[]';
+            "#,
+        )?;
+    }
+
+    // Add post-import action to watch_folders if it doesn't exist
+    let has_post_import_action: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('watch_folders') WHERE name='post_import_action'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_post_import_action {
+        conn.execute(
+            "ALTER TABLE watch_folders ADD COLUMN post_import_action TEXT NOT NULL DEFAULT 'leave'",
+            [],
+        )?;
+    }
+
+    // Add cached match count to smart_groups, cleared whenever papers change
+    let has_cached_count: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('smart_groups') WHERE name='cached_count'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_cached_count {
+        conn.execute_batch(
+            r#"
+            ALTER TABLE smart_groups ADD COLUMN cached_count INTEGER;
+            ALTER TABLE smart_groups ADD COLUMN count_cached_on TEXT;
+
+            CREATE TRIGGER IF NOT EXISTS smart_groups_count_ai AFTER INSERT ON papers BEGIN
+                UPDATE smart_groups SET cached_count = NULL;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS smart_groups_count_ad AFTER DELETE ON papers BEGIN
+                UPDATE smart_groups SET cached_count = NULL;
+            END;
+
+            CREATE TRIGGER IF NOT EXISTS smart_groups_count_au AFTER UPDATE OF
+                year, author, keywords, tags, is_read, importance, is_qualitative, is_quantitative,
+                publisher, subject, pdf_path, created_at, last_analyzed_at
+            ON papers BEGIN
+                UPDATE smart_groups SET cached_count = NULL;
+            END;
+            "#,
+        )?;
+    }
+
+    // Add folder scope to smart_groups; moving or trashing a paper now changes counts too
+    let has_smart_group_scope: bool = conn
+        .query_row(
+            "SELECT COUNT(*) FROM pragma_table_info('smart_groups') WHERE name='scope_folder_ids'",
+            [],
+            |row| row.get::<_, i32>(0),
+        )
+        .map(|count| count > 0)
+        .unwrap_or(false);
+
+    if !has_smart_group_scope {
+        conn.execute_batch(
+            r#"
+            ALTER TABLE smart_groups ADD COLUMN scope_folder_ids TEXT;
+
+            DROP TRIGGER IF EXISTS smart_groups_count_au;
+            CREATE TRIGGER smart_groups_count_au AFTER UPDATE OF
+                year, author, keywords, tags, is_read, importance, is_qualitative, is_quantitative,
+                publisher, subject, pdf_path, created_at, last_analyzed_at, folder_id, deleted_at
+            ON papers BEGIN
+                UPDATE smart_groups SET cached_count = NULL;
+            END;
+            "#,
+        )?;
+    }
+
+    conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+
     Ok(())
 }
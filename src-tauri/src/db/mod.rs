@@ -7,5 +7,10 @@ pub mod settings;
 pub mod highlights;
 pub mod pdf_content;
 pub mod writing;
+pub mod citation_cache;
+pub mod secrets;
+pub mod pdf_files;
+pub mod operation_log;
+pub mod search_history;
 
 pub use connection::DbConnection;
@@ -0,0 +1,27 @@
+use rusqlite::Connection;
+
+use crate::error::AppError;
+
+/// Remember `operation` as the one to undo, replacing any earlier one
+pub fn record(conn: &Connection, operation: &str) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT OR REPLACE INTO operation_log (id, operation, created_at) VALUES (1, ?, datetime('now'))",
+        [operation],
+    )?;
+    Ok(())
+}
+
+/// Remove and return the recorded operation, if any
+pub fn take(conn: &Connection) -> Result<Option<String>, AppError> {
+    let result = conn.query_row(
+        "DELETE FROM operation_log WHERE id = 1 RETURNING operation",
+        [],
+        |row| row.get::<_, String>(0),
+    );
+
+    match result {
+        Ok(operation) => Ok(Some(operation)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
@@ -1,8 +1,10 @@
-use rusqlite::{params, Connection};
+use std::collections::HashSet;
+
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, ToSql};
 use uuid::Uuid;
 
 use crate::error::AppError;
-use crate::models::{CreatePaperInput, Paper, UpdatePaperInput};
+use crate::models::{CreatePaperInput, Paper, PaperFilter, UpdatePaperInput, BIBTEX_ENTRY_TYPES};
 
 fn parse_json_array(json: &str) -> Vec<String> {
     serde_json::from_str(json).unwrap_or_default()
@@ -46,6 +48,15 @@ fn row_to_paper(row: &rusqlite::Row) -> rusqlite::Result<Paper> {
         created_at: row.get(29)?,
         updated_at: row.get(30)?,
         last_analyzed_at: row.get(31)?,
+        last_opened_at: row.get(32)?,
+        doi: row.get(33)?,
+        abstract_text: row.get(34)?,
+        url: row.get(35)?,
+        deleted_at: row.get(36)?,
+        bibtex_type: row.get(37)?,
+        citation_key: row.get(38)?,
+        subject_translated: row.get(39)?,
+        highlight_count: row.get(40)?,
     })
 }
 
@@ -55,37 +66,84 @@ const SELECT_COLUMNS: &str = r#"
     vars_independent, vars_dependent, vars_moderator, vars_mediator, vars_others, quant_techniques,
     results, limitations, implications, future_plans,
     pdf_path, pdf_filename, user_notes, tags, is_read, importance,
-    created_at, updated_at, last_analyzed_at
+    created_at, updated_at, last_analyzed_at, last_opened_at, doi, abstract_text, url, deleted_at,
+    bibtex_type, citation_key, subject_translated,
+    (SELECT COUNT(*) FROM highlights WHERE highlights.paper_id = papers.id)
 "#;
 
+/// Build the WHERE clause and bound values for a paper filter
+fn filter_clause(filter: &PaperFilter) -> (String, Vec<Box<dyn ToSql>>) {
+    // Trashed papers only show up through `get_trashed_papers`
+    let mut conditions: Vec<&str> = vec!["deleted_at IS NULL"];
+    let mut values: Vec<Box<dyn ToSql>> = Vec::new();
+
+    if let Some(fid) = &filter.folder_id {
+        conditions.push("folder_id = ?");
+        values.push(Box::new(fid.clone()));
+    }
+
+    if let Some(is_read) = filter.is_read {
+        conditions.push("is_read = ?");
+        values.push(Box::new(is_read as i32));
+    }
+
+    if let Some(min_importance) = filter.min_importance {
+        conditions.push("importance >= ?");
+        values.push(Box::new(min_importance));
+    }
+
+    if let Some(tag) = &filter.tag {
+        // Tags are stored as a JSON array, so match against its elements
+        conditions.push(
+            "EXISTS (SELECT 1 FROM json_each(papers.tags) WHERE LOWER(json_each.value) = LOWER(?))",
+        );
+        values.push(Box::new(tag.clone()));
+    }
+
+    let where_clause = format!("WHERE {}", conditions.join(" AND "));
+
+    (where_clause, values)
+}
+
 pub fn get_papers(
     conn: &Connection,
-    folder_id: Option<String>,
+    filter: &PaperFilter,
     sort_by: Option<String>,
+    limit: Option<i32>,
+    offset: Option<i32>,
 ) -> Result<Vec<Paper>, AppError> {
     let order_clause = match sort_by.as_deref() {
         Some("name") => "ORDER BY title ASC",
         _ => "ORDER BY created_at DESC",
     };
 
-    if let Some(fid) = folder_id {
-        let query = format!(
-            "SELECT {} FROM papers WHERE folder_id = ? {}",
-            SELECT_COLUMNS, order_clause
-        );
-        let mut stmt = conn.prepare(&query)?;
-        let papers = stmt
-            .query_map([fid], row_to_paper)?
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(papers)
+    let (where_clause, mut values) = filter_clause(filter);
+
+    // SQLite needs a LIMIT before OFFSET; -1 means no limit
+    let limit_clause = if limit.is_some() || offset.is_some() {
+        values.push(Box::new(limit.unwrap_or(-1)));
+        values.push(Box::new(offset.unwrap_or(0)));
+        "LIMIT ? OFFSET ?"
     } else {
-        let query = format!("SELECT {} FROM papers {}", SELECT_COLUMNS, order_clause);
-        let mut stmt = conn.prepare(&query)?;
-        let papers = stmt
-            .query_map([], row_to_paper)?
-            .collect::<Result<Vec<_>, _>>()?;
-        Ok(papers)
-    }
+        ""
+    };
+
+    let query = format!(
+        "SELECT {} FROM papers {} {} {}",
+        SELECT_COLUMNS, where_clause, order_clause, limit_clause
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let papers = stmt
+        .query_map(params_from_iter(values.iter()), row_to_paper)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(papers)
+}
+
+pub fn count_papers(conn: &Connection, filter: &PaperFilter) -> Result<i32, AppError> {
+    let (where_clause, values) = filter_clause(filter);
+    let query = format!("SELECT COUNT(*) FROM papers {}", where_clause);
+    let count: i32 = conn.query_row(&query, params_from_iter(values.iter()), |row| row.get(0))?;
+    Ok(count)
 }
 
 pub fn get_paper(conn: &Connection, paper_id: &str) -> Result<Paper, AppError> {
@@ -96,18 +154,16 @@ pub fn get_paper(conn: &Connection, paper_id: &str) -> Result<Paper, AppError> {
         .map_err(|_| AppError::NotFound(format!("Paper not found: {}", paper_id)))
 }
 
+/// Claim the next paper number. A single statement, so concurrent callers
+/// can never be handed the same number.
 fn get_next_paper_number(conn: &Connection) -> Result<i32, AppError> {
     let number: i32 = conn.query_row(
-        "SELECT next_number FROM paper_sequence WHERE id = 1",
+        "UPDATE paper_sequence SET next_number = next_number + 1 WHERE id = 1
+         RETURNING next_number - 1",
         [],
         |row| row.get(0),
     )?;
 
-    conn.execute(
-        "UPDATE paper_sequence SET next_number = next_number + 1 WHERE id = 1",
-        [],
-    )?;
-
     Ok(number)
 }
 
@@ -146,6 +202,26 @@ pub fn update_paper(
     let paper = get_paper(conn, paper_id)?;
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
+    let bibtex_type = match input.bibtex_type {
+        Some(entry_type) => {
+            let entry_type = entry_type.trim().to_lowercase();
+            if !entry_type.is_empty() && !BIBTEX_ENTRY_TYPES.contains(&entry_type.as_str()) {
+                return Err(AppError::Validation(format!(
+                    "Unknown BibTeX entry type: {}",
+                    entry_type
+                )));
+            }
+            entry_type
+        }
+        None => paper.bibtex_type.clone(),
+    };
+
+    // A new PDF invalidates the full-text index built from the old one
+    let pdf_changed = input
+        .pdf_path
+        .as_ref()
+        .is_some_and(|path| *path != paper.pdf_path);
+
     conn.execute(
         r#"UPDATE papers SET
             folder_id = ?,
@@ -155,6 +231,10 @@ pub fn update_paper(
             title = ?,
             publisher = ?,
             subject = ?,
+            doi = ?,
+            abstract_text = ?,
+            url = ?,
+            bibtex_type = ?,
             purposes = ?,
             is_qualitative = ?,
             is_quantitative = ?,
@@ -186,6 +266,10 @@ pub fn update_paper(
             input.title.unwrap_or(paper.title),
             input.publisher.unwrap_or(paper.publisher),
             input.subject.unwrap_or(paper.subject),
+            input.doi.unwrap_or(paper.doi),
+            input.abstract_text.unwrap_or(paper.abstract_text),
+            input.url.unwrap_or(paper.url),
+            bibtex_type,
             to_json_array(&input.purposes.unwrap_or(paper.purposes)),
             input.is_qualitative.unwrap_or(paper.is_qualitative) as i32,
             input.is_quantitative.unwrap_or(paper.is_quantitative) as i32,
@@ -212,20 +296,225 @@ pub fn update_paper(
         ],
     )?;
 
+    if pdf_changed {
+        crate::db::pdf_content::mark_paper_unindexed(conn, paper_id)?;
+    }
+
     get_paper(conn, paper_id)
 }
 
+/// Permanently remove a paper. The caller releases its PDF.
 pub fn delete_paper(conn: &Connection, paper_id: &str) -> Result<(), AppError> {
     get_paper(conn, paper_id)?;
     conn.execute("DELETE FROM papers WHERE id = ?", [paper_id])?;
     Ok(())
 }
 
+/// Whether a paper other than `paper_id` already uses a citation key
+pub fn citation_key_taken(conn: &Connection, key: &str, paper_id: &str) -> Result<bool, AppError> {
+    let count: i32 = conn.query_row(
+        "SELECT COUNT(*) FROM papers WHERE citation_key = ? AND id != ?",
+        params![key, paper_id],
+        |row| row.get(0),
+    )?;
+    Ok(count > 0)
+}
+
+pub fn set_citation_key(conn: &Connection, paper_id: &str, key: &str) -> Result<(), AppError> {
+    conn.execute(
+        "UPDATE papers SET citation_key = ? WHERE id = ?",
+        params![key, paper_id],
+    )?;
+    Ok(())
+}
+
+pub fn set_subject_translated(conn: &Connection, paper_id: &str, text: &str) -> Result<(), AppError> {
+    conn.execute(
+        "UPDATE papers SET subject_translated = ? WHERE id = ?",
+        params![text, paper_id],
+    )?;
+    Ok(())
+}
+
+/// Set one flag column on several papers in a single transaction, failing
+/// without changes if any paper is missing. Returns the affected folder ids.
+fn set_for_papers(
+    conn: &Connection,
+    paper_ids: &[String],
+    column: &str,
+    value: i32,
+) -> Result<HashSet<String>, AppError> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let tx = conn.unchecked_transaction()?;
+    let mut folders = HashSet::new();
+    {
+        let sql = format!(
+            "UPDATE papers SET {} = ?, updated_at = ? WHERE id = ? RETURNING folder_id",
+            column
+        );
+        let mut stmt = tx.prepare(&sql)?;
+        for paper_id in paper_ids {
+            let folder_id: String = stmt
+                .query_row(params![value, now, paper_id], |row| row.get(0))
+                .optional()?
+                .ok_or_else(|| AppError::NotFound(format!("Paper not found: {}", paper_id)))?;
+            folders.insert(folder_id);
+        }
+    }
+    tx.commit()?;
+    Ok(folders)
+}
+
+/// Mark several papers read or unread. Returns the affected folder ids.
+pub fn set_read_status(
+    conn: &Connection,
+    paper_ids: &[String],
+    is_read: bool,
+) -> Result<HashSet<String>, AppError> {
+    set_for_papers(conn, paper_ids, "is_read", is_read as i32)
+}
+
+/// Set the importance (0 for unrated, up to 5) of several papers. Returns the affected folder ids.
+pub fn set_importance(
+    conn: &Connection,
+    paper_ids: &[String],
+    importance: i32,
+) -> Result<HashSet<String>, AppError> {
+    if !(0..=5).contains(&importance) {
+        return Err(AppError::Validation(format!(
+            "Importance must be between 0 and 5, got {}",
+            importance
+        )));
+    }
+    set_for_papers(conn, paper_ids, "importance", importance)
+}
+
+/// Move a paper to the trash, keeping its PDF and analysis
+pub fn trash_paper(conn: &Connection, paper_id: &str) -> Result<(), AppError> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let affected = conn.execute(
+        "UPDATE papers SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL",
+        params![now, paper_id],
+    )?;
+
+    if affected == 0 {
+        return Err(AppError::NotFound(format!("Paper not found: {}", paper_id)));
+    }
+    Ok(())
+}
+
+pub fn restore_paper(conn: &Connection, paper_id: &str) -> Result<Paper, AppError> {
+    let affected = conn.execute(
+        "UPDATE papers SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL",
+        [paper_id],
+    )?;
+
+    if affected == 0 {
+        return Err(AppError::NotFound(format!("Paper not in trash: {}", paper_id)));
+    }
+    get_paper(conn, paper_id)
+}
+
+/// Get trashed papers, most recently deleted first
+pub fn get_trashed_papers(conn: &Connection) -> Result<Vec<Paper>, AppError> {
+    let query = format!(
+        "SELECT {} FROM papers WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+        SELECT_COLUMNS
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let papers = stmt
+        .query_map([], row_to_paper)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(papers)
+}
+
+/// Papers whose author field contains `term` (ASCII case-insensitive), by year
+/// with undated papers last
+pub fn get_papers_by_author_text(conn: &Connection, term: &str) -> Result<Vec<Paper>, AppError> {
+    let pattern = format!(
+        "%{}%",
+        term.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+    );
+    let query = format!(
+        r#"SELECT {} FROM papers
+           WHERE deleted_at IS NULL AND author LIKE ? ESCAPE '\'
+           ORDER BY year = 0, year, title"#,
+        SELECT_COLUMNS
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let papers = stmt
+        .query_map([pattern], row_to_paper)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(papers)
+}
+
+/// Get (id, title, folder_id, pdf_path) for every paper with a PDF attached
+pub fn get_papers_with_pdf(
+    conn: &Connection,
+) -> Result<Vec<(String, String, String, String)>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, title, folder_id, pdf_path FROM papers WHERE pdf_path IS NOT NULL AND pdf_path != ''",
+    )?;
+    let papers = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(papers)
+}
+
+/// Point a paper at the same PDF in a new location, without touching its index state
+pub fn update_pdf_location(conn: &Connection, paper_id: &str, pdf_path: &str) -> Result<(), AppError> {
+    conn.execute(
+        "UPDATE papers SET pdf_path = ? WHERE id = ?",
+        params![pdf_path, paper_id],
+    )?;
+    Ok(())
+}
+
+pub fn update_paper_last_opened(conn: &Connection, paper_id: &str) -> Result<(), AppError> {
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let affected = conn.execute(
+        "UPDATE papers SET last_opened_at = ? WHERE id = ?",
+        params![now, paper_id],
+    )?;
+
+    if affected == 0 {
+        return Err(AppError::NotFound(format!("Paper not found: {}", paper_id)));
+    }
+
+    Ok(())
+}
+
+pub fn get_recently_opened(conn: &Connection, limit: i32) -> Result<Vec<Paper>, AppError> {
+    let query = format!(
+        "SELECT {} FROM papers WHERE last_opened_at IS NOT NULL AND deleted_at IS NULL
+         ORDER BY last_opened_at DESC LIMIT ?",
+        SELECT_COLUMNS
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let papers = stmt
+        .query_map([limit], row_to_paper)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(papers)
+}
+
 pub fn check_duplicate(conn: &Connection, title: &str) -> Result<bool, AppError> {
     let count: i32 = conn.query_row(
-        "SELECT COUNT(*) FROM papers WHERE title = ?",
+        "SELECT COUNT(*) FROM papers WHERE title = ? AND deleted_at IS NULL",
         [title],
         |row| row.get(0),
     )?;
     Ok(count > 0)
 }
+
+/// The oldest paper outside the trash that uses the stored PDF at `pdf_path`
+pub fn find_paper_by_pdf_path(
+    conn: &Connection,
+    pdf_path: &str,
+) -> Result<Option<Paper>, AppError> {
+    let query = format!(
+        r#"SELECT {} FROM papers WHERE pdf_path = ? AND deleted_at IS NULL
+           ORDER BY created_at LIMIT 1"#,
+        SELECT_COLUMNS
+    );
+    Ok(conn.query_row(&query, [pdf_path], row_to_paper).optional()?)
+}
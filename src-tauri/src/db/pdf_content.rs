@@ -1,7 +1,97 @@
 use rusqlite::{params, Connection};
+use std::collections::HashMap;
 use uuid::Uuid;
 use crate::error::AppError;
-use crate::models::{PdfPage, FullTextSearchResult, FullTextSearchQuery, FullTextSearchResponse};
+use crate::models::{PdfPage, FullTextSearchResult, FullTextSearchQuery, FullTextSearchResponse, FtsRebuildReport, IndexingProgress};
+
+/// Tokenizers the `pdf_pages_fts` index can be built with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FtsTokenizer {
+    /// Word-based; fine for space-separated languages
+    Unicode61,
+    /// Substring matching on character trigrams, for Korean and other CJK text
+    Trigram,
+}
+
+impl FtsTokenizer {
+    /// Parse the `fts_tokenizer` setting, defaulting to `unicode61`
+    pub fn from_setting(value: Option<&str>) -> Self {
+        match value {
+            Some("trigram") => Self::Trigram,
+            _ => Self::Unicode61,
+        }
+    }
+
+    /// The value used for this tokenizer in the `fts_tokenizer` setting
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Unicode61 => "unicode61",
+            Self::Trigram => "trigram",
+        }
+    }
+
+    fn tokenize_option(self) -> &'static str {
+        match self {
+            Self::Unicode61 => "unicode61 remove_diacritics 2",
+            Self::Trigram => "trigram",
+        }
+    }
+}
+
+/// The tokenizer the current `pdf_pages_fts` table was created with
+pub fn current_fts_tokenizer(conn: &Connection) -> Result<FtsTokenizer, AppError> {
+    let sql: String = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'pdf_pages_fts'",
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(if sql.contains("'trigram'") {
+        FtsTokenizer::Trigram
+    } else {
+        FtsTokenizer::Unicode61
+    })
+}
+
+/// Recreate `pdf_pages_fts` with another tokenizer and re-index every stored page.
+/// The sync triggers refer to the table by name, so they keep working.
+pub fn recreate_fts_index(conn: &Connection, tokenizer: FtsTokenizer) -> Result<(), AppError> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute_batch(&format!(
+        r#"
+        DROP TABLE IF EXISTS pdf_pages_fts;
+        CREATE VIRTUAL TABLE pdf_pages_fts USING fts5(
+            text_content,
+            content='pdf_pages',
+            content_rowid='rowid',
+            tokenize='{}'
+        );
+        INSERT INTO pdf_pages_fts(pdf_pages_fts) VALUES('rebuild');
+        "#,
+        tokenizer.tokenize_option()
+    ))?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Rebuild the FTS index in place from `pdf_pages`, for when the two have
+/// drifted apart (e.g. rows changed with the sync triggers bypassed), then
+/// check that they agree
+pub fn rebuild_fts_index(conn: &Connection) -> Result<FtsRebuildReport, AppError> {
+    conn.execute("INSERT INTO pdf_pages_fts(pdf_pages_fts) VALUES('rebuild')", [])?;
+
+    let page_count: i64 = conn.query_row("SELECT COUNT(*) FROM pdf_pages", [], |row| row.get(0))?;
+    let indexed_count: i64 =
+        conn.query_row("SELECT COUNT(*) FROM pdf_pages_fts_docsize", [], |row| row.get(0))?;
+    let integrity_ok = conn
+        .execute("INSERT INTO pdf_pages_fts(pdf_pages_fts) VALUES('integrity-check')", [])
+        .is_ok();
+
+    Ok(FtsRebuildReport {
+        page_count,
+        indexed_count,
+        in_sync: integrity_ok && page_count == indexed_count,
+    })
+}
 
 /// Insert or replace a page's text content
 pub fn insert_pdf_page(
@@ -28,6 +118,26 @@ pub fn insert_pdf_page(
     })
 }
 
+/// Get a paper's indexed pages in page order
+pub fn get_pdf_pages(conn: &Connection, paper_id: &str) -> Result<Vec<PdfPage>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT id, paper_id, page_number, text_content, created_at
+         FROM pdf_pages WHERE paper_id = ? ORDER BY page_number",
+    )?;
+    let pages = stmt
+        .query_map([paper_id], |row| {
+            Ok(PdfPage {
+                id: row.get(0)?,
+                paper_id: row.get(1)?,
+                page_number: row.get(2)?,
+                text_content: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(pages)
+}
+
 /// Delete all pages for a paper (for re-indexing)
 pub fn delete_pdf_pages(conn: &Connection, paper_id: &str) -> Result<(), AppError> {
     conn.execute("DELETE FROM pdf_pages WHERE paper_id = ?", [paper_id])?;
@@ -44,6 +154,45 @@ pub fn mark_paper_indexed(conn: &Connection, paper_id: &str) -> Result<(), AppEr
     Ok(())
 }
 
+/// Mark paper as needing re-indexing (e.g. after its PDF changed)
+pub fn mark_paper_unindexed(conn: &Connection, paper_id: &str) -> Result<(), AppError> {
+    conn.execute(
+        "UPDATE papers SET is_indexed = 0, indexed_at = NULL WHERE id = ?",
+        [paper_id],
+    )?;
+    Ok(())
+}
+
+/// Full page text with each match wrapped in control characters that never
+/// occur in extracted text, so match positions can be recovered exactly
+const HIGHLIGHTED_TEXT_COLUMN: &str = "highlight(pdf_pages_fts, 0, char(1), char(2))";
+
+/// Ranges of the matches marked in a `HIGHLIGHTED_TEXT_COLUMN` value, relative
+/// to the unmarked text. Offsets count UTF-16 code units, as JavaScript string
+/// indices do, so characters outside the BMP (e.g. math italic letters) count twice.
+fn match_offsets(highlighted: &str) -> Vec<(usize, usize)> {
+    let mut offsets = Vec::new();
+    let mut position = 0;
+    let mut start = None;
+    for c in highlighted.chars() {
+        match c {
+            '\u{1}' => start = Some(position),
+            '\u{2}' => {
+                if let Some(start) = start.take() {
+                    offsets.push((start, position));
+                }
+            }
+            _ => position += c.len_utf16(),
+        }
+    }
+    offsets
+}
+
+const DEFAULT_SNIPPET_TOKENS: i32 = 32;
+
+/// FTS5 caps snippets at 64 tokens
+const MAX_SNIPPET_TOKENS: i32 = 64;
+
 /// Full-text search with snippet extraction
 pub fn search_pdf_content(
     conn: &Connection,
@@ -51,128 +200,151 @@ pub fn search_pdf_content(
 ) -> Result<FullTextSearchResponse, AppError> {
     let limit = query.limit.unwrap_or(20).min(100);
     let offset = query.offset.unwrap_or(0);
+    let include_offsets = query.include_offsets.unwrap_or(false);
+    let snippet_tokens = query
+        .snippet_tokens
+        .unwrap_or(DEFAULT_SNIPPET_TOKENS)
+        .clamp(1, MAX_SNIPPET_TOKENS);
+    let mark_start = query.mark_start.as_deref().unwrap_or("<mark>");
+    let mark_end = query.mark_end.as_deref().unwrap_or("</mark>");
 
     // Sanitize query for FTS5
-    let search_query = sanitize_fts_query(&query.query);
+    let search_query = sanitize_fts_query(&query.query, current_fts_tokenizer(conn)?);
     if search_query.is_empty() {
         return Ok(FullTextSearchResponse { total: 0, results: vec![] });
     }
 
-    let (results, total) = match &query.folder_id {
-        Some(folder_id) => search_with_folder(conn, &search_query, folder_id, limit, offset)?,
-        None => search_all(conn, &search_query, limit, offset)?,
-    };
-
-    Ok(FullTextSearchResponse { total, results })
-}
+    // Optional scopes, applied on top of the FTS match
+    let mut filters = String::new();
+    let mut filter_values: Vec<&str> = Vec::new();
+    if let Some(folder_id) = &query.folder_id {
+        filters.push_str(" AND p.folder_id = ?");
+        filter_values.push(folder_id);
+    }
+    if let Some(paper_id) = &query.paper_id {
+        filters.push_str(" AND pp.paper_id = ?");
+        filter_values.push(paper_id);
+    }
 
-fn search_with_folder(
-    conn: &Connection,
-    search_query: &str,
-    folder_id: &str,
-    limit: i32,
-    offset: i32,
-) -> Result<(Vec<FullTextSearchResult>, i32), AppError> {
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         r#"
         SELECT
             pp.paper_id,
             p.title,
             p.author,
             pp.page_number,
-            snippet(pdf_pages_fts, 0, '<mark>', '</mark>', '...', 32) as snippet,
-            bm25(pdf_pages_fts) as rank
+            snippet(pdf_pages_fts, 0, ?, ?, '...', {}) as snippet,
+            bm25(pdf_pages_fts) as rank,
+            {}
         FROM pdf_pages_fts
         JOIN pdf_pages pp ON pdf_pages_fts.rowid = pp.rowid
-        JOIN papers p ON pp.paper_id = p.id
-        WHERE pdf_pages_fts MATCH ?
-        AND p.folder_id = ?
+        JOIN papers p ON pp.paper_id = p.id AND p.deleted_at IS NULL
+        WHERE pdf_pages_fts MATCH ?{}
         ORDER BY rank
         LIMIT ? OFFSET ?
         "#,
-    )?;
-
-    let mut results = Vec::new();
-    let rows = stmt.query_map(params![search_query, folder_id, limit, offset], |row| {
-        Ok(FullTextSearchResult {
-            paper_id: row.get(0)?,
-            paper_title: row.get(1)?,
-            paper_author: row.get(2)?,
-            page_number: row.get(3)?,
-            snippet: row.get(4)?,
-            rank: row.get(5)?,
-        })
-    })?;
-
-    for result in rows {
-        results.push(result?);
-    }
+        snippet_tokens,
+        if include_offsets {
+            HIGHLIGHTED_TEXT_COLUMN
+        } else {
+            "NULL"
+        },
+        filters
+    ))?;
 
+    let mut values: Vec<&dyn rusqlite::ToSql> = vec![&search_query];
+    values.extend(filter_values.iter().map(|v| v as &dyn rusqlite::ToSql));
     let total: i32 = conn.query_row(
-        r#"SELECT COUNT(*) FROM pdf_pages_fts
-           JOIN pdf_pages pp ON pdf_pages_fts.rowid = pp.rowid
-           JOIN papers p ON pp.paper_id = p.id
-           WHERE pdf_pages_fts MATCH ? AND p.folder_id = ?"#,
-        params![search_query, folder_id],
+        &format!(
+            r#"SELECT COUNT(*) FROM pdf_pages_fts
+               JOIN pdf_pages pp ON pdf_pages_fts.rowid = pp.rowid
+               JOIN papers p ON pp.paper_id = p.id AND p.deleted_at IS NULL
+               WHERE pdf_pages_fts MATCH ?{}"#,
+            filters
+        ),
+        values.as_slice(),
         |r| r.get(0),
     )?;
 
-    Ok((results, total))
+    // The snippet marks come first, in the SELECT list
+    let mut values: Vec<&dyn rusqlite::ToSql> = [&mark_start as &dyn rusqlite::ToSql, &mark_end]
+        .into_iter()
+        .chain(values)
+        .collect();
+    values.push(&limit);
+    values.push(&offset);
+    let results = stmt
+        .query_map(values.as_slice(), |row| {
+            Ok(FullTextSearchResult {
+                paper_id: row.get(0)?,
+                paper_title: row.get(1)?,
+                paper_author: row.get(2)?,
+                page_number: row.get(3)?,
+                snippet: row.get(4)?,
+                rank: row.get(5)?,
+                offsets: row
+                    .get::<_, Option<String>>(6)?
+                    .map(|highlighted| match_offsets(&highlighted)),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(FullTextSearchResponse { total, results })
 }
 
-fn search_all(
+/// Score indexed papers by how well their content matches any of the given terms.
+/// Returns paper ID to best (most negative) bm25 rank.
+pub fn match_papers_by_terms(
     conn: &Connection,
-    search_query: &str,
-    limit: i32,
-    offset: i32,
-) -> Result<(Vec<FullTextSearchResult>, i32), AppError> {
+    terms: &[String],
+) -> Result<HashMap<String, f64>, AppError> {
+    let tokenizer = current_fts_tokenizer(conn)?;
+    let search_query = terms
+        .iter()
+        .map(|term| sanitize_fts_query(term, tokenizer))
+        .filter(|term| !term.is_empty())
+        .collect::<Vec<_>>()
+        .join(" OR ");
+
+    if search_query.is_empty() {
+        return Ok(HashMap::new());
+    }
+
     let mut stmt = conn.prepare(
         r#"
-        SELECT
-            pp.paper_id,
-            p.title,
-            p.author,
-            pp.page_number,
-            snippet(pdf_pages_fts, 0, '<mark>', '</mark>', '...', 32) as snippet,
-            bm25(pdf_pages_fts) as rank
+        SELECT pp.paper_id, MIN(bm25(pdf_pages_fts)) as rank
         FROM pdf_pages_fts
         JOIN pdf_pages pp ON pdf_pages_fts.rowid = pp.rowid
-        JOIN papers p ON pp.paper_id = p.id
         WHERE pdf_pages_fts MATCH ?
-        ORDER BY rank
-        LIMIT ? OFFSET ?
+        GROUP BY pp.paper_id
         "#,
     )?;
 
-    let mut results = Vec::new();
-    let rows = stmt.query_map(params![search_query, limit, offset], |row| {
-        Ok(FullTextSearchResult {
-            paper_id: row.get(0)?,
-            paper_title: row.get(1)?,
-            paper_author: row.get(2)?,
-            page_number: row.get(3)?,
-            snippet: row.get(4)?,
-            rank: row.get(5)?,
-        })
-    })?;
+    let ranks = stmt
+        .query_map([search_query], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?))
+        })?
+        .collect::<Result<HashMap<_, _>, _>>()?;
 
-    for result in rows {
-        results.push(result?);
-    }
+    Ok(ranks)
+}
 
-    let total: i32 = conn.query_row(
-        "SELECT COUNT(*) FROM pdf_pages_fts WHERE pdf_pages_fts MATCH ?",
-        params![search_query],
-        |r| r.get(0),
+/// Count papers with a PDF outside the trash, and how many of them are indexed
+pub fn get_indexing_progress(conn: &Connection) -> Result<IndexingProgress, AppError> {
+    let (indexed, total) = conn.query_row(
+        "SELECT COALESCE(SUM(COALESCE(is_indexed, 0) = 1), 0), COUNT(*) FROM papers
+         WHERE pdf_path != '' AND deleted_at IS NULL",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
     )?;
-
-    Ok((results, total))
+    Ok(IndexingProgress { indexed, total })
 }
 
-/// Get papers that haven't been indexed yet
+/// Get papers outside the trash that haven't been indexed yet
 pub fn get_unindexed_papers(conn: &Connection) -> Result<Vec<(String, String)>, AppError> {
     let mut stmt = conn.prepare(
-        "SELECT id, pdf_path FROM papers WHERE COALESCE(is_indexed, 0) = 0 AND pdf_path != ''"
+        "SELECT id, pdf_path FROM papers
+         WHERE COALESCE(is_indexed, 0) = 0 AND pdf_path != '' AND deleted_at IS NULL",
     )?;
 
     let mut papers = Vec::new();
@@ -188,13 +360,23 @@ pub fn get_unindexed_papers(conn: &Connection) -> Result<Vec<(String, String)>,
 }
 
 /// Sanitize user input for FTS5 query
-fn sanitize_fts_query(query: &str) -> String {
+fn sanitize_fts_query(query: &str, tokenizer: FtsTokenizer) -> String {
     // Remove special FTS5 operators and wrap each word in quotes for literal matching
     let cleaned: String = query
         .chars()
         .filter(|c| c.is_alphanumeric() || c.is_whitespace() || *c == '-' || *c == '_')
         .collect();
 
+    // Trigram indexes match substrings, so the query is searched as one literal
+    // string rather than as separate words. Fewer than 3 characters can't match.
+    if tokenizer == FtsTokenizer::Trigram {
+        let phrase = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+        if phrase.chars().count() < 3 {
+            return String::new();
+        }
+        return format!("\"{}\"", phrase);
+    }
+
     // Split into words and join with spaces
     cleaned
         .split_whitespace()
@@ -202,3 +384,20 @@ fn sanitize_fts_query(query: &str) -> String {
         .collect::<Vec<_>>()
         .join(" ")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_offsets() {
+        assert_eq!(
+            match_offsets("a \u{1}neural\u{2} net, \u{1}neural\u{2}"),
+            vec![(2, 8), (14, 20)]
+        );
+        assert!(match_offsets("no matches here").is_empty());
+
+        // 𝑥 (U+1D465) is two UTF-16 code units
+        assert_eq!(match_offsets("𝑥 = \u{1}loss\u{2}"), vec![(5, 9)]);
+    }
+}
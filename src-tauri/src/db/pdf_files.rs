@@ -0,0 +1,58 @@
+use rusqlite::{params, Connection};
+
+use crate::error::AppError;
+
+/// Find the stored path of a PDF with the given content hash
+pub fn find_by_hash(conn: &Connection, hash: &str) -> Result<Option<String>, AppError> {
+    let result = conn.query_row(
+        "SELECT pdf_path FROM pdf_files WHERE hash = ?",
+        [hash],
+        |row| row.get::<_, String>(0),
+    );
+
+    match result {
+        Ok(path) => Ok(Some(path)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) => Err(AppError::Database(e.to_string())),
+    }
+}
+
+/// Record the stored path for a PDF content hash
+pub fn register(conn: &Connection, hash: &str, pdf_path: &str) -> Result<(), AppError> {
+    conn.execute(
+        "INSERT OR REPLACE INTO pdf_files (hash, pdf_path) VALUES (?, ?)",
+        params![hash, pdf_path],
+    )?;
+    Ok(())
+}
+
+/// Number of papers that reference a stored PDF path
+pub fn count_references(conn: &Connection, pdf_path: &str) -> Result<i32, AppError> {
+    let count = conn.query_row(
+        "SELECT COUNT(*) FROM papers WHERE pdf_path = ?",
+        [pdf_path],
+        |row| row.get(0),
+    )?;
+    Ok(count)
+}
+
+/// Forget a stored PDF once its file has been removed
+pub fn remove_path(conn: &Connection, pdf_path: &str) -> Result<(), AppError> {
+    conn.execute("DELETE FROM pdf_files WHERE pdf_path = ?", [pdf_path])?;
+    Ok(())
+}
+
+/// Move a stored PDF, updating every paper that shares it
+pub fn rename_path(conn: &Connection, old_path: &str, new_path: &str) -> Result<(), AppError> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "UPDATE pdf_files SET pdf_path = ? WHERE pdf_path = ?",
+        params![new_path, old_path],
+    )?;
+    tx.execute(
+        "UPDATE papers SET pdf_path = ? WHERE pdf_path = ?",
+        params![new_path, old_path],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::error::AppError;
+use crate::models::{SearchHistoryEntry, SearchSource};
+
+/// Searches kept; older ones are dropped as new ones are logged
+const MAX_ENTRIES: i64 = 200;
+
+/// Stored name of a source, the same as in the API (e.g. `semantic_scholar`)
+fn source_name(source: SearchSource) -> Result<String, AppError> {
+    Ok(serde_json::to_value(source)?
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// Log a search. Repeating the latest search only refreshes its entry.
+pub fn record(
+    conn: &Connection,
+    query: &str,
+    source: SearchSource,
+    result_count: i32,
+) -> Result<(), AppError> {
+    let query = query.trim();
+    let source = source_name(source)?;
+
+    let tx = conn.unchecked_transaction()?;
+    let latest: Option<(i64, String, String)> = tx
+        .query_row(
+            "SELECT id, query, source FROM search_history ORDER BY id DESC LIMIT 1",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+
+    match latest {
+        Some((id, last_query, last_source)) if last_query == query && last_source == source => {
+            tx.execute(
+                r#"UPDATE search_history SET result_count = ?, searched_at = datetime('now')
+                   WHERE id = ?"#,
+                params![result_count, id],
+            )?;
+        }
+        _ => {
+            tx.execute(
+                "INSERT INTO search_history (query, source, result_count) VALUES (?, ?, ?)",
+                params![query, source, result_count],
+            )?;
+        }
+    }
+
+    tx.execute(
+        r#"DELETE FROM search_history WHERE id NOT IN
+           (SELECT id FROM search_history ORDER BY id DESC LIMIT ?)"#,
+        [MAX_ENTRIES],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// The most recent searches, newest first
+pub fn list(conn: &Connection, limit: i64) -> Result<Vec<SearchHistoryEntry>, AppError> {
+    let mut stmt = conn.prepare(
+        r#"SELECT id, query, source, result_count, searched_at FROM search_history
+           ORDER BY id DESC LIMIT ?"#,
+    )?;
+    let rows = stmt
+        .query_map([limit], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i32>(3)?,
+                row.get::<_, String>(4)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Entries for a source that no longer exists are left out
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, query, source, result_count, searched_at)| {
+            let source = serde_json::from_value(serde_json::Value::String(source)).ok()?;
+            Some(SearchHistoryEntry {
+                id,
+                query,
+                source,
+                result_count,
+                searched_at,
+            })
+        })
+        .collect())
+}
+
+pub fn clear(conn: &Connection) -> Result<(), AppError> {
+    conn.execute("DELETE FROM search_history", [])?;
+    Ok(())
+}
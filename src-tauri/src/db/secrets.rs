@@ -0,0 +1,129 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::error::AppError;
+
+// Key file lives next to the database but is never part of the Drive backup,
+// so a copied papers.db can't be decrypted elsewhere
+const KEY_FILE_NAME: &str = "secret.key";
+const KEY_LEN: usize = 32;
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+static SECRET_KEY: Mutex<Option<[u8; KEY_LEN]>> = Mutex::new(None);
+
+/// Whether a setting key holds a credential that must be encrypted at rest
+pub fn is_sensitive(key: &str) -> bool {
+    key.ends_with("_api_key")
+        || key.ends_with("_secret")
+        || key == "google_access_token"
+        || key == "google_refresh_token"
+        || key == "serpapi_key"
+}
+
+/// Load the machine-bound encryption key from the app data directory, creating it if needed
+pub fn init(app_data: &Path) -> Result<(), AppError> {
+    let key_path = app_data.join(KEY_FILE_NAME);
+
+    let key = if key_path.exists() {
+        let bytes = std::fs::read(&key_path)?;
+        <[u8; KEY_LEN]>::try_from(bytes.as_slice())
+            .map_err(|_| AppError::Io("Invalid encryption key file".to_string()))?
+    } else {
+        let mut key = [0u8; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut key)
+            .map_err(|_| AppError::Io("Failed to generate encryption key".to_string()))?;
+        write_key_file(&key_path, &key)?;
+        key
+    };
+
+    *SECRET_KEY.lock().unwrap() = Some(key);
+    Ok(())
+}
+
+#[cfg(unix)]
+fn write_key_file(path: &Path, key: &[u8]) -> Result<(), AppError> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(key)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn write_key_file(path: &Path, key: &[u8]) -> Result<(), AppError> {
+    std::fs::write(path, key)?;
+    Ok(())
+}
+
+fn cipher() -> Result<LessSafeKey, AppError> {
+    let key = SECRET_KEY
+        .lock()
+        .unwrap()
+        .ok_or_else(|| AppError::Io("Encryption key not initialized".to_string()))?;
+    let unbound = UnboundKey::new(&AES_256_GCM, &key)
+        .map_err(|_| AppError::Io("Invalid encryption key".to_string()))?;
+    Ok(LessSafeKey::new(unbound))
+}
+
+/// Whether a stored value was produced by `encrypt`
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Encrypt a value with AES-256-GCM, returning `enc:v1:<base64(nonce || ciphertext)>`
+pub fn encrypt(plaintext: &str) -> Result<String, AppError> {
+    let cipher = cipher()?;
+
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce_bytes)
+        .map_err(|_| AppError::Io("Failed to generate nonce".to_string()))?;
+
+    let mut in_out = plaintext.as_bytes().to_vec();
+    cipher
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce_bytes),
+            Aad::empty(),
+            &mut in_out,
+        )
+        .map_err(|_| AppError::Io("Failed to encrypt value".to_string()))?;
+
+    let mut payload = nonce_bytes.to_vec();
+    payload.extend_from_slice(&in_out);
+    Ok(format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(payload)))
+}
+
+/// Decrypt a value produced by `encrypt`; values without the prefix are returned unchanged
+pub fn decrypt(stored: &str) -> Result<String, AppError> {
+    let encoded = match stored.strip_prefix(ENCRYPTED_PREFIX) {
+        Some(encoded) => encoded,
+        None => return Ok(stored.to_string()),
+    };
+
+    let payload = STANDARD
+        .decode(encoded)
+        .map_err(|e| AppError::Parse(e.to_string()))?;
+    if payload.len() < NONCE_LEN {
+        return Err(AppError::Parse("Encrypted value is truncated".to_string()));
+    }
+
+    let (nonce_bytes, ciphertext) = payload.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce_bytes)
+        .map_err(|_| AppError::Parse("Invalid nonce".to_string()))?;
+
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = cipher()?
+        .open_in_place(nonce, Aad::empty(), &mut in_out)
+        .map_err(|_| AppError::Auth("Stored secret could not be decrypted".to_string()))?;
+
+    String::from_utf8(plaintext.to_vec()).map_err(|e| AppError::Parse(e.to_string()))
+}
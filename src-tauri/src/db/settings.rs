@@ -1,20 +1,176 @@
 use rusqlite::Connection;
+use crate::db::secrets;
 use crate::error::AppError;
 
+// Decrypt a stored value for sensitive keys. Secrets that can't be decrypted
+// (e.g. a database restored on another machine) are treated as unset.
+fn reveal(key: &str, value: String) -> Option<String> {
+    if !secrets::is_sensitive(key) {
+        return Some(value);
+    }
+
+    match secrets::decrypt(&value) {
+        Ok(plaintext) => Some(plaintext),
+        Err(e) => {
+            log::warn!("Ignoring undecryptable setting {}: {}", key, e);
+            None
+        }
+    }
+}
+
+/// Settings holding whole numbers, with their allowed range
+const INTEGER_SETTINGS: &[(&str, i32, i32)] = &[
+    ("default_font_size", 8, 48),
+    ("http_timeout_secs", 1, 600),
+    ("auto_backup_interval_hours", 0, 24 * 30),
+    ("auto_analyze_delay_secs", 0, 3600),
+    ("rename_max_title_length", 10, 255),
+    ("oauth_redirect_port", 1024, 65535),
+    ("indexing_concurrency", 1, 16),
+    ("analysis_max_output_tokens", 256, 8192),
+];
+
+/// Settings holding decimal numbers, with their allowed range
+const FLOAT_SETTINGS: &[(&str, f32, f32)] = &[
+    ("analysis_temperature", 0.0, 2.0),
+    ("text_temperature", 0.0, 2.0),
+];
+
+/// Settings holding `true`/`false`
+const BOOL_SETTINGS: &[&str] = &["rename_lowercase", "auto_rename_after_analysis"];
+
+/// Settings restricted to a fixed set of values
+const CHOICE_SETTINGS: &[(&str, &[&str])] = &[
+    ("fts_tokenizer", &["unicode61", "trigram"]),
+    ("default_citation_style", &["apa", "mla", "chicago", "harvard"]),
+];
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "true" | "1" | "yes" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Check a value against the known type of its setting. Unknown keys accept
+/// any value, and an empty value always means "unset".
+pub fn validate_setting(key: &str, value: &str) -> Result<(), AppError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(());
+    }
+
+    if let Some((_, min, max)) = INTEGER_SETTINGS.iter().find(|(k, _, _)| *k == key) {
+        let number: i32 = value.parse().map_err(|_| {
+            AppError::Validation(format!("{} must be a whole number, got \"{}\"", key, value))
+        })?;
+        if !(*min..=*max).contains(&number) {
+            return Err(AppError::Validation(format!(
+                "{} must be between {} and {}, got {}",
+                key, min, max, number
+            )));
+        }
+    } else if let Some((_, min, max)) = FLOAT_SETTINGS.iter().find(|(k, _, _)| *k == key) {
+        let number: f32 = value
+            .parse()
+            .ok()
+            .filter(|n: &f32| n.is_finite())
+            .ok_or_else(|| {
+                AppError::Validation(format!("{} must be a number, got \"{}\"", key, value))
+            })?;
+        if !(*min..=*max).contains(&number) {
+            return Err(AppError::Validation(format!(
+                "{} must be between {} and {}, got {}",
+                key, min, max, number
+            )));
+        }
+    } else if BOOL_SETTINGS.contains(&key) && parse_bool(value).is_none() {
+        return Err(AppError::Validation(format!(
+            "{} must be true or false, got \"{}\"",
+            key, value
+        )));
+    } else if let Some((_, choices)) = CHOICE_SETTINGS.iter().find(|(k, _)| *k == key) {
+        if !choices.contains(&value) {
+            return Err(AppError::Validation(format!(
+                "{} must be one of {}, got \"{}\"",
+                key,
+                choices.join(", "),
+                value
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Get a setting as trimmed text; empty values count as unset
+pub fn get_setting_string(conn: &Connection, key: &str) -> Result<Option<String>, AppError> {
+    Ok(get_setting(conn, key)?
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty()))
+}
+
+/// Get a numeric setting, failing with a validation error on a malformed or out-of-range value
+pub fn get_setting_i32(conn: &Connection, key: &str) -> Result<Option<i32>, AppError> {
+    match get_setting_string(conn, key)? {
+        Some(value) => {
+            validate_setting(key, &value)?;
+            value.parse().map(Some).map_err(|_| {
+                AppError::Validation(format!("{} must be a whole number, got \"{}\"", key, value))
+            })
+        }
+        None => Ok(None),
+    }
+}
+
+/// Get a decimal setting, failing with a validation error on a malformed or out-of-range value
+pub fn get_setting_f32(conn: &Connection, key: &str) -> Result<Option<f32>, AppError> {
+    match get_setting_string(conn, key)? {
+        Some(value) => {
+            validate_setting(key, &value)?;
+            value.parse().map(Some).map_err(|_| {
+                AppError::Validation(format!("{} must be a number, got \"{}\"", key, value))
+            })
+        }
+        None => Ok(None),
+    }
+}
+
+/// Get a boolean setting, failing with a validation error on anything but true/false
+pub fn get_setting_bool(conn: &Connection, key: &str) -> Result<Option<bool>, AppError> {
+    match get_setting_string(conn, key)? {
+        Some(value) => parse_bool(&value).map(Some).ok_or_else(|| {
+            AppError::Validation(format!("{} must be true or false, got \"{}\"", key, value))
+        }),
+        None => Ok(None),
+    }
+}
+
 /// Get a setting value by key
 pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>, AppError> {
     let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?")?;
     let result = stmt.query_row([key], |row| row.get::<_, String>(0));
 
     match result {
-        Ok(value) => Ok(Some(value)),
+        Ok(value) => Ok(reveal(key, value)),
         Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
         Err(e) => Err(AppError::Database(e.to_string())),
     }
 }
 
-/// Set a setting value (insert or update)
+/// Set a setting value (insert or update); sensitive keys are encrypted at rest.
+/// Values for known typed settings are validated first.
 pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<(), AppError> {
+    validate_setting(key, value)?;
+
+    let stored;
+    let value = if secrets::is_sensitive(key) && !value.is_empty() {
+        stored = secrets::encrypt(value)?;
+        stored.as_str()
+    } else {
+        value
+    };
+
     conn.execute(
         r#"
         INSERT INTO settings (key, value, updated_at)
@@ -28,15 +184,24 @@ pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<(), AppE
     Ok(())
 }
 
-/// Get all settings as a list of (key, value) pairs
-pub fn get_all_settings(conn: &Connection) -> Result<Vec<(String, String)>, AppError> {
-    let mut stmt = conn.prepare("SELECT key, value FROM settings ORDER BY key")?;
-    let settings = stmt
+/// Encrypt sensitive settings that were stored as plaintext by older versions
+pub fn encrypt_plaintext_secrets(conn: &Connection) -> Result<(), AppError> {
+    let mut stmt = conn.prepare("SELECT key, value FROM settings")?;
+    let plaintext: Vec<(String, String)> = stmt
         .query_map([], |row| {
             Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
         })?
-        .collect::<Result<Vec<_>, _>>()?;
-    Ok(settings)
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|(key, value)| {
+            secrets::is_sensitive(key) && !value.is_empty() && !secrets::is_encrypted(value)
+        })
+        .collect();
+
+    for (key, value) in plaintext {
+        set_setting(conn, &key, &value)?;
+    }
+    Ok(())
 }
 
 /// Delete a setting by key
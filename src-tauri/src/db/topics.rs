@@ -2,7 +2,7 @@ use rusqlite::{params, Connection};
 use uuid::Uuid;
 
 use crate::error::AppError;
-use crate::models::{CreateTopicInput, Topic, UpdateTopicInput};
+use crate::models::{CreateTopicInput, Topic, TopicWithCount, UpdateTopicInput};
 
 pub fn get_topics(conn: &Connection) -> Result<Vec<Topic>, AppError> {
     let mut stmt = conn.prepare(
@@ -28,6 +28,38 @@ pub fn get_topics(conn: &Connection) -> Result<Vec<Topic>, AppError> {
     Ok(topics)
 }
 
+pub fn get_topics_with_counts(conn: &Connection) -> Result<Vec<TopicWithCount>, AppError> {
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.name, t.color, t.icon, t.sort_order, t.parent_id, t.created_at, t.updated_at,
+                COUNT(p.id)
+         FROM topics t
+         LEFT JOIN folders f ON f.topic_id = t.id
+         LEFT JOIN papers p ON p.folder_id = f.id AND p.deleted_at IS NULL
+         GROUP BY t.id
+         ORDER BY t.sort_order ASC",
+    )?;
+
+    let topics = stmt
+        .query_map([], |row| {
+            Ok(TopicWithCount {
+                topic: Topic {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    color: row.get(2)?,
+                    icon: row.get(3)?,
+                    sort_order: row.get(4)?,
+                    parent_id: row.get(5)?,
+                    created_at: row.get(6)?,
+                    updated_at: row.get(7)?,
+                },
+                paper_count: row.get(8)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(topics)
+}
+
 pub fn get_topic(conn: &Connection, topic_id: &str) -> Result<Topic, AppError> {
     let mut stmt = conn.prepare(
         "SELECT id, name, color, icon, sort_order, parent_id, created_at, updated_at 
@@ -98,6 +130,25 @@ pub fn update_topic(
     get_topic(conn, topic_id)
 }
 
+pub fn reorder_topics(conn: &Connection, ordered_ids: &[String]) -> Result<Vec<Topic>, AppError> {
+    let tx = conn.unchecked_transaction()?;
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+    for (index, topic_id) in ordered_ids.iter().enumerate() {
+        let affected = tx.execute(
+            "UPDATE topics SET sort_order = ?, updated_at = ? WHERE id = ?",
+            params![index as i32, now, topic_id],
+        )?;
+
+        if affected == 0 {
+            return Err(AppError::NotFound(format!("Topic not found: {}", topic_id)));
+        }
+    }
+
+    tx.commit()?;
+    get_topics(conn)
+}
+
 pub fn delete_topic(conn: &Connection, topic_id: &str) -> Result<(), AppError> {
     // Check if topic exists
     get_topic(conn, topic_id)?;
@@ -3,7 +3,7 @@ use uuid::Uuid;
 
 use crate::error::AppError;
 use crate::models::{
-    CreateWritingDocumentInput, CreateWritingProjectInput, MoveWritingDocumentInput,
+    CreateWritingDocumentInput, CreateWritingProjectInput, Highlight, MoveWritingDocumentInput,
     UpdateWritingDocumentInput, UpdateWritingProjectInput, WritingDocument, WritingProject,
     WritingProjectMetadata,
 };
@@ -355,6 +355,78 @@ pub fn move_writing_document(
     get_writing_document(conn, document_id)
 }
 
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Append a highlight to a document's HTML content as a blockquote, followed
+/// by its note and `source` (e.g. the paper title), and record the link so the
+/// highlight's uses can be found later
+pub fn append_highlight(
+    conn: &Connection,
+    document_id: &str,
+    highlight: &Highlight,
+    source: &str,
+) -> Result<WritingDocument, AppError> {
+    let document = get_writing_document(conn, document_id)?;
+    if document.content_type != "text" {
+        return Err(AppError::Validation(
+            "Highlights can only be inserted into text documents".to_string(),
+        ));
+    }
+
+    let mut quote = format!("<p>{}</p>", escape_html(highlight.selected_text.trim()));
+    if !highlight.note.trim().is_empty() {
+        quote.push_str(&format!("<p><em>{}</em></p>", escape_html(highlight.note.trim())));
+    }
+    quote.push_str(&format!(
+        "<p>— {}, p. {}</p>",
+        escape_html(source),
+        highlight.page_number
+    ));
+    let content = format!("{}<blockquote>{}</blockquote>", document.content, quote);
+    let word_count = document.word_count + highlight.selected_text.split_whitespace().count() as i32;
+
+    let tx = conn.unchecked_transaction()?;
+    let document = update_writing_document(
+        &tx,
+        document_id,
+        UpdateWritingDocumentInput {
+            content: Some(content),
+            word_count: Some(word_count),
+            ..Default::default()
+        },
+    )?;
+    tx.execute(
+        "INSERT OR IGNORE INTO writing_document_highlights (document_id, highlight_id) VALUES (?, ?)",
+        params![document_id, highlight.id],
+    )?;
+    tx.commit()?;
+
+    Ok(document)
+}
+
+/// Documents a highlight has been inserted into
+pub fn get_highlight_documents(
+    conn: &Connection,
+    highlight_id: &str,
+) -> Result<Vec<WritingDocument>, AppError> {
+    let query = format!(
+        r#"SELECT {} FROM writing_documents
+           WHERE id IN (SELECT document_id FROM writing_document_highlights WHERE highlight_id = ?)
+           ORDER BY updated_at DESC"#,
+        DOCUMENT_SELECT_COLUMNS
+    );
+    let mut stmt = conn.prepare(&query)?;
+    let documents = stmt
+        .query_map([highlight_id], row_to_document)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(documents)
+}
+
 // ============================================================================
 // Export Operations
 // ============================================================================
@@ -1,3 +1,4 @@
+use serde::ser::SerializeStruct;
 use serde::Serialize;
 use thiserror::Error;
 
@@ -26,14 +27,42 @@ pub enum AppError {
 
     #[error("Analysis error: {0}")]
     Analysis(String),
+
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("PDF is encrypted: {0}")]
+    PdfEncrypted(String),
+}
+
+impl AppError {
+    /// Machine-readable error code for the frontend
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Database(_) => "database",
+            AppError::NotFound(_) => "not_found",
+            AppError::Io(_) => "io",
+            AppError::Validation(_) => "validation",
+            AppError::Auth(_) => "auth",
+            AppError::Network(_) => "network",
+            AppError::Parse(_) => "parse",
+            AppError::Analysis(_) => "analysis",
+            AppError::RateLimited(_) => "rate_limited",
+            AppError::PdfEncrypted(_) => "pdf_encrypted",
+        }
+    }
 }
 
+/// Serialized as `{ code, message }` so the UI can react to the error kind
 impl Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
     }
 }
 
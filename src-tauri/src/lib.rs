@@ -7,6 +7,7 @@ use tauri::Manager;
 
 use db::DbConnection;
 use commands::automation::WatchFolderState;
+use commands::cancellation::CancellationState;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -25,10 +26,25 @@ pub fn run() {
             let db = DbConnection::new(&db_path)
                 .expect("Failed to create database connection");
 
+            // Load the key used to encrypt credentials stored in settings
+            db::secrets::init(&app_data).expect("Failed to load encryption key");
+
             // Run migrations
             {
                 let conn = db.get().expect("Failed to get database connection");
                 db::migrations::run(&conn).expect("Failed to run migrations");
+                db::settings::encrypt_plaintext_secrets(&conn)
+                    .expect("Failed to encrypt stored secrets");
+
+                // Older versions stored absolute PDF paths; keep them portable across machines
+                if let Err(e) = commands::pdf::relativize_pdf_paths(&conn, &app_data) {
+                    log::warn!("Failed to convert PDF paths: {}", e);
+                }
+
+                // Load network timeout/proxy settings for outgoing requests
+                if let Err(e) = commands::http::load_settings(&conn) {
+                    log::warn!("Failed to load HTTP settings: {}", e);
+                }
             }
 
             // Store database connection in app state
@@ -37,6 +53,15 @@ pub fn run() {
             // Initialize watch folder state
             app.manage(WatchFolderState::default());
 
+            // Initialize cancellation state for batch operations
+            app.manage(CancellationState::default());
+
+            // Run auto-analysis requests one at a time
+            app.manage(commands::ai_analysis::AnalysisQueue::start(app.handle().clone()));
+
+            // Back up to Google Drive on the configured schedule
+            commands::google_drive::spawn_auto_backup(app.handle().clone());
+
             log::info!("Paper Manager initialized with database at {:?}", db_path);
 
             Ok(())
@@ -44,29 +69,55 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             // Topics
             commands::topics::get_topics,
+            commands::topics::get_topics_with_counts,
             commands::topics::get_topic,
             commands::topics::create_topic,
             commands::topics::update_topic,
             commands::topics::delete_topic,
+            commands::topics::reorder_topics,
             // Folders
             commands::folders::get_folders,
+            commands::folders::get_folders_with_counts,
             commands::folders::get_all_folders,
             commands::folders::get_folder,
             commands::folders::create_folder,
             commands::folders::update_folder,
             commands::folders::delete_folder,
+            commands::folders::reorder_folders,
+            commands::folders::move_folder,
             // Papers
             commands::papers::get_papers,
+            commands::papers::count_papers,
             commands::papers::get_paper,
             commands::papers::create_paper,
             commands::papers::update_paper,
             commands::papers::delete_paper,
+            commands::papers::restore_paper,
+            commands::papers::get_trashed_papers,
+            commands::papers::empty_trash,
             commands::papers::check_duplicate,
+            commands::papers::touch_paper_opened,
+            commands::papers::get_recently_opened,
+            commands::papers::find_related_local,
+            commands::papers::suggest_tags,
             commands::papers::batch_update_papers,
             commands::papers::batch_delete_papers,
+            commands::papers::batch_set_read,
+            commands::papers::batch_set_importance,
+            commands::papers::get_papers_by_author,
             // PDF
             commands::pdf::import_pdf,
             commands::pdf::get_pdf_as_base64,
+            commands::pdf::resolve_pdf_file,
+            commands::pdf::verify_pdf_paths,
+            commands::pdf::relink_pdfs,
+            commands::pdf::import_directory,
+            commands::pdf::get_pdf_size,
+            commands::pdf::get_pdf_info,
+            commands::pdf_edit::merge_pdfs,
+            commands::pdf_edit::extract_pdf_pages,
+            commands::pdf::get_pdf_byte_range,
+            commands::pdf::generate_pdf_thumbnail,
             commands::pdf::delete_pdf,
             commands::pdf::get_pdf_storage_path,
             // Settings
@@ -75,6 +126,10 @@ pub fn run() {
             commands::settings::set_setting,
             commands::settings::update_settings,
             commands::settings::delete_setting,
+            commands::diagnostics::get_diagnostics,
+            commands::links::validate_paper_links,
+            commands::links::validate_all_links,
+            commands::undo::undo_last_operation,
             // Google OAuth
             commands::google_auth::start_google_oauth,
             commands::google_auth::handle_google_oauth_callback,
@@ -84,40 +139,73 @@ pub fn run() {
             commands::google_auth::start_oauth_server,
             // Paper Search
             commands::paper_search::search_papers,
+            commands::paper_search::get_search_history,
+            commands::paper_search::clear_search_history,
+            commands::paper_search::search_multiple_sources,
+            commands::paper_search::get_search_sources,
             commands::paper_search::get_paper_details,
             commands::paper_search::search_by_doi,
             commands::paper_search::search_by_arxiv,
             commands::paper_search::get_paper_recommendations,
+            commands::paper_search::get_paper_references,
+            commands::paper_search::get_paper_citations,
+            commands::paper_search::get_citation_graph,
+            commands::paper_search::enrich_paper_from_doi,
             // Google Drive
             commands::google_drive::backup_to_drive,
             commands::google_drive::restore_from_drive,
             commands::google_drive::get_sync_status,
             commands::google_drive::list_drive_files,
+            commands::google_drive::describe_drive_backup,
             // AI Analysis
             commands::ai_analysis::analyze_paper,
+            commands::ai_analysis::analyze_papers_batch,
             commands::ai_analysis::summarize_text,
+            commands::ai_analysis::summarize_paper,
+            commands::ai_analysis::ask_paper,
+            commands::ai_analysis::compare_papers,
             commands::ai_analysis::translate_text,
+            commands::ai_analysis::detect_and_translate_abstract,
+            commands::keywords::extract_keywords,
             // Highlights
             commands::highlights::get_highlights,
+            commands::highlights::get_highlight_counts,
             commands::highlights::get_highlight,
             commands::highlights::create_highlight,
             commands::highlights::update_highlight,
             commands::highlights::delete_highlight,
+            commands::highlights::export_highlights_json,
+            commands::highlights::import_highlights_json,
             // PDF Indexing & Full-Text Search
             commands::pdf_indexing::index_paper,
             commands::pdf_indexing::index_all_papers,
+            commands::pdf_indexing::get_indexing_progress,
             commands::pdf_indexing::search_full_text,
             commands::pdf_indexing::get_paper_index_status,
+            commands::pdf_indexing::rebuild_search_index,
+            commands::pdf_indexing::rebuild_fts_index,
             // Citations
             commands::citations::export_bibtex,
             commands::citations::export_bibtex_batch,
             commands::citations::export_ris,
             commands::citations::export_ris_batch,
+            commands::citations::export_folder_bibtex,
+            commands::citations::regenerate_citation_key,
+            commands::citations::export_folder_ris,
+            commands::citations::export_papers_csv,
+            commands::markdown::export_paper_markdown,
+            commands::markdown::export_library_obsidian,
+            commands::citations::import_bibtex,
+            commands::citations::import_ris,
             commands::citations::generate_citation,
             commands::citations::generate_citation_batch,
+            commands::citations::export_annotated_bibliography,
             commands::citations::get_citation_styles,
+            commands::citations::get_default_citation_style,
+            commands::citations::set_default_citation_style,
             // Automation - Smart Groups
             commands::automation::get_smart_group_papers,
+            commands::automation::get_smart_group_count,
             commands::automation::get_predefined_smart_groups,
             commands::automation::create_smart_group,
             commands::automation::get_smart_groups,
@@ -127,10 +215,12 @@ pub fn run() {
             commands::automation::get_watch_folders,
             commands::automation::delete_watch_folder,
             commands::automation::toggle_watch_folder,
+            commands::automation::set_watch_folder_post_import_action,
             commands::automation::start_watching,
             commands::automation::stop_watching,
             commands::automation::scan_watch_folder,
             commands::automation::import_from_watch_folder,
+            commands::automation::get_watch_folder_history,
             // Automation - PDF Auto-Rename
             commands::automation::generate_paper_filename,
             commands::automation::rename_paper_pdf,
@@ -138,6 +228,7 @@ pub fn run() {
             commands::automation::get_rename_config,
             commands::automation::save_rename_config,
             commands::automation::preview_rename,
+            commands::automation::export_pdfs,
             // Writing - Projects
             commands::writing::get_writing_projects,
             commands::writing::get_writing_project,
@@ -152,8 +243,12 @@ pub fn run() {
             commands::writing::update_writing_document,
             commands::writing::delete_writing_document,
             commands::writing::move_writing_document,
+            commands::writing::insert_highlight_into_document,
+            commands::writing::get_highlight_documents,
             // Writing - Export
             commands::writing::export_project_markdown,
+            // Operations
+            commands::cancellation::cancel_operation,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
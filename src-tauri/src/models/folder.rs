@@ -7,15 +7,30 @@ pub struct Folder {
     pub topic_id: String,
     pub name: String,
     pub sort_order: i32,
+    /// Importance given to papers imported into this folder
+    pub default_importance: Option<i32>,
+    /// Tags given to papers imported into this folder
+    pub default_tags: Vec<String>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// A folder together with the number of papers it contains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FolderWithCount {
+    #[serde(flatten)]
+    pub folder: Folder,
+    pub paper_count: i32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateFolderInput {
     pub topic_id: String,
     pub name: String,
+    pub default_importance: Option<i32>,
+    pub default_tags: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,4 +38,7 @@ pub struct CreateFolderInput {
 pub struct UpdateFolderInput {
     pub name: Option<String>,
     pub sort_order: Option<i32>,
+    /// `0` clears the default
+    pub default_importance: Option<i32>,
+    pub default_tags: Option<Vec<String>>,
 }
@@ -14,6 +14,17 @@ pub struct Paper {
     pub title: String,
     pub publisher: String,
     pub subject: String,
+    /// Machine translation of the abstract (or subject) into the user's language
+    pub subject_translated: String,
+    pub doi: String,
+    #[serde(rename = "abstract")]
+    pub abstract_text: String,
+    /// Landing page or PDF link, e.g. from the search result it was added from
+    pub url: String,
+    /// BibTeX entry type such as `inproceedings`; empty uses the export default
+    pub bibtex_type: String,
+    /// BibTeX key, fixed once assigned so edits don't break references to it
+    pub citation_key: String,
 
     // Research design
     pub purposes: Vec<String>,
@@ -46,11 +57,44 @@ pub struct Paper {
     pub tags: Vec<String>,
     pub is_read: bool,
     pub importance: i32,
+    /// Number of highlights on the paper, counted when it is loaded
+    #[serde(default)]
+    pub highlight_count: i32,
 
     // Timestamps
     pub created_at: String,
     pub updated_at: String,
     pub last_analyzed_at: Option<String>,
+    pub last_opened_at: Option<String>,
+    /// Set while the paper is in the trash
+    pub deleted_at: Option<String>,
+}
+
+/// Entry types a paper can be exported to BibTeX as
+pub const BIBTEX_ENTRY_TYPES: &[&str] = &[
+    "article",
+    "book",
+    "booklet",
+    "inbook",
+    "incollection",
+    "inproceedings",
+    "manual",
+    "mastersthesis",
+    "misc",
+    "phdthesis",
+    "proceedings",
+    "techreport",
+    "unpublished",
+];
+
+/// Optional filters applied when listing papers
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PaperFilter {
+    pub folder_id: Option<String>,
+    pub is_read: Option<bool>,
+    pub min_importance: Option<i32>,
+    pub tag: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,6 +118,11 @@ pub struct UpdatePaperInput {
     pub title: Option<String>,
     pub publisher: Option<String>,
     pub subject: Option<String>,
+    pub doi: Option<String>,
+    #[serde(rename = "abstract")]
+    pub abstract_text: Option<String>,
+    pub url: Option<String>,
+    pub bibtex_type: Option<String>,
     pub purposes: Option<Vec<String>>,
     pub is_qualitative: Option<bool>,
     pub is_quantitative: Option<bool>,
@@ -96,3 +145,41 @@ pub struct UpdatePaperInput {
     pub importance: Option<i32>,
     pub last_analyzed_at: Option<String>,
 }
+
+/// A paper whose stored PDF path no longer points to an existing file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingPdf {
+    pub paper_id: String,
+    pub title: String,
+    pub pdf_path: String,
+}
+
+/// Progress of a bulk directory import, sent once per file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportDirectoryProgress {
+    pub current: usize,
+    pub total: usize,
+    pub file_path: String,
+    /// `imported`, `skipped` (already in the library) or `failed`
+    pub status: String,
+    pub error: Option<String>,
+}
+
+/// Counts reported when a bulk directory import finishes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportDirectorySummary {
+    pub imported: usize,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+/// Outcome of relinking missing PDFs against a new base directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelinkResult {
+    pub relinked: i32,
+    pub still_missing: Vec<MissingPdf>,
+}
@@ -11,6 +11,17 @@ pub enum SearchSource {
     GoogleScholar,
 }
 
+impl SearchSource {
+    pub const ALL: [SearchSource; 6] = [
+        SearchSource::SemanticScholar,
+        SearchSource::PubMed,
+        SearchSource::Crossref,
+        SearchSource::Arxiv,
+        SearchSource::Kci,
+        SearchSource::GoogleScholar,
+    ];
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchResult {
@@ -25,6 +36,9 @@ pub struct SearchResult {
     pub url: Option<String>,
     pub open_access_pdf: Option<OpenAccessPdf>,
     pub external_ids: Option<ExternalIds>,
+    /// One-sentence summary from Semantic Scholar, when available
+    #[serde(default)]
+    pub tldr: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -50,7 +64,7 @@ pub struct ExternalIds {
     pub pubmed_central: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SearchQuery {
     pub query: String,
@@ -59,6 +73,17 @@ pub struct SearchQuery {
     pub offset: Option<i32>,
     pub year: Option<String>,
     pub fields_of_study: Option<Vec<String>>,
+    // Field-specific queries, used by Crossref only; other sources ignore them
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub title_query: Option<String>,
+    #[serde(default)]
+    pub bibliographic: Option<String>,
+    /// Page through Semantic Scholar, Crossref or PubMed results until `limit`
+    /// (or every result, up to a safety ceiling) has been collected
+    #[serde(default)]
+    pub fetch_all: Option<bool>,
 }
 
 #[derive(Debug, Serialize)]
@@ -67,3 +92,66 @@ pub struct SearchResponse {
     pub total: i32,
     pub results: Vec<SearchResult>,
 }
+
+/// How one source fared in a multi-source search
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceStat {
+    pub source: SearchSource,
+    /// Results returned, before duplicates were merged
+    pub count: usize,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultiSearchResponse {
+    pub total: i32,
+    pub results: Vec<SearchResult>,
+    pub sources: Vec<SourceStat>,
+}
+
+/// What a search source can do, so the UI can hide filters it ignores
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchSourceInfo {
+    pub source: SearchSource,
+    pub display_name: String,
+    pub supports_year_filter: bool,
+    pub supports_fields_of_study: bool,
+    /// The API can order results itself (by date or citations)
+    pub supports_server_sort: bool,
+    /// Searching fails without a key
+    pub requires_api_key: bool,
+    pub api_key_configured: bool,
+    /// Setting that holds the key, when it is set from the settings dialog
+    pub api_key_setting: Option<String>,
+}
+
+/// A logged `search_papers` call, for a recent-searches list
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHistoryEntry {
+    pub id: i64,
+    pub query: String,
+    pub source: SearchSource,
+    pub result_count: i32,
+    pub searched_at: String,
+}
+
+/// A library paper identified by DOI, used as input for citation graph export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationGraphNode {
+    pub paper_id: String,
+    pub doi: String,
+}
+
+/// A citation between two library papers: `from_paper_id` cites `to_paper_id`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationEdge {
+    pub from_paper_id: String,
+    pub to_paper_id: String,
+}
@@ -19,6 +19,10 @@ pub struct FullTextSearchResult {
     pub page_number: i32,
     pub snippet: String,
     pub rank: f64,
+    /// Ranges `(start, end)` of each match within the page text, in UTF-16 code
+    /// units like JavaScript string indices; present when requested with `include_offsets`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offsets: Option<Vec<(usize, usize)>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +32,21 @@ pub struct FullTextSearchQuery {
     pub limit: Option<i32>,
     pub offset: Option<i32>,
     pub folder_id: Option<String>,
+    /// Restrict results to a single paper
+    #[serde(default)]
+    pub paper_id: Option<String>,
+    /// Also return where on the page each match is, for jump-to-hit
+    #[serde(default)]
+    pub include_offsets: Option<bool>,
+    /// Snippet length in tokens, 32 by default
+    #[serde(default)]
+    pub snippet_tokens: Option<i32>,
+    /// Inserted before and after each match in the snippet, `<mark>` and
+    /// `</mark>` by default; empty strings give plain text
+    #[serde(default)]
+    pub mark_start: Option<String>,
+    #[serde(default)]
+    pub mark_end: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,4 +64,50 @@ pub struct IndexingStatus {
     pub indexed_pages: i32,
     pub is_complete: bool,
     pub error: Option<String>,
+    /// Pages whose text could not be extracted; the rest are still indexed
+    #[serde(default)]
+    pub failed_pages: Vec<i32>,
+}
+
+/// Result of rebuilding the full-text index from `pdf_pages`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FtsRebuildReport {
+    /// Rows in `pdf_pages`
+    pub page_count: i64,
+    /// Documents in the FTS index after the rebuild
+    pub indexed_count: i64,
+    /// Whether the counts match and FTS5's integrity check passed
+    pub in_sync: bool,
+}
+
+/// How many papers with a PDF have been indexed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexingProgress {
+    pub indexed: i32,
+    pub total: i32,
+}
+
+/// Bibliographic metadata read directly from a PDF file, without AI
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub keywords: Option<String>,
+    pub year: Option<i32>,
+    pub doi: Option<String>,
+}
+
+/// Basic properties of a PDF file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfInfo {
+    pub page_count: u32,
+    pub is_encrypted: bool,
+    /// Whether the first page yields extractable text; scanned PDFs need OCR
+    /// before they are searchable
+    pub has_text_layer: bool,
+    pub file_size: u64,
 }
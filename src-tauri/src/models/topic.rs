@@ -13,6 +13,15 @@ pub struct Topic {
     pub updated_at: String,
 }
 
+/// A topic together with the number of papers across all of its folders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicWithCount {
+    #[serde(flatten)]
+    pub topic: Topic,
+    pub paper_count: i32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateTopicInput {